@@ -0,0 +1,206 @@
+use std::{fs::File, io::{self, BufWriter, Write}};
+
+use clap::ValueEnum;
+use miniz_oxide::{
+    DataFormat, MZFlush, MZStatus,
+    deflate::{core::CompressorOxide, stream::deflate},
+};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::{errors::io::IoError, output::write_json};
+
+/// The compression applied to output written to a file
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// gzip (`.json.gz`)
+    Gzip,
+}
+
+/// the suffix of Zstandard compressed output which -- with no Zstandard
+/// support in this build -- can't be written
+const ZSTD_SUFFIX: &str = ".zst";
+
+impl Compression {
+    /// the compression implied by the suffix of an output path
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with(".gz") {
+            Some(Compression::Gzip)
+        } else {
+            None
+        }
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!crc, |c, b| CRC32_TABLE[((c ^ *b as u32) & 0xFF) as usize] ^ (c >> 8))
+}
+
+/// A writer which gzip compresses everything written to it as it goes so
+/// that large output is never held in memory twice; `finish` must be called
+/// to write the gzip trailer.
+pub struct GzipWriter<W: Write> {
+    inner: W,
+    compressor: Box<CompressorOxide>,
+    buffer: Vec<u8>,
+    crc: u32,
+    size: u32,
+}
+
+impl<W: Write> GzipWriter<W> {
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        // magic, deflate, no flags, no mtime, no extra flags, unknown OS
+        inner.write_all(&[0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255])?;
+        let mut compressor = Box::<CompressorOxide>::default();
+        compressor.set_format_and_level(DataFormat::Raw, 6);
+
+        Ok(GzipWriter { inner, compressor, buffer: vec![0; 64 * 1024], crc: 0, size: 0 })
+    }
+
+    fn compress(&mut self, mut input: &[u8], flush: MZFlush) -> io::Result<()> {
+        loop {
+            let result = deflate(&mut self.compressor, input, &mut self.buffer, flush);
+            let status = result.status.map_err(|e| io::Error::other(format!("{:?}", e)))?;
+            self.inner.write_all(&self.buffer[..result.bytes_written])?;
+            input = &input[result.bytes_consumed..];
+
+            let done = match flush {
+                MZFlush::Finish => status == MZStatus::StreamEnd,
+                _ => input.is_empty() && result.bytes_written < self.buffer.len(),
+            };
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// compresses any remaining input, writes the gzip trailer and returns
+    /// the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.compress(&[], MZFlush::Finish)?;
+        self.inner.write_all(&self.crc.to_le_bytes())?;
+        self.inner.write_all(&self.size.to_le_bytes())?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for GzipWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.compress(buf, MZFlush::None)?;
+        self.crc = crc32_update(self.crc, buf);
+        self.size = self.size.wrapping_add(buf.len() as u32);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes the JSON output to the file at `path`, streaming it through the
+/// given compression; when no compression is given it is inferred from the
+/// path's suffix (e.g., `report.json.gz`). A `.zst` path is always
+/// rejected as it could only be written uncompressed or -- with an explicit
+/// compression -- in a format its suffix doesn't match.
+pub fn write_output(
+    path: &str,
+    output: &Value,
+    compression: Option<Compression>,
+    indent: Option<usize>
+) -> Result<(), IoError> {
+    if path.ends_with(ZSTD_SUFFIX) {
+        return Err(IoError::UnsupportedCompression("zstd".to_string()));
+    }
+    let compression = compression.or_else(|| Compression::from_path(path));
+
+    let failed = |e| IoError::WriteFailedWith(path.to_string(), e);
+    let file = BufWriter::new(File::create(path).map_err(failed)?);
+
+    match compression {
+        Some(_) => {
+            let mut writer = GzipWriter::new(file).map_err(failed)?;
+            write_json(&mut writer, output, indent).map_err(failed)?;
+            writer.finish().map_err(failed)?;
+        }
+        None => {
+            let mut writer = file;
+            write_json(&mut writer, output, indent).map_err(failed)?;
+            writer.flush().map_err(failed)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miniz_oxide::inflate::decompress_to_vec;
+    use serde_json::json;
+
+    #[test]
+    fn gzip_output_round_trips() {
+        let content = "the lumberjack song ".repeat(5_000);
+        let mut writer = GzipWriter::new(vec![]).unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+        let gz = writer.finish().unwrap();
+
+        assert_eq!(&gz[..2], &[0x1f, 0x8b]);
+        assert!(gz.len() < content.len() / 10);
+        let trailer = &gz[gz.len() - 8..];
+        let inflated = decompress_to_vec(&gz[10..gz.len() - 8]).unwrap();
+        assert_eq!(inflated, content.as_bytes());
+        assert_eq!(u32::from_le_bytes(trailer[..4].try_into().unwrap()), crc32_update(0, content.as_bytes()));
+        assert_eq!(u32::from_le_bytes(trailer[4..].try_into().unwrap()), content.len() as u32);
+    }
+
+    #[test]
+    fn crc32_matches_known_value() {
+        assert_eq!(crc32_update(0, b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn compression_is_detected_from_suffix() {
+        assert_eq!(Compression::from_path("out.json.gz"), Some(Compression::Gzip));
+        assert_eq!(Compression::from_path("out.json.zst"), None);
+        assert_eq!(Compression::from_path("out.json"), None);
+        assert!(matches!(
+            write_output("out.json.zst", &json!([]), None, None),
+            Err(IoError::UnsupportedCompression(_))
+        ));
+        assert!(matches!(
+            write_output("out.json.zst", &json!([]), Some(Compression::Gzip), None),
+            Err(IoError::UnsupportedCompression(_))
+        ));
+        assert!(Compression::from_str("zstd", true).is_err());
+    }
+
+    #[test]
+    fn write_failures_keep_the_io_error() {
+        let path = std::env::temp_dir().join(format!("ctx-missing-{}", std::process::id())).join("out.json");
+        let err = write_output(&path.to_string_lossy(), &json!([]), None, None).unwrap_err();
+
+        assert!(matches!(&err, IoError::WriteFailedWith(_, e) if e.kind() == io::ErrorKind::NotFound));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
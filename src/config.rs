@@ -0,0 +1,393 @@
+use std::{collections::BTreeMap, env, fs::read_to_string, path::Path, time::Duration};
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    errors::config::ConfigError,
+    md::{frontmatter::{FmFence, default_fences}, links::DEFAULT_POOR_LINK_TEXT, quality::QualityWeights, typography::Typography},
+    output::SortField,
+    remote::RetryPolicy,
+};
+
+/// the configuration file which is looked for in the current directory
+pub const CONFIG_FILE: &str = "ctx.toml";
+/// the prefix of environment variables which set configuration
+pub const ENV_PREFIX: &str = "CTX_";
+
+/// Where the effective value of a configuration property came from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+/// **Config**
+/// 
+/// The effective configuration which governs how targets are processed;
+/// this is the result of merging -- in order of precedence -- the defaults,
+/// the `ctx.toml` file, `CTX_*` environment variables and CLI switches.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Config {
+    /// the boolean frontmatter property which marks a document as a _draft_
+    pub draft_field: String,
+    /// whether documents marked as drafts should be processed
+    pub include_drafts: bool,
+    /// whether documents which set `requires_auth: true` should be skipped
+    pub skip_requires_auth: bool,
+    /// whether documents whose `expires` date has been reached should be
+    /// skipped
+    pub skip_expired: bool,
+    /// whether emoji usage should be extracted from the prose
+    pub emoji: bool,
+    /// documents with more emoji than this are flagged
+    pub emoji_threshold: Option<usize>,
+    /// whether the prose hash should ignore insignificant whitespace
+    pub hash_normalize_whitespace: bool,
+    /// the fences which delimit a frontmatter block; these are tried in order
+    pub fm_fences: Vec<FmFence>,
+    /// whether any warning should cause the run to fail
+    pub strict: bool,
+    /// whether a target which was not recognized should cause the run to fail
+    pub fail_on_unknown: bool,
+    /// whether results should be wrapped in an envelope with tool metadata
+    pub envelope: bool,
+    /// the number of spaces each level of JSON output is indented by; the
+    /// output is compact when this isn't set
+    pub indent: Option<usize>,
+    /// how many directories below each directory target are walked; `0`
+    /// walks only the files immediately within it while `None` has no limit
+    pub max_depth: Option<usize>,
+    /// whether the keys of JSON objects in the output are sorted
+    pub sort_keys: bool,
+    /// the field by which multi-file output is sorted
+    pub sort: Option<SortField>,
+    /// whether the sort order should be reversed
+    pub reverse: bool,
+    /// the number of times a transient failure fetching a remote target
+    /// is retried
+    pub retries: u32,
+    /// the timeout -- in seconds -- of each attempt to fetch a remote target
+    pub timeout: u64,
+    /// whether lint issues in documents should be reported as warnings
+    pub lint: bool,
+    /// link text which is flagged as non-descriptive when linting
+    pub poor_link_text: Vec<String>,
+    /// whether hidden files are included when hashing a directory
+    pub hidden: bool,
+    /// whether Obsidian style `[[wikilinks]]` are extracted from the prose
+    pub obsidian: bool,
+    /// whether the word count of each heading's section is reported
+    pub section_words: bool,
+    /// the typographic normalization applied to quotes and dashes
+    pub typography: Option<Typography>,
+    /// the base URL the documents are served from; when set, each
+    /// document's canonical URL is reported
+    pub url_base: Option<String>,
+    /// the directory -- relative to which canonical URLs are derived -- that
+    /// holds the site's content (the current directory when not set)
+    pub content_root: Option<String>,
+    /// the directory remote responses are cached in so that they can be
+    /// revalidated -- rather than fetched again -- on later runs
+    pub cache: Option<String>,
+    /// the most targets -- after directories are expanded -- which will be
+    /// processed in a single run; `0` means there is no limit
+    pub max_targets: usize,
+    /// whether boolean-like strings (e.g., `"yes"`) in typed boolean
+    /// frontmatter properties are coerced rather than rejected
+    pub lenient_fm: bool,
+    /// whether the markdown in the doc comments of source files is
+    /// extracted and analyzed
+    pub extract_doc_comments: bool,
+    /// whether the readability of each document is reported
+    pub readability: bool,
+    /// whether invalid UTF-8 in a file is replaced -- rather than the file
+    /// failing to load -- with the document flagged as `lossy`
+    pub lossy: bool,
+    /// whether the markdown files a document includes (`{{ include: a.md }}`
+    /// or `![[a.md]]`) are inlined into it before it is analyzed
+    pub resolve_includes: bool,
+    /// whether documents without a frontmatter `description` are warned
+    /// about when their description is backfilled from the first paragraph
+    pub backfill_fm: bool,
+    /// whether a UTF-8 byte order mark is removed from the start of a file
+    /// before its content is hashed and analyzed
+    pub strip_bom: bool,
+    /// whether the number of characters in each document -- with and
+    /// without whitespace -- is reported
+    pub char_count: bool,
+    /// whether markdown syntax is removed before characters are counted
+    pub strip_syntax: bool,
+    /// whether documents whose prose has fewer than `min_words` words
+    /// (e.g., documents which are all frontmatter) are warned about
+    pub fail_on_empty_prose: bool,
+    /// the fewest words of prose a document may have before it is flagged
+    /// by `fail_on_empty_prose`
+    pub min_words: usize,
+    /// whether raw HTML blocks in the prose fail the run
+    pub fail_on_html: bool,
+    /// whether the frontmatter of each `_index.md` cascades -- as defaults --
+    /// into the other documents in its directory and those below it
+    pub cascade_fm: bool,
+    /// whether hashing of content is skipped (for speed); hashes are then
+    /// omitted from the output
+    pub no_hash: bool,
+    /// the weight of each check which contributes to a document's quality
+    /// score
+    pub quality_weights: QualityWeights,
+    /// the source of each property which was set by something other than
+    /// the defaults
+    #[serde(skip)]
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            draft_field: "draft".to_string(),
+            include_drafts: false,
+            skip_requires_auth: false,
+            skip_expired: false,
+            emoji: false,
+            emoji_threshold: None,
+            hash_normalize_whitespace: false,
+            fm_fences: default_fences(),
+            strict: false,
+            fail_on_unknown: false,
+            envelope: false,
+            indent: None,
+            max_depth: None,
+            sort_keys: false,
+            sort: None,
+            reverse: false,
+            retries: 0,
+            timeout: 30,
+            lint: false,
+            poor_link_text: DEFAULT_POOR_LINK_TEXT.iter().map(|t| t.to_string()).collect(),
+            hidden: false,
+            obsidian: false,
+            section_words: false,
+            typography: None,
+            url_base: None,
+            content_root: None,
+            cache: None,
+            max_targets: 10_000,
+            lenient_fm: false,
+            extract_doc_comments: false,
+            readability: false,
+            lossy: false,
+            resolve_includes: false,
+            backfill_fm: false,
+            strip_bom: false,
+            char_count: false,
+            strip_syntax: false,
+            fail_on_empty_prose: false,
+            min_words: 1,
+            fail_on_html: false,
+            cascade_fm: false,
+            no_hash: false,
+            quality_weights: QualityWeights::default(),
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+/// A configuration layer where only the properties which are set will
+/// override the layers beneath it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PartialConfig {
+    pub draft_field: Option<String>,
+    pub include_drafts: Option<bool>,
+    pub skip_requires_auth: Option<bool>,
+    pub skip_expired: Option<bool>,
+    pub emoji: Option<bool>,
+    pub emoji_threshold: Option<usize>,
+    pub hash_normalize_whitespace: Option<bool>,
+    pub fm_fences: Option<Vec<FmFence>>,
+    pub strict: Option<bool>,
+    pub fail_on_unknown: Option<bool>,
+    pub envelope: Option<bool>,
+    pub indent: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub sort_keys: Option<bool>,
+    pub sort: Option<SortField>,
+    pub reverse: Option<bool>,
+    pub retries: Option<u32>,
+    pub timeout: Option<u64>,
+    pub lint: Option<bool>,
+    pub poor_link_text: Option<Vec<String>>,
+    pub hidden: Option<bool>,
+    pub obsidian: Option<bool>,
+    pub section_words: Option<bool>,
+    pub typography: Option<Typography>,
+    pub url_base: Option<String>,
+    pub content_root: Option<String>,
+    pub cache: Option<String>,
+    pub max_targets: Option<usize>,
+    pub lenient_fm: Option<bool>,
+    pub extract_doc_comments: Option<bool>,
+    pub readability: Option<bool>,
+    pub lossy: Option<bool>,
+    pub resolve_includes: Option<bool>,
+    pub backfill_fm: Option<bool>,
+    pub strip_bom: Option<bool>,
+    pub char_count: Option<bool>,
+    pub strip_syntax: Option<bool>,
+    pub fail_on_empty_prose: Option<bool>,
+    pub min_words: Option<usize>,
+    pub fail_on_html: Option<bool>,
+    pub cascade_fm: Option<bool>,
+    pub no_hash: Option<bool>,
+    pub quality_weights: Option<QualityWeights>,
+}
+
+impl PartialConfig {
+    /// loads the configuration layer from a TOML file
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let content = read_to_string(path)
+            .map_err(|_| ConfigError::FileNotReadable(path.to_string()))?;
+
+        toml::from_str(&content)
+            .map_err(|e| ConfigError::InvalidFile(path.to_string(), e.to_string()))
+    }
+
+    /// Loads the configuration layer from `CTX_*` environment variables
+    /// (e.g., `CTX_DRAFT_FIELD=wip`); values are interpreted as JSON where
+    /// possible and as a string otherwise.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let props: Map<String, Value> = env::vars()
+            .filter_map(|(k, v)| {
+                let key = k.strip_prefix(ENV_PREFIX)?.to_lowercase();
+                let value = serde_json::from_str(&v).unwrap_or(Value::String(v));
+                Some((key, value))
+            })
+            .collect();
+
+        serde_json::from_value(Value::Object(props))
+            .map_err(|e| ConfigError::InvalidEnv(e.to_string()))
+    }
+}
+
+macro_rules! merge_props {
+    ($config:ident, $layer:ident, $source:ident, $($prop:ident),*) => {
+        $(
+            if let Some(value) = $layer.$prop {
+                $config.$prop = value;
+                $config.sources.insert(stringify!($prop).to_string(), $source);
+            }
+        )*
+    };
+}
+
+impl Config {
+    /// merges a configuration layer over this configuration, recording the
+    /// source of every property the layer sets
+    pub fn merge(&mut self, layer: PartialConfig, source: ConfigSource) {
+        if let Some(threshold) = layer.emoji_threshold {
+            self.emoji_threshold = Some(threshold);
+            self.sources.insert("emoji_threshold".to_string(), source);
+        }
+        if let Some(sort) = layer.sort {
+            self.sort = Some(sort);
+            self.sources.insert("sort".to_string(), source);
+        }
+        if let Some(typography) = layer.typography {
+            self.typography = Some(typography);
+            self.sources.insert("typography".to_string(), source);
+        }
+        if let Some(url_base) = layer.url_base {
+            self.url_base = Some(url_base);
+            self.sources.insert("url_base".to_string(), source);
+        }
+        if let Some(content_root) = layer.content_root {
+            self.content_root = Some(content_root);
+            self.sources.insert("content_root".to_string(), source);
+        }
+        if let Some(indent) = layer.indent {
+            self.indent = Some(indent);
+            self.sources.insert("indent".to_string(), source);
+        }
+        if let Some(max_depth) = layer.max_depth {
+            self.max_depth = Some(max_depth);
+            self.sources.insert("max_depth".to_string(), source);
+        }
+        if let Some(cache) = layer.cache {
+            self.cache = Some(cache);
+            self.sources.insert("cache".to_string(), source);
+        }
+        merge_props!(
+            self, layer, source,
+            draft_field, include_drafts, skip_requires_auth, skip_expired, emoji,
+            hash_normalize_whitespace, fm_fences, strict, fail_on_unknown, envelope, sort_keys, reverse, retries, timeout,
+            lint, poor_link_text, hidden, quality_weights, obsidian, section_words,
+            max_targets, lenient_fm, extract_doc_comments, readability, lossy,
+            resolve_includes, backfill_fm, strip_bom,
+            char_count, strip_syntax, fail_on_empty_prose, min_words,
+            fail_on_html, cascade_fm, no_hash
+        );
+    }
+
+    /// Resolves the effective configuration by merging the defaults, the
+    /// `ctx.toml` file (if present), the environment and finally the CLI.
+    pub fn resolve(cli: PartialConfig) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+        if Path::new(CONFIG_FILE).is_file() {
+            config.merge(PartialConfig::from_file(CONFIG_FILE)?, ConfigSource::File);
+        }
+        config.merge(PartialConfig::from_env()?, ConfigSource::Env);
+        config.merge(cli, ConfigSource::Cli);
+
+        Ok(config)
+    }
+
+    /// the retry policy used when fetching remote targets
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            retries: self.retries,
+            timeout: Duration::from_secs(self.timeout),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// the source of the effective value of the given property
+    pub fn source_of(&self, prop: &str) -> ConfigSource {
+        *self.sources.get(prop).unwrap_or(&ConfigSource::Default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let mut config = Config::default();
+        config.merge(
+            PartialConfig { draft_field: Some("wip".to_string()), strict: Some(true), ..PartialConfig::default() },
+            ConfigSource::File
+        );
+        config.merge(
+            PartialConfig { draft_field: Some("hidden".to_string()), ..PartialConfig::default() },
+            ConfigSource::Cli
+        );
+
+        assert_eq!(config.draft_field, "hidden");
+        assert_eq!(config.source_of("draft_field"), ConfigSource::Cli);
+        assert!(config.strict);
+        assert_eq!(config.source_of("strict"), ConfigSource::File);
+        assert_eq!(config.source_of("emoji"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn toml_layer_deserializes() {
+        let layer: PartialConfig = toml::from_str("draft_field = \"wip\"\nemoji_threshold = 3\n").unwrap();
+
+        assert_eq!(layer.draft_field, Some("wip".to_string()));
+        assert_eq!(layer.emoji_threshold, Some(3));
+        assert_eq!(layer.strict, None);
+    }
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("The configuration file \"{0}\" could not be read!")]
+    FileNotReadable(String),
+
+    #[error("The configuration file \"{0}\" is not valid: {1}")]
+    InvalidFile(String, String),
+
+    #[error("The configuration found in the environment is not valid: {0}")]
+    InvalidEnv(String),
+}
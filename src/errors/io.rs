@@ -1,5 +1,9 @@
+use std::io::ErrorKind;
+
 use thiserror::Error;
 
+use super::remote::RemoteError;
+
 #[derive(Error, Debug)]
 pub enum IoError {
     #[error("The file \"{0}\" does not exist!")]
@@ -8,6 +12,91 @@ pub enum IoError {
     #[error("Attempt to treat \"{0}\" as a file failed! The path does exist but it not a file!")]
     PathExistsButNotFile(String),
 
+    #[error("Permission was denied when trying to access \"{0}\"!")]
+    PermissionDenied(String),
+
+    #[error("The directory \"{0}\" could not be read!")]
+    DirectoryNotReadable(String),
+
+    #[error("Attempt to write the file \"{0}\" failed: {1}")]
+    WriteFailedWith(String, #[source] std::io::Error),
+
+    #[error("The file \"{0}\" was written but its content did not match what was expected!")]
+    WriteVerificationFailed(String),
+
+    #[error("Unable to read \"{0}\" at the git ref \"{1}\"!")]
+    GitShowFailed(String, String),
+
     #[error("Attempt to get content of a binary file; this is not implemented yet!")]
-    BinaryContentNotImplemented(String)
+    BinaryContentNotImplemented(String),
+
+    #[error("The \"{0}\" compression format is not supported by this build!")]
+    UnsupportedCompression(String),
+
+    #[error("The source file \"{0}\" is not in a language whose doc comments can be extracted!")]
+    UnsupportedSourceLanguage(String),
+
+    #[error("The notebook \"{0}\" is not valid: {1}")]
+    InvalidNotebook(String, String),
+
+    #[error("The cache \"{0}\" is not valid: {1}")]
+    InvalidCache(String, String),
+
+    #[error("The include of \"{0}\" creates a cycle of includes!")]
+    IncludeCycle(String),
+
+    #[error("The include of \"{0}\" is nested more than {1} includes deep!")]
+    IncludeTooDeep(String, usize),
+
+    #[error("The file \"{0}\" changed on disk since it was loaded!")]
+    FileChanged(String),
+
+    #[error("{0} targets were found which exceeds the limit of {1}; narrow the targets or raise the limit with --max-targets (0 for no limit)")]
+    TooManyTargets(usize, usize),
+
+    #[error("The remote target \"{url}\" could not be fetched: {message}")]
+    NetworkError {
+        url: String,
+        /// the HTTP status of the response, if a response was received
+        status: Option<u16>,
+        message: String,
+    },
+
+    #[error("The remote target \"{0}\" is not a valid URL; only http:// URLs are supported")]
+    InvalidUrl(String),
+}
+
+/// The failure of a remote request -- once any retries are exhausted -- as
+/// it is reported for the target
+impl From<RemoteError> for IoError {
+    fn from(err: RemoteError) -> Self {
+        match err {
+            RemoteError::InvalidUrl(url) | RemoteError::UnsupportedScheme(url) => IoError::InvalidUrl(url),
+            RemoteError::HttpStatus(url, status) => IoError::NetworkError {
+                message: format!("the server responded with an HTTP {} status", status),
+                status: Some(status),
+                url,
+            },
+            RemoteError::Connection(url, message) => IoError::NetworkError { url, status: None, message },
+            RemoteError::InvalidResponse(url) => IoError::NetworkError {
+                url,
+                status: None,
+                message: "the response could not be understood".to_string(),
+            },
+        }
+    }
+}
+
+impl IoError {
+    /// Maps an `std::io::Error` raised while accessing `path` to the
+    /// `IoError` which most accurately describes it; `fallback` is used when
+    /// there is no more specific variant.
+    pub fn from_io(path: &str, err: &std::io::Error, fallback: IoError) -> IoError {
+        match err.kind() {
+            ErrorKind::PermissionDenied => IoError::PermissionDenied(path.to_string()),
+            ErrorKind::NotFound => IoError::FileDoesNotExist(path.to_string()),
+            ErrorKind::InvalidData => IoError::BinaryContentNotImplemented(path.to_string()),
+            _ => fallback,
+        }
+    }
 }
@@ -14,5 +14,13 @@ pub enum MarkdownError {
     #[error("While trying to set the {0} property on Frontmatter we ran into a type error; this property was expected to be a {1}.")]
     PropertyIsWrongType(String, String),
 
+    #[error("The frontmatter property {0} is required but was not set.")]
+    MissingRequiredProperty(String),
+
+    #[error("The frontmatter could not be parsed: {0}")]
+    UnparsableFrontmatter(String),
+
+    #[error("The document has a frontmatter block but it holds no data")]
+    FrontmatterEmpty,
 
 }
@@ -1,3 +1,105 @@
+use thiserror::Error;
+
 pub mod io;
 pub mod md;
 
+pub mod config;
+pub mod remote;
+
+use self::{config::ConfigError, io::IoError, md::MarkdownError, remote::RemoteError};
+
+/// The single error type which any failure in processing a target can be
+/// expressed as; it wraps the more specific error types.
+#[derive(Error, Debug)]
+pub enum ContextError {
+    #[error(transparent)]
+    Io(#[from] IoError),
+
+    #[error(transparent)]
+    Markdown(#[from] MarkdownError),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("while processing '{target}': {source}")]
+    Target {
+        target: String,
+        source: Box<ContextError>,
+    },
+}
+
+/// A failed remote request surfaces as the `IoError::NetworkError` or
+/// `IoError::InvalidUrl` it maps to so that there is a single variant for
+/// each kind of remote failure.
+impl From<RemoteError> for ContextError {
+    fn from(err: RemoteError) -> Self {
+        ContextError::Io(IoError::from(err))
+    }
+}
+
+/// a `Result` whose error is a `ContextError`
+pub type Result<T, E = ContextError> = std::result::Result<T, E>;
+
+/// Adds the target being processed to the error of a `Result` so that the
+/// error's message always names the file (or URL) which caused it.
+pub trait WithTarget<T> {
+    fn with_target(self, target: &str) -> Result<T>;
+}
+
+impl<T, E: Into<ContextError>> WithTarget<T> for std::result::Result<T, E> {
+    fn with_target(self, target: &str) -> Result<T> {
+        self.map_err(|e| match e.into() {
+            // an error already names the target it relates to
+            e @ ContextError::Target { .. } => e,
+            e => ContextError::Target { target: target.to_string(), source: Box::new(e) },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_name_their_target_once() {
+        let failed: std::result::Result<(), IoError> = Err(IoError::FileDoesNotExist("docs/a.md".to_string()));
+        let err = failed.with_target("docs/a.md").with_target("docs").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "while processing 'docs/a.md': The file \"docs/a.md\" does not exist!"
+        );
+    }
+
+    #[test]
+    fn remote_failures_surface_as_a_single_variant() {
+        let invalid: ContextError = RemoteError::InvalidUrl("http//nope".to_string()).into();
+        let status: ContextError = RemoteError::HttpStatus("http://a.dev/x.md".to_string(), 404).into();
+
+        assert!(matches!(&invalid, ContextError::Io(IoError::InvalidUrl(url)) if url == "http//nope"));
+        assert!(matches!(status, ContextError::Io(IoError::NetworkError { status: Some(404), .. })));
+        assert_eq!(
+            invalid.to_string(),
+            "The remote target \"http//nope\" is not a valid URL; only http:// URLs are supported"
+        );
+    }
+
+    #[test]
+    fn remote_failures_map_to_network_and_url_io_errors() {
+        let status: ContextError = IoError::from(RemoteError::HttpStatus("http://a.dev/x.md".to_string(), 503)).into();
+        let refused = IoError::from(RemoteError::Connection("http://a.dev/x.md".to_string(), "refused".to_string()));
+        let invalid = IoError::from(RemoteError::UnsupportedScheme("ftp://a.dev/x.md".to_string()));
+
+        assert!(matches!(
+            &status,
+            ContextError::Io(IoError::NetworkError { url, status: Some(503), .. }) if url == "http://a.dev/x.md"
+        ));
+        assert!(matches!(refused, IoError::NetworkError { status: None, .. }));
+        assert!(matches!(invalid, IoError::InvalidUrl(_)));
+        assert_eq!(
+            status.to_string(),
+            "The remote target \"http://a.dev/x.md\" could not be fetched: the server responded with an HTTP 503 status"
+        );
+        assert_eq!(refused.to_string(), "The remote target \"http://a.dev/x.md\" could not be fetched: refused");
+    }
+}
@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RemoteError {
+    #[error("\"{0}\" is not a valid URL!")]
+    InvalidUrl(String),
+
+    #[error("The URL \"{0}\" uses a scheme which is not supported; only http:// URLs can be fetched.")]
+    UnsupportedScheme(String),
+
+    #[error("Unable to connect to \"{0}\": {1}")]
+    Connection(String, String),
+
+    #[error("The response from \"{0}\" could not be understood!")]
+    InvalidResponse(String),
+
+    #[error("The request for \"{0}\" failed with an HTTP {1} status")]
+    HttpStatus(String, u16),
+}
+
+impl RemoteError {
+    /// whether the failure is _transient_ and therefore worth retrying;
+    /// connection failures and 5xx statuses are while 4xx statuses are not
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RemoteError::Connection(..) => true,
+            RemoteError::HttpStatus(_, status) => *status >= 500,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, fs::{read_to_string, write}, io::ErrorKind};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{errors::io::IoError, hasher::is_unhashed};
+
+use super::FileWithMeta;
+
+/// the file -- in the current directory -- which the `FileCache` is
+/// persisted to between runs
+pub const FILE_CACHE: &str = ".ctx-cache.json";
+
+/// **FileCache**
+///
+/// The hash of each file's content -- keyed by its path -- as of the last
+/// run so that files which have not changed since can be skipped.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct FileCache {
+    hashes: HashMap<String, u64>,
+}
+
+impl FileCache {
+    /// Loads the cache persisted at `path`; a cache which has not been
+    /// persisted yet is empty.
+    pub fn load(path: &str) -> Result<Self, IoError> {
+        match read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| IoError::InvalidCache(path.to_string(), e.to_string())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(FileCache::default()),
+            Err(e) => Err(IoError::from_io(path, &e, IoError::PathExistsButNotFile(path.to_string()))),
+        }
+    }
+
+    /// persists the cache to the file at `path`
+    pub fn save(&self, path: &str) -> Result<(), IoError> {
+        let failed = |e| IoError::WriteFailedWith(path.to_string(), e);
+        let json = serde_json::to_string_pretty(self).map_err(|e| failed(e.into()))?;
+        write(path, json).map_err(failed)
+    }
+
+    /// the hash of the file's content when it was last cached
+    pub fn hash_of(&self, path: &str) -> Option<u64> {
+        self.hashes.get(path).copied()
+    }
+
+    /// whether the file has changed since it was cached; files which were
+    /// never cached -- or whose content is unhashed -- have always changed
+    pub fn has_changed(&self, file: &FileWithMeta) -> bool {
+        is_unhashed(&file.hash)
+            || self.hash_of(file.meta.filename()).is_none_or(|previous| file.has_changed_since(previous))
+    }
+
+    /// records the current hash of the file's content; unhashed content is
+    /// not recorded
+    pub fn update(&mut self, file: &FileWithMeta) {
+        if !is_unhashed(&file.hash) {
+            self.hashes.insert(file.meta.filename().to_string(), file.hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::FileMeta;
+
+    #[test]
+    fn unchanged_files_are_detected_across_runs() {
+        let dir = std::env::temp_dir().join(format!("ctx-file-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache_path = dir.join(FILE_CACHE).to_string_lossy().to_string();
+        let doc = dir.join("doc.md").to_string_lossy().to_string();
+        std::fs::write(&doc, "# Cached\n").unwrap();
+        let load = || FileMeta::try_from(doc.as_str()).unwrap().load_content().unwrap();
+
+        let mut cache = FileCache::load(&cache_path).unwrap();
+        assert!(cache.has_changed(&load()));
+        cache.update(&load());
+        cache.save(&cache_path).unwrap();
+
+        let cache = FileCache::load(&cache_path).unwrap();
+        assert!(!cache.has_changed(&load()));
+        std::fs::write(&doc, "# Cached\n\nEdited.\n").unwrap();
+        assert!(cache.has_changed(&load()));
+        assert!(load().has_changed_since(cache.hash_of(&doc).unwrap()));
+
+        std::fs::write(&cache_path, "not json").unwrap();
+        assert!(matches!(FileCache::load(&cache_path), Err(IoError::InvalidCache(..))));
+
+        let unwritable = dir.join("missing").join(FILE_CACHE).to_string_lossy().to_string();
+        assert!(matches!(
+            cache.save(&unwritable),
+            Err(IoError::WriteFailedWith(_, e)) if e.kind() == ErrorKind::NotFound
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,14 +1,44 @@
-use std::{time::SystemTime, fs::{metadata, read_to_string}};
+pub mod cache;
+
+use std::{
+    borrow::Cow,
+    time::SystemTime,
+    fs::{self, metadata, read_dir, read_link, read_to_string, rename, remove_file, symlink_metadata, DirEntry, File, Metadata},
+    io::{Read, Write},
+    path::Path,
+    process::Command,
+};
 use serde::{Serialize, Deserialize};
 use tracing::instrument;
 
-use crate::{errors::io::IoError, hasher::hash};
+use crate::{errors::io::IoError, hasher::{UNHASHED, hash, is_unhashed}, fingerprint::{sniff_mime, SNIFF_LEN}};
+
+/// the byte order mark which may start a UTF-8 file
+pub const UTF8_BOM: char = '\u{FEFF}';
+
+/// Detects the encoding of a file's content from its byte order mark, if it
+/// has one, or otherwise whether it is valid UTF-8; `None` when the
+/// encoding can not be determined.
+pub fn detect_encoding(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Some("UTF-8 (BOM)"),
+        [0xFF, 0xFE, ..] => Some("UTF-16LE"),
+        [0xFE, 0xFF, ..] => Some("UTF-16BE"),
+        _ if std::str::from_utf8(bytes).is_ok() => Some("UTF-8"),
+        _ => None,
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileWithMeta {
     pub meta: FileMeta,
     pub content: String,
-    pub hash: u64
+    /// the hash of the content; `UNHASHED` when it was not hashed
+    #[serde(default, skip_serializing_if = "is_unhashed")]
+    pub hash: u64,
+    /// whether invalid UTF-8 in the file was replaced when it was read
+    #[serde(default)]
+    pub lossy: bool,
 }
 
 impl TryFrom<FileMeta> for FileWithMeta {
@@ -16,27 +46,170 @@ impl TryFrom<FileMeta> for FileWithMeta {
 
     #[instrument]
     fn try_from(value: FileMeta) -> Result<Self, Self::Error> {
-        if let Ok(content) = read_to_string(&value.filename) {
-            Ok(Self {
-                hash: hash(&content),
-                content,
-                meta: value
-            })
-        } else {
-            Err(IoError::PathExistsButNotFile(value.filename.clone()))
+        FileWithMeta::read(value, false, true)
+    }
+}
+
+impl FileWithMeta {
+    /// Reads the file like `try_from` but replaces any invalid UTF-8 with
+    /// the replacement character (`�`) rather than failing; `lossy` records
+    /// whether anything was replaced.
+    pub fn read_lossy(meta: FileMeta) -> Result<Self, IoError> {
+        FileWithMeta::read(meta, true, true)
+    }
+
+    /// reads the file -- replacing invalid UTF-8 when `lossy` is set -- and
+    /// hashes its content unless `hashed` is unset (leaving it `UNHASHED`)
+    fn read(mut meta: FileMeta, lossy: bool, hashed: bool) -> Result<Self, IoError> {
+        let failed = |e: std::io::Error| IoError::from_io(
+            &meta.filename,
+            &e,
+            IoError::PathExistsButNotFile(meta.filename.clone())
+        );
+        let (content, replaced) = match lossy {
+            true => {
+                let bytes = fs::read(&meta.filename).map_err(failed)?;
+                meta.detected_encoding = detect_encoding(&bytes).map(String::from);
+                meta.classify(&bytes);
+                match String::from_utf8_lossy(&bytes) {
+                    Cow::Borrowed(text) => (text.to_string(), false),
+                    Cow::Owned(text) => (text, true),
+                }
+            }
+            false => {
+                let content = read_to_string(&meta.filename).map_err(failed)?;
+                meta.detected_encoding = detect_encoding(content.as_bytes()).map(String::from);
+                meta.classify(content.as_bytes());
+                (content, false)
+            }
+        };
+        let hash = match hashed {
+            true => hash(&content),
+            false => UNHASHED,
+        };
+
+        Ok(Self { hash, content, meta, lossy: replaced })
+    }
+
+    /// Removes a UTF-8 byte order mark from the start of the content (and
+    /// rehashes it unless it is unhashed); returns whether there was one to
+    /// remove.
+    pub fn strip_bom(&mut self) -> bool {
+        match self.content.strip_prefix(UTF8_BOM) {
+            Some(content) => {
+                self.content = content.to_string();
+                self.rehash();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// updates the hash once the content has changed; content which is
+    /// `UNHASHED` stays that way
+    pub fn rehash(&mut self) {
+        if !is_unhashed(&self.hash) {
+            self.hash = hash(&self.content);
         }
     }
+
+    /// whether the content has changed since it had the `previous_hash`
+    pub fn has_changed_since(&self, previous_hash: u64) -> bool {
+        self.hash != previous_hash
+    }
+
+    /// writes the content back to the file it was loaded from
+    pub fn write_back(&self) -> Result<(), IoError> {
+        self.write_to(&self.meta.filename)
+    }
+
+    /// Writes the content to the file at `path`. The write is atomic -- the
+    /// content is written to a temporary file which is then renamed over
+    /// `path` -- and the written content is verified against `self.hash`
+    /// (or, when the content is `UNHASHED`, a hash taken before writing).
+    pub fn write_to(&self, path: &str) -> Result<(), IoError> {
+        let expected = match is_unhashed(&self.hash) {
+            true => hash(&self.content),
+            false => self.hash,
+        };
+        let tmp = format!("{}.ctx-tmp", path);
+        let failed = |e| IoError::WriteFailedWith(path.to_string(), e);
+
+        let write = || -> std::io::Result<()> {
+            let mut file = File::create(&tmp)?;
+            file.write_all(self.content.as_bytes())?;
+            file.set_modified(SystemTime::now())?;
+            file.sync_all()?;
+            rename(&tmp, path)
+        };
+        if let Err(e) = write() {
+            let _ = remove_file(&tmp);
+            return Err(failed(e));
+        }
+
+        let written = read_to_string(path).map_err(failed)?;
+        if hash(&written) != expected {
+            return Err(IoError::WriteVerificationFailed(path.to_string()));
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The kind of content found at a path
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum FileType {
+    /// a non-empty file whose content has not (yet) been sniffed; this
+    /// becomes `Text` or `Binary` once the content is loaded
+    File,
+    /// content without null bytes whose sniffed MIME type is `text/*`
+    Text,
+    /// any other non-empty content, including content which can't be read
+    Binary,
+    /// a file without any content
+    Empty,
+    Directory,
+    /// a symlink along with the metadata of the path it resolves to
+    Symlink(Box<FileMeta>),
+}
+
+impl FileType {
+    /// the type of the file or directory at `filename` -- other than a
+    /// symlink -- from its (already retrieved) metadata; the content is
+    /// not read so a non-empty file is just a `File`
+    fn of(meta: &Metadata) -> Self {
+        match meta {
+            _ if meta.is_dir() => FileType::Directory,
+            _ if meta.len() == 0 => FileType::Empty,
+            _ => FileType::File,
+        }
+    }
+
+    /// the type of a non-empty file from (the start of) its content
+    fn sniff(content: &[u8]) -> Self {
+        match sniff_mime(content).starts_with("text/") {
+            true => FileType::Text,
+            false => FileType::Binary,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct FileMeta {
     filename: String,
-    /// whether or not the file is a symlink reference to a file
-    is_symlink: bool,
+    /// the kind of content the path holds; a symlink holds the metadata of
+    /// the path it resolves to
+    file_type: FileType,
+    /// the size of the file in bytes
+    size: u64,
     /// the _last modified_ time of the file if the OS supports providing this
     modified: Option<SystemTime>,
     /// the _created_ time of the file if the OS supports providing this
-    created: Option<SystemTime>
+    created: Option<SystemTime>,
+    /// the encoding detected when the file's content was loaded (e.g.,
+    /// `UTF-8` or `UTF-8 (BOM)`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detected_encoding: Option<String>,
 }
 
 /// try to convert a string slice -- representing a file path -- into
@@ -46,30 +219,40 @@ impl TryFrom<&str> for FileMeta {
 
     #[instrument]
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        // let mut meta: Result<Metadata,Self::Error>;
-        
-        if let Ok(meta) = metadata(value) {
-            if meta.is_file() {
-                let mut modified: Option<SystemTime> = None;
-                if let Ok(st) = meta.modified() {
-                    modified = Some(st);
-                }
-                let mut created: Option<SystemTime> = None;
-                if let Ok(st) = meta.created() {
-                    created = Some(st);
-                }
+        let missing = |e: std::io::Error| IoError::from_io(value, &e, IoError::FileDoesNotExist(value.to_string()));
+        let link = symlink_metadata(value).map_err(missing)?;
+        if !link.is_symlink() {
+            return FileMeta::from_metadata(value, &link);
+        }
 
-                Ok(Self {
-                    filename: value.to_string(),
-                    is_symlink: meta.is_symlink(),
-                    modified,
-                    created
-                })
-            } else {
-                Err(IoError::PathExistsButNotFile(value.to_string()))
-            }
-        } else {
-            Err(IoError::FileDoesNotExist(value.to_string()))
+        // a symlink takes the size and times of the path it resolves to
+        let mut meta = FileMeta::from_metadata(value, &metadata(value).map_err(missing)?)?;
+        let target = read_link(value).map_err(missing)?;
+        let target = match Path::new(value).parent() {
+            Some(dir) if target.is_relative() => dir.join(target),
+            _ => target,
+        };
+        meta.file_type = FileType::Symlink(Box::new(FileMeta::try_from(target.to_string_lossy().as_ref())?));
+
+        Ok(meta)
+    }
+}
+
+/// try to convert a `DirEntry` -- as provided when walking a directory --
+/// into a FileMeta structure; this uses the entry's own metadata so no
+/// further `stat` of the path is needed.
+impl TryFrom<DirEntry> for FileMeta {
+    type Error = IoError;
+
+    #[instrument]
+    fn try_from(entry: DirEntry) -> Result<Self, Self::Error> {
+        let filename = entry.path().to_string_lossy().to_string();
+        if entry.file_type().is_ok_and(|t| t.is_symlink()) {
+            return FileMeta::try_from(filename.as_str());
+        }
+        match entry.metadata() {
+            Ok(meta) => FileMeta::from_metadata(&filename, &meta),
+            Err(e) => Err(IoError::from_io(&filename, &e, IoError::FileDoesNotExist(filename.clone()))),
         }
     }
 }
@@ -87,10 +270,312 @@ impl TryFrom<&String> for FileMeta {
 }
 
 impl FileMeta {
+    /// builds a `FileMeta` from metadata which has already been retrieved
+    /// for the file (or directory) at `filename`
+    fn from_metadata(filename: &str, meta: &Metadata) -> Result<Self, IoError> {
+        if meta.is_file() || meta.is_dir() {
+            Ok(Self {
+                filename: filename.to_string(),
+                file_type: FileType::of(meta),
+                size: meta.len(),
+                modified: meta.modified().ok(),
+                created: meta.created().ok(),
+                detected_encoding: None,
+            })
+        } else {
+            Err(IoError::PathExistsButNotFile(filename.to_string()))
+        }
+    }
+
+    /// the path to the file as it was provided by the caller
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// the size of the file in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// when the file was last modified, if the OS provides this
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// the encoding detected when the file's content was loaded; `None`
+    /// until then or when it could not be determined
+    pub fn detected_encoding(&self) -> Option<&str> {
+        self.detected_encoding.as_deref()
+    }
+
+    /// the kind of content the path holds; a non-empty file is just a
+    /// `File` until its content is loaded or sniffed
+    pub fn file_type(&self) -> &FileType {
+        &self.file_type
+    }
+
+    /// Reads the start of a non-empty file to tell whether it is `Text` or
+    /// `Binary`; a file which can't be read is `Binary`. Other types --
+    /// including a symlink's target -- are left as they are.
+    pub fn sniff_file_type(mut self) -> Self {
+        if self.file_type == FileType::File {
+            let mut head: Vec<u8> = vec![];
+            self.file_type = match File::open(&self.filename).map(|f| f.take(SNIFF_LEN as u64).read_to_end(&mut head)) {
+                Ok(Ok(_)) => FileType::sniff(&head),
+                _ => FileType::Binary,
+            };
+        }
+        self
+    }
+
+    /// sets the type of a (not yet sniffed) file -- or of the file a
+    /// symlink resolves to -- from the `content` which was loaded for it
+    fn classify(&mut self, content: &[u8]) {
+        match &mut self.file_type {
+            FileType::File => self.file_type = FileType::sniff(content),
+            FileType::Symlink(target) => target.classify(content),
+            _ => {}
+        }
+    }
+
     /// attempt to upgrade the `FileMeta` to a `FileWithMeta` which
     /// includes the file's contents, a hash of these contents, along
     /// with all the prior metadata preserved.
     pub fn load_content(self) -> Result<FileWithMeta, IoError> {
         FileWithMeta::try_from(self)
     }
+
+    /// loads the file's contents like `load_content` but -- when `lossy` is
+    /// set -- invalid UTF-8 is replaced rather than being an error and --
+    /// when `hashed` is unset -- the content is left `UNHASHED`
+    pub fn load_content_with(self, lossy: bool, hashed: bool) -> Result<FileWithMeta, IoError> {
+        FileWithMeta::read(self, lossy, hashed)
+    }
+}
+
+/// Recursively walks the directory at `dir` and returns the metadata of all
+/// files found. Results are sorted so that output is stable between runs.
+#[instrument]
+pub fn files_in_dir(dir: &str) -> Result<Vec<FileMeta>, IoError> {
+    files_in_dir_to_depth(dir, None)
+}
+
+/// Walks the directory at `dir` like `files_in_dir` but -- when `max_depth`
+/// is set -- does not descend more than `max_depth` directories below it; a
+/// depth of `0` finds only the files immediately within `dir`.
+#[instrument]
+pub fn files_in_dir_to_depth(dir: &str, max_depth: Option<usize>) -> Result<Vec<FileMeta>, IoError> {
+    let mut found: Vec<FileMeta> = vec![];
+    let entries = read_dir(dir).map_err(|_| IoError::DirectoryNotReadable(dir.to_string()))?;
+
+    for entry in entries.flatten() {
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        if is_dir {
+            match max_depth {
+                Some(0) => continue,
+                depth => found.extend(files_in_dir_to_depth(&entry.path().to_string_lossy(), depth.map(|d| d - 1))?),
+            }
+        } else {
+            found.push(FileMeta::try_from(entry)?);
+        }
+    }
+    found.sort_by(|a, b| a.filename.cmp(&b.filename));
+
+    Ok(found)
+}
+
+/// Recursively walks the directory at `dir` and returns the metadata of all
+/// files which have the given extension (e.g., `md`).
+#[instrument]
+pub fn files_with_extension(dir: &str, ext: &str) -> Result<Vec<FileMeta>, IoError> {
+    files_with_extension_to_depth(dir, ext, None)
+}
+
+/// Walks the directory at `dir` -- no more than `max_depth` directories
+/// deep when it is set -- and returns the metadata of all files which have
+/// the given extension.
+#[instrument]
+pub fn files_with_extension_to_depth(dir: &str, ext: &str, max_depth: Option<usize>) -> Result<Vec<FileMeta>, IoError> {
+    Ok(
+        files_in_dir_to_depth(dir, max_depth)?
+            .into_iter()
+            .filter(|f| Path::new(&f.filename).extension().is_some_and(|e| e == ext))
+            .collect()
+    )
+}
+
+/// Reads the content of the file at `path` as it was at the given git
+/// `git_ref` (e.g., `HEAD~1` or `main`).
+#[instrument]
+pub fn read_at_git_ref(path: &str, git_ref: &str) -> Result<String, IoError> {
+    let failed = || IoError::GitShowFailed(path.to_string(), git_ref.to_string());
+    let relative = if Path::new(path).is_absolute() { path.to_string() } else { format!("./{}", path) };
+
+    let output = Command::new("git")
+        .args(["show", &format!("{}:{}", git_ref, relative)])
+        .output()
+        .map_err(|_| failed())?;
+    if !output.status.success() {
+        return Err(failed());
+    }
+
+    String::from_utf8(output.stdout).map_err(|_| failed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_entry_converts_to_file_meta() {
+        let entry = read_dir("test/data")
+            .unwrap()
+            .flatten()
+            .find(|e| e.file_name() == "lumberjack.md")
+            .unwrap();
+        let from_entry = FileMeta::try_from(entry).unwrap();
+        let from_path = FileMeta::try_from("test/data/lumberjack.md").unwrap();
+
+        assert_eq!(from_entry.filename(), from_path.filename());
+        assert_eq!(from_entry.modified, from_path.modified);
+    }
+
+    #[test]
+    fn walking_a_dir_finds_markdown_files() {
+        let files = files_with_extension("test", "md").unwrap();
+        assert!(files.iter().any(|f| f.filename().ends_with("lumberjack.md")));
+    }
+
+    #[test]
+    fn files_beyond_the_max_depth_are_not_found() {
+        let dir = std::env::temp_dir().join(format!("ctx-depth-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub/deeper")).unwrap();
+        for file in ["top.md", "sub/mid.md", "sub/deeper/low.md", "sub/notes.txt"] {
+            std::fs::write(dir.join(file), "# Doc\n").unwrap();
+        }
+        let root = dir.to_string_lossy().to_string();
+        let names = |depth: Option<usize>| -> Vec<String> {
+            files_with_extension_to_depth(&root, "md", depth)
+                .unwrap()
+                .iter()
+                .map(|f| f.filename()[root.len() + 1..].to_string())
+                .collect()
+        };
+
+        assert_eq!(names(Some(0)), vec!["top.md"]);
+        assert_eq!(names(Some(1)), vec!["sub/mid.md", "top.md"]);
+        assert_eq!(names(None), vec!["sub/deeper/low.md", "sub/mid.md", "top.md"]);
+        assert_eq!(files_in_dir_to_depth(&root, Some(1)).unwrap().len(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_to_round_trips_content() {
+        let dir = std::env::temp_dir().join(format!("ctx-write-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("copy.md").to_string_lossy().to_string();
+
+        let original = FileMeta::try_from("test/data/lumberjack.md").unwrap().load_content().unwrap();
+        original.write_to(&target).unwrap();
+        let mut copy = FileMeta::try_from(target.as_str()).unwrap().load_content().unwrap();
+        assert_eq!(copy.hash, original.hash);
+        assert!(copy.meta.modified.is_some());
+
+        copy.content.push_str("\nMore content\n");
+        copy.hash = hash(&copy.content);
+        copy.write_back().unwrap();
+        assert!(read_to_string(&target).unwrap().ends_with("More content\n"));
+        assert!(!Path::new(&format!("{}.ctx-tmp", target)).exists());
+
+        let unhashed = FileMeta::try_from(target.as_str()).unwrap().load_content_with(false, false).unwrap();
+        unhashed.write_back().unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_file_reports_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("ctx-perm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("secret.md");
+        std::fs::write(&file, "# Secret\n").unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o000)).unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        // privileged users (e.g., root) can read the file regardless
+        if read_to_string(&file).is_err() {
+            let meta = FileMeta::try_from(path.as_str()).unwrap();
+            assert!(matches!(meta.load_content(), Err(IoError::PermissionDenied(p)) if p == path));
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_type_reflects_content() {
+        let dir = std::env::temp_dir().join(format!("ctx-types-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        std::fs::write(path("text.md"), "# Text\n").unwrap();
+        std::fs::write(path("image.png"), [0x89, b'P', b'N', b'G', 0, 0]).unwrap();
+        std::fs::write(path("empty.md"), "").unwrap();
+
+        let meta = |name: &str| FileMeta::try_from(path(name).as_str()).unwrap();
+        assert_eq!(meta("text.md").file_type, FileType::File);
+        assert_eq!(meta("text.md").sniff_file_type().file_type, FileType::Text);
+        assert_eq!(meta("text.md").load_content().unwrap().meta.file_type, FileType::Text);
+        assert_eq!(meta("image.png").sniff_file_type().file_type, FileType::Binary);
+        assert_eq!(meta("image.png").load_content_with(true, false).unwrap().meta.file_type, FileType::Binary);
+        assert_eq!(meta("empty.md").file_type, FileType::Empty);
+        assert_eq!(FileMeta::try_from(dir.to_string_lossy().as_ref()).unwrap().file_type, FileType::Directory);
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("text.md", path("link.md")).unwrap();
+            let link = FileMeta::try_from(path("link.md").as_str()).unwrap();
+            assert_eq!(link.size(), 7);
+            match &link.file_type {
+                FileType::Symlink(target) => {
+                    assert_eq!(target.filename(), path("text.md"));
+                    assert_eq!(target.file_type, FileType::File);
+                }
+                other => panic!("expected a symlink but found {:?}", other),
+            }
+            match link.load_content().unwrap().meta.file_type {
+                FileType::Symlink(target) => assert_eq!(target.file_type, FileType::Text),
+                other => panic!("expected a symlink but found {:?}", other),
+            }
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encoding_is_detected_when_content_is_loaded() {
+        let path = std::env::temp_dir().join(format!("ctx-bom-{}.md", std::process::id()));
+        std::fs::write(&path, "\u{FEFF}# Title\n").unwrap();
+        let meta = FileMeta::try_from(path.to_str().unwrap()).unwrap();
+        assert_eq!(meta.detected_encoding(), None);
+        let mut file = meta.load_content().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(file.meta.detected_encoding(), Some("UTF-8 (BOM)"));
+        assert_eq!(file.meta.size(), 11);
+        assert!(file.strip_bom());
+        assert_eq!(file.content, "# Title\n");
+        assert_eq!(file.hash, hash("# Title\n"));
+        assert!(!file.strip_bom());
+        assert_eq!(detect_encoding(b"plain"), Some("UTF-8"));
+        assert_eq!(detect_encoding(&[0xFF, 0xFE, b'#', 0]), Some("UTF-16LE"));
+        assert_eq!(detect_encoding(b"bad \xff"), None);
+    }
+
+    #[test]
+    fn missing_file_reports_does_not_exist() {
+        assert!(matches!(FileMeta::try_from("test/data/nope.md"), Err(IoError::FileDoesNotExist(_))));
+    }
 }
@@ -0,0 +1,395 @@
+use std::{fs::File, io::Read, path::Path};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use crate::{errors::io::IoError, file::files_in_dir_to_depth};
+
+/// **Fingerprint** enum
+/// 
+/// Provides a list of all _identifiable_ targets which this CLI can
+/// give context on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Fingerprint {
+    MarkdownFile,
+    HtmlFile,
+    /// a source code file (`.rs`, `.py` or `.js`) whose doc comments may
+    /// hold markdown
+    SourceFile,
+    /// a Jupyter notebook (`.ipynb`)
+    JupyterNotebook,
+    /// a remote resource identified by an `http://` URL; URLs with any
+    /// other scheme (e.g., `https://`) are not supported and are `Unknown`
+    RemoteUrl,
+    /// a local directory which will be scanned for files this CLI
+    /// can give context on
+    Directory,
+
+    /// a target string which matches none of the regex patterns currently
+    /// in this library
+    Unknown
+}
+
+impl Fingerprint {
+    /// a human readable label for the kind of target
+    pub fn label(&self) -> &'static str {
+        match self {
+            Fingerprint::MarkdownFile => "Markdown file",
+            Fingerprint::HtmlFile => "HTML file",
+            Fingerprint::SourceFile => "Source file",
+            Fingerprint::JupyterNotebook => "Jupyter notebook",
+            Fingerprint::RemoteUrl => "Remote URL",
+            Fingerprint::Directory => "Directory",
+            Fingerprint::Unknown => "Unknown",
+        }
+    }
+
+    /// Looks up a supported fingerprint by name; the variant name (e.g.,
+    /// `MarkdownFile`) or its short form (e.g., `markdown`) may be used and
+    /// case is ignored.
+    pub fn from_name(name: &str) -> Option<Fingerprint> {
+        let name = name.to_lowercase();
+        Fingerprint::all_supported().into_iter().map(|(kind, _)| kind).find(|kind| {
+            let variant = serde_json::to_value(kind)
+                .ok()
+                .and_then(|v| v.as_str().map(|v| v.to_lowercase()))
+                .unwrap_or_default();
+            let short = variant.trim_end_matches("file").trim_end_matches("url");
+            name == variant || name == short
+        })
+    }
+
+    /// Every fingerprint which is identified by a matcher along with the
+    /// matcher's regex pattern, in the order the matchers are evaluated.
+    pub fn all_supported() -> Vec<(Fingerprint, &'static str)> {
+        MATCHERS.iter().map(|m| (m.kind.clone(), m.re.as_str())).collect()
+    }
+}
+
+struct Matcher {
+    re: Regex,
+    kind: Fingerprint
+}
+
+/// Registers the `Fingerprint` of content whose sniffed MIME type starts
+/// with `mime_prefix`; used for files whose name reveals nothing (e.g., no
+/// extension).
+pub struct MimeMatcher {
+    pub mime_prefix: String,
+    pub kind: Fingerprint,
+}
+
+#[derive(Clone)]
+pub struct Target {
+    pub user_input: String,
+    pub kind: Fingerprint
+}
+
+lazy_static! {
+    static ref MATCHERS: [Matcher; 5] = [
+        Matcher { kind: Fingerprint::RemoteUrl, re:  Regex::new(r"^http://").unwrap() },
+        Matcher { kind: Fingerprint::MarkdownFile, re:  Regex::new(r"\w\.md$").unwrap() },
+        Matcher { kind: Fingerprint::HtmlFile, re:  Regex::new(r"\w\.htm(l){0,1}$").unwrap() },
+        Matcher { kind: Fingerprint::SourceFile, re:  Regex::new(r"\w\.(rs|py|js)$").unwrap() },
+        Matcher { kind: Fingerprint::JupyterNotebook, re:  Regex::new(r"\w\.ipynb$").unwrap() }
+    ];
+    /// any URL; those which the `RemoteUrl` matcher doesn't accept have a
+    /// scheme which can't be fetched
+    static ref URL: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*://").unwrap();
+    static ref MIME_MATCHERS: Vec<MimeMatcher> = vec![
+        MimeMatcher { mime_prefix: "text/html".to_string(), kind: Fingerprint::HtmlFile },
+        MimeMatcher { mime_prefix: "text/markdown".to_string(), kind: Fingerprint::MarkdownFile },
+    ];
+}
+
+/// the number of bytes read from the start of a file to sniff its MIME type
+pub(crate) const SNIFF_LEN: usize = 8 * 1024;
+
+/// whether plain text content looks like markdown (it has frontmatter,
+/// a heading, a list or a link)
+pub(crate) fn looks_like_markdown(content: &str) -> bool {
+    content.starts_with("---\n")
+        || content.lines().any(|l| {
+            let l = l.trim_start();
+            l.starts_with("# ") || l.starts_with("## ") || l.starts_with("- ") || l.starts_with("```")
+        })
+        || content.contains("](")
+}
+
+/// Sniffs the MIME type of content from its leading bytes; content which
+/// is not text is `application/octet-stream`.
+pub fn sniff_mime(content: &[u8]) -> &'static str {
+    let head = &content[..content.len().min(SNIFF_LEN)];
+    // a multi-byte character may have been cut off at the end of the head
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).unwrap_or_default(),
+        Err(_) => return "application/octet-stream",
+    };
+    if text.contains('\0') {
+        return "application/octet-stream";
+    }
+
+    let start = text.trim_start_matches('\u{feff}').trim_start().to_lowercase();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        "text/html"
+    } else if looks_like_markdown(text) {
+        "text/markdown"
+    } else {
+        "text/plain"
+    }
+}
+
+/// Fingerprints a name (e.g., a file name or the path of a URL) against
+/// the regex `MATCHERS` alone; nothing on the local filesystem is consulted.
+pub fn fingerprint_name(name: &str) -> Fingerprint {
+    MATCHERS
+        .iter()
+        .find(|m| m.re.is_match(name))
+        .map(|m| m.kind.clone())
+        .unwrap_or(Fingerprint::Unknown)
+}
+
+/// Fingerprints a target in two phases: the name is matched against the
+/// regex `MATCHERS` and -- only when that is not conclusive -- the sniffed
+/// MIME type of the content is matched against the `MIME_MATCHERS`.
+pub fn fingerprint_with_content(path: &str, content: &[u8]) -> Fingerprint {
+    match fingerprint_name(path) {
+        Fingerprint::Unknown => {
+            let mime = sniff_mime(content);
+            MIME_MATCHERS
+                .iter()
+                .find(|m| mime.starts_with(&m.mime_prefix))
+                .map(|m| m.kind.clone())
+                .unwrap_or(Fingerprint::Unknown)
+        }
+        kind => kind,
+    }
+}
+
+/// Tests whether the input string matches a known matcher pattern which will
+/// contextualize what a given "target" is. At most one match will be found as
+/// match conditions are evaluated lazily until a match is found. Only the
+/// name is considered -- no content is read -- so a file without a
+/// recognized extension is `Unknown` until `sniff_target` is used.
+/// 
+/// For debugging purposes, you may want to try `matches(input)` function instead
+/// as it will return ALL matches.
+pub fn fingerprint(input: &str) -> Target {
+    if Path::new(input).is_dir() {
+        return Target { kind: Fingerprint::Directory, user_input: input.to_string() };
+    }
+
+    let found = MATCHERS.iter().find(|m| m.re.is_match(input));
+
+    match found {
+        // a URL with an unsupported scheme is not a local file either
+        Some(m) if m.kind != Fingerprint::RemoteUrl && URL.is_match(input) => {
+            Target { kind: Fingerprint::Unknown, user_input: input.to_string() }
+        }
+        Some(m) => Target { kind: m.kind.clone(), user_input: input.to_string() },
+        None => Target { kind: Fingerprint::Unknown, user_input: input.to_string() }
+    }
+}
+
+/// Fingerprints a target which was not recognized by name from the start of
+/// its content (see `fingerprint_with_content`); meant for where the content
+/// is about to be loaded anyway. Other targets are returned unchanged.
+pub fn sniff_target(target: &Target) -> Target {
+    match target.kind {
+        Fingerprint::Unknown if !URL.is_match(&target.user_input) => {
+            Target { kind: sniff_file(&target.user_input), user_input: target.user_input.clone() }
+        }
+        _ => target.clone(),
+    }
+}
+
+/// the fingerprint of a local file -- which no matcher recognized by name --
+/// based on its content
+fn sniff_file(path: &str) -> Fingerprint {
+    let mut head: Vec<u8> = vec![];
+    match File::open(path).map(|f| f.take(SNIFF_LEN as u64).read_to_end(&mut head)) {
+        Ok(Ok(_)) => fingerprint_with_content(path, &head),
+        _ => Fingerprint::Unknown,
+    }
+}
+
+/// Expands any directory targets into a target for each of the files found
+/// within them; all other targets are passed through unchanged.
+pub fn expand_targets(targets: &[Target]) -> Result<Vec<Target>, IoError> {
+    expand_targets_to_depth(targets, None)
+}
+
+/// Expands targets like `expand_targets` but -- when `max_depth` is set --
+/// only finds files up to `max_depth` directories below each directory
+/// target.
+pub fn expand_targets_to_depth(targets: &[Target], max_depth: Option<usize>) -> Result<Vec<Target>, IoError> {
+    let mut expanded: Vec<Target> = vec![];
+
+    for t in targets {
+        if t.kind == Fingerprint::Directory {
+            expanded.extend(files_in_dir_to_depth(&t.user_input, max_depth)?.iter().map(|f| fingerprint(f.filename())));
+        } else {
+            expanded.push(Target { kind: t.kind.clone(), user_input: t.user_input.clone() });
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Keeps the already expanded targets (see `expand_targets_to_depth`) whose
+/// kind is one of `kinds` so that the files within directories are filtered
+/// too. The targets which were filtered out are returned second.
+pub fn filter_kinds(expanded: Vec<Target>, kinds: &[Fingerprint]) -> (Vec<Target>, Vec<Target>) {
+    expanded.into_iter().partition(|t| kinds.contains(&t.kind))
+}
+
+/// Checks that the already expanded targets (see `expand_targets_to_depth`)
+/// do not exceed `limit` (where `0` means there is no limit); no content is
+/// loaded.
+pub fn check_target_limit(expanded: &[Target], limit: usize) -> Result<(), IoError> {
+    let count = expanded.len();
+    if limit > 0 && count > limit {
+        return Err(IoError::TooManyTargets(count, limit));
+    }
+
+    Ok(())
+}
+
+/// A count of targets by their `Fingerprint`
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct FingerprintCensus {
+    pub markdown: usize,
+    pub html: usize,
+    pub remote: usize,
+    pub source: usize,
+    pub notebook: usize,
+    pub unknown: usize,
+}
+
+/// Counts the targets by their `Fingerprint` without loading any content
+pub fn census(targets: &[Target]) -> FingerprintCensus {
+    let mut counts = FingerprintCensus::default();
+    for t in targets {
+        match t.kind {
+            Fingerprint::MarkdownFile => counts.markdown += 1,
+            Fingerprint::HtmlFile => counts.html += 1,
+            Fingerprint::RemoteUrl => counts.remote += 1,
+            Fingerprint::SourceFile => counts.source += 1,
+            Fingerprint::JupyterNotebook => counts.notebook += 1,
+            Fingerprint::Directory | Fingerprint::Unknown => counts.unknown += 1,
+        }
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn census_counts_expanded_targets_by_kind() {
+        let targets: Vec<Target> = ["test", "index.html", "README.md", "foo.txt", "http://example.com/a.md"]
+            .iter()
+            .map(|i| fingerprint(i))
+            .collect();
+        let counts = census(&expand_targets(&targets).unwrap());
+
+        assert_eq!(counts.html, 1);
+        assert_eq!(counts.remote, 1);
+        assert_eq!(counts.unknown, 1);
+        assert!(counts.markdown >= 2);
+    }
+
+    #[test]
+    fn target_limit_applies_after_expansion() {
+        let targets = vec![fingerprint("test"), fingerprint("README.md")];
+        let expanded = expand_targets(&targets).unwrap();
+
+        assert!(check_target_limit(&expanded, expanded.len()).is_ok());
+        assert!(check_target_limit(&expanded, 0).is_ok());
+        assert!(matches!(
+            check_target_limit(&expanded, expanded.len() - 1),
+            Err(IoError::TooManyTargets(count, _)) if count == expanded.len()
+        ));
+    }
+
+    #[test]
+    fn max_depth_keeps_targets_under_the_limit() {
+        let targets = vec![fingerprint("test"), fingerprint("README.md")];
+        let shallow = expand_targets_to_depth(&targets, Some(0)).unwrap();
+
+        // only `README.md` is found as `test` holds nothing but a directory
+        assert_eq!(shallow.len(), 1);
+        assert!(check_target_limit(&shallow, 1).is_ok());
+        assert!(check_target_limit(&expand_targets(&targets).unwrap(), 1).is_err());
+    }
+
+    #[test]
+    fn only_the_given_kinds_are_kept() {
+        let targets = vec![fingerprint("test"), fingerprint("index.html"), fingerprint("notes.txt")];
+        let (kept, skipped) = filter_kinds(expand_targets(&targets).unwrap(), &[Fingerprint::MarkdownFile]);
+
+        assert!(kept.len() >= 2);
+        assert!(kept.iter().all(|t| t.kind == Fingerprint::MarkdownFile));
+        assert!(kept.iter().any(|t| t.user_input.ends_with("lumberjack.md")));
+        assert_eq!(skipped.len(), 2);
+    }
+
+    #[test]
+    fn extensionless_content_is_fingerprinted_by_mime() {
+        assert_eq!(fingerprint_with_content("Dockerfile", b"FROM rust:latest\nRUN cargo build\n"), Fingerprint::Unknown);
+        assert_eq!(fingerprint_with_content("NOTES", b"# Notes\n\n- one\n"), Fingerprint::MarkdownFile);
+        assert_eq!(fingerprint_with_content("page", b"\n<!DOCTYPE html><html></html>"), Fingerprint::HtmlFile);
+        assert_eq!(fingerprint_with_content("README.md", b"<html></html>"), Fingerprint::MarkdownFile);
+        assert_eq!(sniff_mime(&[0x89, b'P', b'N', b'G', 0, 0]), "application/octet-stream");
+    }
+
+    #[test]
+    fn names_are_fingerprinted_without_the_filesystem() {
+        assert_eq!(fingerprint_name("/docs/a.md"), Fingerprint::MarkdownFile);
+        assert_eq!(fingerprint_name("/index.html"), Fingerprint::HtmlFile);
+        // a directory on disk is still just a name
+        assert_eq!(fingerprint_name("test"), Fingerprint::Unknown);
+        assert_eq!(fingerprint_name("/etc/passwd"), Fingerprint::Unknown);
+    }
+
+    #[test]
+    fn content_is_only_sniffed_on_request() {
+        let file = std::env::temp_dir().join(format!("ctx-sniff-{}", std::process::id()));
+        std::fs::write(&file, "# Notes
+
+- one
+").unwrap();
+        let target = fingerprint(&file.to_string_lossy());
+        let sniffed = sniff_target(&target);
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(target.kind, Fingerprint::Unknown);
+        assert_eq!(sniffed.kind, Fingerprint::MarkdownFile);
+        assert_eq!(sniff_target(&fingerprint("index.html")).kind, Fingerprint::HtmlFile);
+    }
+
+    #[test]
+    fn fingerprints_are_found_by_name() {
+        assert_eq!(Fingerprint::from_name("markdown"), Some(Fingerprint::MarkdownFile));
+        assert_eq!(Fingerprint::from_name("HtmlFile"), Some(Fingerprint::HtmlFile));
+        assert_eq!(Fingerprint::from_name("remote"), Some(Fingerprint::RemoteUrl));
+        assert_eq!(Fingerprint::from_name("source"), Some(Fingerprint::SourceFile));
+        assert_eq!(Fingerprint::from_name("jupyternotebook"), Some(Fingerprint::JupyterNotebook));
+        assert_eq!(Fingerprint::from_name("directory"), None);
+    }
+
+    #[test]
+    fn supported_fingerprints_come_from_matchers() {
+        let supported = Fingerprint::all_supported();
+        let kinds: Vec<&Fingerprint> = supported.iter().map(|(kind, _)| kind).collect();
+
+        assert!(kinds.contains(&&Fingerprint::MarkdownFile));
+        assert!(kinds.contains(&&Fingerprint::HtmlFile));
+        assert_eq!(supported.len(), MATCHERS.len());
+        assert!(supported.iter().all(|(kind, pattern)| MATCHERS.iter().any(|m| &m.kind == kind && m.re.as_str() == *pattern)));
+    }
+}
@@ -1,6 +1,20 @@
-use xxhash_rust::xxh3::{xxh3_64, xxh3_64_with_secret};
+use std::{fs::{read, read_dir}, path::Path};
 
-const MIN_SECRET_LEN: usize = 256;
+use xxhash_rust::xxh3::{Xxh3, xxh3_64, xxh3_64_with_seed, xxh3_64_with_secret};
+
+use crate::errors::io::IoError;
+
+/// the secret length used when generating secrets for `secret_hash`
+pub const MIN_SECRET_LEN: usize = 256;
+
+/// the sentinel hash of content which was deliberately not hashed (e.g.,
+/// with `--no-hash`); such hashes are omitted from output
+pub const UNHASHED: u64 = 0;
+
+/// whether the hash is the `UNHASHED` sentinel
+pub fn is_unhashed(hash: &u64) -> bool {
+    *hash == UNHASHED
+}
 
 /// Provides a hash for a passed in string slice using the `xxh3` hasher
 /// which is currently the fastest quality hasher available to userland. It
@@ -10,6 +24,12 @@ pub fn hash(content: &str) -> u64 {
     xxh3_64(content.as_bytes())
 }
 
+/// Provides a hash for the string content using a specific seed; a seed of
+/// `0` produces the same hash as `hash`.
+pub fn hash_with_seed(content: &str, seed: u64) -> u64 {
+    xxh3_64_with_seed(content.as_bytes(), seed)
+}
+
 /// Provides a hash for the string content passed in and includes 
 /// some secret text to help obfuscate the underlying document.
 /// 
@@ -19,6 +39,92 @@ pub fn secret_hash(content: &str, secret: &str) -> u64 {
     xxh3_64_with_secret(content.as_bytes(), secret.as_bytes())
 }
 
+/// Normalizes insignificant whitespace so that reformatting does not
+/// change a hash: trailing whitespace is trimmed from each line, runs of
+/// blank lines are collapsed into one and the content ends with a single
+/// trailing newline.
+pub fn normalize_whitespace(content: &str) -> String {
+    let mut lines: Vec<&str> = vec![];
+    for line in content.lines().map(|l| l.trim_end()) {
+        if line.is_empty() && lines.last().is_some_and(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+
+    format!("{}\n", lines.join("\n").trim_matches('\n'))
+}
+
+/// Provides a hash of the content after insignificant whitespace has been
+/// normalized (see `normalize_whitespace`)
+pub fn normalized_hash(content: &str) -> u64 {
+    hash(&normalize_whitespace(content))
+}
+
+/// whether a file or directory name marks it as hidden (e.g., `.git`)
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+/// the paths -- relative to `root` -- of the files found in `dir`
+fn files_to_hash(root: &Path, dir: &Path, recursive: bool, include_hidden: bool) -> Result<Vec<String>, IoError> {
+    let display = dir.to_string_lossy().to_string();
+    let entries = read_dir(dir)
+        .map_err(|e| IoError::from_io(&display, &e, IoError::DirectoryNotReadable(display.clone())))?;
+
+    let mut files: Vec<String> = vec![];
+    for entry in entries.flatten() {
+        if !include_hidden && is_hidden(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                files.extend(files_to_hash(root, &path, recursive, include_hidden)?);
+            }
+        } else {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Provides a stable hash of the files in the directory at `path`. Files
+/// are visited in sorted order and both their relative path and content
+/// contribute to the hash so that renames -- not just edits -- are detected.
+pub fn hash_directory_with(path: &str, recursive: bool, include_hidden: bool) -> Result<u64, IoError> {
+    let root = Path::new(path);
+    let mut files = files_to_hash(root, root, recursive, include_hidden)?;
+    files.sort();
+
+    let mut hasher = Xxh3::new();
+    for file in files {
+        let full = root.join(&file);
+        let content = read(&full).map_err(|e| {
+            let display = full.to_string_lossy().to_string();
+            IoError::from_io(&display, &e, IoError::FileDoesNotExist(display.clone()))
+        })?;
+        hasher.update(file.as_bytes());
+        hasher.update(&xxh3_64(&content).to_le_bytes());
+    }
+
+    Ok(hasher.digest())
+}
+
+/// a stable hash of every (non-hidden) file found -- recursively -- under the
+/// directory at `path`; useful for cache invalidation
+pub fn hash_directory(path: &str) -> Result<u64, IoError> {
+    hash_directory_with(path, true, false)
+}
+
+/// a stable hash of the (non-hidden) files directly inside the directory at
+/// `path`; sub-directories are ignored
+pub fn hash_directory_shallow(path: &str) -> Result<u64, IoError> {
+    hash_directory_with(path, false, false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -26,23 +132,65 @@ mod tests {
     #[test]
     fn consistency_without_secret() {
         let content = String::from("There I was, There I was, ... in the Congo");
-        let h = hash(&content, None);
+        let h = hash(&content);
         // test result is consistent
-        for _i in [0..100] {
-            let r = hash(&content, None);
+        for _i in 0..100 {
+            let r = hash(&content);
             assert_eq!(h, r);
         }
     }
 
+    #[test]
+    fn seeded_hash_depends_on_seed() {
+        let content = "There I was, There I was, ... in the Congo";
+
+        assert_eq!(hash_with_seed(content, 42), hash_with_seed(content, 42));
+        assert_ne!(hash_with_seed(content, 42), hash_with_seed(content, 43));
+        assert_eq!(hash_with_seed(content, 0), hash(content));
+    }
+
     #[test]
     fn consistency_with_secret() {
         let content = String::from("There I was, There I was, ... in the Congo");
-        use rand::distributions::{Alphanumeric, DistString};
-        let secret = Alphanumeric.sample_string(&mut rand::thread_rng(), MIN_SECRET_LEN);
-        let h = hash(&content, Some(&secret));
-        for _i in [0..100] {
-            let r = hash(&content, Some(&secret));
+        let secret = "s".repeat(MIN_SECRET_LEN);
+        let h = secret_hash(&content, &secret);
+        for _i in 0..100 {
+            let r = secret_hash(&content, &secret);
             assert_eq!(h, r);
         }
     }
+
+    #[test]
+    fn whitespace_variants_have_same_normalized_hash() {
+        let a = "# Title\n\nSome text\n";
+        let b = "\n# Title   \n\n\n\nSome text\t\n\n\n";
+
+        assert_ne!(hash(a), hash(b));
+        assert_eq!(normalized_hash(a), normalized_hash(b));
+        assert_ne!(normalized_hash(a), normalized_hash("# Title\n\nOther text\n"));
+    }
+
+    #[test]
+    fn directory_hash_tracks_content_and_ignores_hidden_files() {
+        let dir = std::env::temp_dir().join(format!("ctx-hash-dir-{}", std::process::id()));
+        let path = dir.to_string_lossy().to_string();
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.md"), "# A").unwrap();
+        std::fs::write(dir.join("nested/b.md"), "# B").unwrap();
+
+        let deep = hash_directory(&path).unwrap();
+        let shallow = hash_directory_shallow(&path).unwrap();
+        assert_eq!(deep, hash_directory(&path).unwrap());
+        assert_ne!(deep, shallow);
+
+        std::fs::write(dir.join(".hidden"), "secret").unwrap();
+        assert_eq!(deep, hash_directory(&path).unwrap());
+        assert_ne!(deep, hash_directory_with(&path, true, true).unwrap());
+
+        std::fs::write(dir.join("nested/b.md"), "# B!").unwrap();
+        assert_ne!(deep, hash_directory(&path).unwrap());
+        assert_eq!(shallow, hash_directory_shallow(&path).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
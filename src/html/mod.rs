@@ -0,0 +1,144 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::{
+    file::{FileMeta, FileWithMeta},
+    md::frontmatter::Frontmatter,
+};
+
+lazy_static! {
+    static ref META_TAG: Regex = Regex::new(r"(?is)<meta\s[^>]*>").unwrap();
+    static ref ATTR: Regex = Regex::new(r#"(?s)([A-Za-z_:\-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    static ref TITLE_TAG: Regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    static ref HTML_LANG: Regex = Regex::new(r#"(?i)<html[^>]*\slang=["']([^"']+)["']"#).unwrap();
+}
+
+/// decodes the handful of character entities commonly found in attributes
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// The `name` (or OpenGraph `property`) and `content` of each `<meta>` tag
+/// in the document, in document order.
+pub fn meta_tags(html: &str) -> Vec<(String, String)> {
+    META_TAG
+        .find_iter(html)
+        .filter_map(|tag| {
+            let mut name: Option<String> = None;
+            let mut content: Option<String> = None;
+            for attr in ATTR.captures_iter(tag.as_str()) {
+                let value = attr.get(2).or(attr.get(3)).map(|v| decode_entities(v.as_str()));
+                match attr[1].to_lowercase().as_str() {
+                    "name" | "property" => name = name.or(value.map(|v| v.to_lowercase())),
+                    "content" => content = value,
+                    _ => {}
+                }
+            }
+            Some((name?, content?))
+        })
+        .collect()
+}
+
+/// **HtmlDoc**
+///
+/// The metadata of an HTML document; `<meta>` tags -- including the OpenGraph
+/// and Twitter card variants -- are mapped onto the same `Frontmatter` used
+/// for markdown so that both formats produce comparable output.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HtmlDoc {
+    /// the language set on the `<html>` tag
+    pub language: Option<String>,
+    pub fm: Frontmatter,
+    pub file: Option<FileMeta>,
+}
+
+impl HtmlDoc {
+    /// builds an `HtmlDoc` from the raw content of an HTML file
+    pub fn parse(raw: &str) -> Self {
+        HtmlDoc::from_raw(raw, None)
+    }
+
+    /// builds an `HtmlDoc` from a loaded HTML file
+    pub fn from_file(file: FileWithMeta) -> Self {
+        HtmlDoc::from_raw(&file.content, Some(file.meta))
+    }
+
+    fn from_raw(raw: &str, file: Option<FileMeta>) -> Self {
+        let mut fm = Frontmatter::default();
+
+        for (name, content) in meta_tags(raw) {
+            match name.as_str() {
+                "og:title" | "twitter:title" => fm.title = fm.title.or(Some(content)),
+                "description" | "og:description" | "twitter:description" => {
+                    fm.description = fm.description.or(Some(content))
+                }
+                "og:image" | "twitter:image" => fm.image = fm.image.or(Some(content)),
+                "keywords" => {
+                    fm.tags = fm.tags.or(Some(
+                        content.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect()
+                    ))
+                }
+                _ => {
+                    fm.other.entry(name).or_insert(Value::String(content));
+                }
+            }
+        }
+
+        if fm.title.is_none() {
+            fm.title = TITLE_TAG
+                .captures(raw)
+                .map(|c| decode_entities(c[1].trim()))
+                .filter(|t| !t.is_empty());
+        }
+
+        HtmlDoc {
+            language: HTML_LANG.captures(raw).map(|c| c[1].to_string()),
+            fm,
+            file,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html lang="en"><head>
+<title>Fallback Title</title>
+<meta name="description" content="All about &quot;ctx&quot;">
+<meta property="og:title" content="Open Graph Title" />
+<meta property="og:image" content="https://example.com/card.png">
+<meta name="twitter:description" content="ignored as description is already set">
+<meta name="keywords" content="docs, markdown">
+<meta name="author" content="Ken">
+<meta charset="utf-8">
+</head><body></body></html>"#;
+
+    #[test]
+    fn meta_and_open_graph_map_onto_frontmatter() {
+        let doc = HtmlDoc::parse(PAGE);
+
+        assert_eq!(doc.language, Some("en".to_string()));
+        assert_eq!(doc.fm.title, Some("Open Graph Title".to_string()));
+        assert_eq!(doc.fm.description, Some("All about \"ctx\"".to_string()));
+        assert_eq!(doc.fm.image, Some("https://example.com/card.png".to_string()));
+        assert_eq!(doc.fm.tags, Some(vec!["docs".to_string(), "markdown".to_string()]));
+        assert_eq!(doc.fm.other.get("author"), Some(&Value::String("Ken".to_string())));
+        assert!(!doc.fm.other.contains_key("twitter:description"));
+    }
+
+    #[test]
+    fn title_tag_is_used_without_meta_title() {
+        let doc = HtmlDoc::parse("<head><title> Plain </title></head>");
+
+        assert_eq!(doc.fm.title, Some("Plain".to_string()));
+        assert!(doc.fm.other.is_empty());
+    }
+}
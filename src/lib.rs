@@ -0,0 +1,21 @@
+pub mod errors;
+pub mod hasher;
+pub mod md;
+pub mod html;
+pub mod file;
+pub mod fingerprint;
+pub mod config;
+pub mod warning;
+pub mod schema;
+pub mod output;
+pub mod compress;
+pub mod progress;
+pub mod notebook;
+pub mod repl;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod remote;
+
+pub use fingerprint::{Fingerprint, Target, fingerprint, sniff_target, expand_targets, expand_targets_to_depth, census, check_target_limit, filter_kinds};
+pub use config::Config;
+pub use warning::{Warning, WarningKind};
@@ -1,22 +1,112 @@
-pub mod errors;
-pub mod hasher;
-pub mod md;
-pub mod file;
-
-use color_eyre::eyre::Result;
-
-use lazy_static::lazy_static;
-use md::reporting::md_file;
-use regex::Regex;
-use clap::Parser;
-use serde::{Serialize, Deserialize};
+use std::{fs, io, path::Path, process::exit, time::{Duration, SystemTime}};
+
+use clap::{Parser, Subcommand};
 use serde_json::{Value, json};
 
+use ctx::{
+    Config, Fingerprint, Target, Warning, WarningKind, fingerprint, sniff_target, expand_targets_to_depth, census, check_target_limit, filter_kinds,
+    config::PartialConfig,
+    errors::{Result, WithTarget, io::IoError, md::MarkdownError},
+    compress::{Compression, write_output},
+    progress::Progress,
+    file::{FileMeta, FileWithMeta, read_at_git_ref, cache::{FileCache, FILE_CACHE}},
+    hasher::{hash, hash_directory_with},
+    html::HtmlDoc,
+    notebook::Notebook,
+    repl,
+    schema::json_schemas,
+    output::{
+        envelope, sort_results, query_results, parse_json_pointer, sidecar_path, render_json, sort_keys, SortField,
+    },
+    remote::remote_report,
+    md::{
+        diff::compare,
+        quality::quality_summary,
+        links::link_graph,
+        index::FrontmatterIndex,
+        stale::{parse_age, stale_docs},
+        lint::{LintCheck, lint_docs},
+        typography::Typography,
+        frontmatter::{FmFence, Frontmatter, default_fences, set_field, unset_field},
+        reporting::{
+            md_file, md_dir, md_report, index_pages_in_dir, load_md, load_file, md_docs_for, auth_report,
+            check_word_count, rewrite_prose, taxonomy, source_file,
+        },
+        markdown::MarkdownDoc,
+    },
+};
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// print the value of a frontmatter field -- one value per target --
+    /// where a dotted path (e.g., `author.name`) reaches into nested values
+    Get {
+        /// the frontmatter field to print
+        field: String,
+
+        #[arg(long)]
+        /// exit with a non-zero exit code if any target does not set the field
+        required: bool,
+
+        /// the markdown files (or directories) to read the field from
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+    /// set a frontmatter field in each target; the type of fields which
+    /// aren't typed frontmatter properties is inferred (boolean, number or
+    /// string)
+    Set {
+        /// the frontmatter field to set
+        field: String,
+
+        /// the value to set the field to
+        value: String,
+
+        #[arg(long)]
+        /// print the updated content of each file rather than writing it
+        dry_run: bool,
+
+        /// the markdown files (or directories) to set the field in
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+    /// remove a frontmatter field from each target; targets which don't
+    /// set the field are left as is
+    Unset {
+        /// the frontmatter field to remove
+        field: String,
+
+        #[arg(long)]
+        /// print the updated content of each file rather than writing it
+        dry_run: bool,
+
+        /// the markdown files (or directories) to remove the field from
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+    /// run the lint checks against each markdown document and print the
+    /// issues grouped by check; exits with a non-zero exit code if there
+    /// are any issues
+    Lint {
+        #[arg(long, value_enum, value_delimiter = ',')]
+        /// the checks to run (comma separated) [default: all of them]
+        lint_select: Vec<LintCheck>,
+
+        /// the markdown files (or directories) to lint
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "Context CLI")]
 #[command(author = "Ken Snyder<ken@ken.net>")]
 #[command(version,long_about= None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short)]
     /// show more verbose output
     v: bool,
@@ -25,113 +115,624 @@ struct Cli {
     /// force output to JSON format
     json: bool,
 
-    /// items which you want context on
-    targets: Vec<String>
-}
+    #[arg(short, long)]
+    /// don't display a progress bar on stderr while processing targets
+    quiet: bool,
 
+    #[arg(long)]
+    /// print only the paths of index pages found in directory targets
+    list_index_pages: bool,
 
-/// **Fingerprint** enum
-/// 
-/// Provides a list of all _identifiable_ targets which this CLI can
-/// give context on.
-#[derive(Serialize, Deserialize, Clone)]
-pub enum Fingerprint {
-    MarkdownFile,
-    HtmlFile,
+    #[arg(long)]
+    /// process documents marked as drafts rather than skipping them
+    include_drafts: bool,
 
-    /// a target string which matches none of the regex patterns currently
-    /// in this library
-    Unknown
-}
+    #[arg(long)]
+    /// the boolean frontmatter property which marks a document as a draft
+    /// [default: draft]
+    draft_field: Option<String>,
 
-struct Matcher {
-    re: Regex,
-    kind: Fingerprint
-}
+    #[arg(long)]
+    /// skip documents which set `requires_auth: true` in their frontmatter
+    skip_requires_auth: bool,
+
+    #[arg(long)]
+    /// skip documents whose frontmatter `expires` date has been reached
+    skip_expired: bool,
+
+    #[arg(long)]
+    /// report on the emoji (shortcodes and Unicode) used in the prose
+    emoji: bool,
+
+    #[arg(long, requires = "emoji")]
+    /// flag documents which use more emoji than this threshold
+    emoji_threshold: Option<usize>,
+
+    #[arg(long, num_args = 2, value_names = ["A", "B"])]
+    /// report the structural differences between two markdown files
+    compare: Option<Vec<String>>,
+
+    #[arg(long)]
+    /// only report a count of targets by kind without loading any content
+    fingerprint_only: bool,
+
+    #[arg(long)]
+    /// only report file metadata; file contents are never loaded
+    meta_only: bool,
+
+    #[arg(long)]
+    /// report a stable hash of each directory target (for cache invalidation)
+    hash_dir: bool,
+
+    #[arg(long)]
+    /// include hidden files (e.g., `.env`) when hashing directories
+    hidden: bool,
+
+    #[arg(long)]
+    /// score each markdown document from 0 to 100 on a set of weighted
+    /// quality checks and report an aggregate across all documents
+    quality: bool,
+
+    #[arg(long)]
+    /// print each markdown document's heading hierarchy as an indented
+    /// markdown list (rather than JSON)
+    outline: bool,
+
+    #[arg(long, requires = "outline")]
+    /// include the line number of each heading in the `--outline`
+    line_numbers: bool,
+
+    #[arg(long)]
+    /// report the graph of internal links between markdown documents,
+    /// including links to pages which do not exist
+    link_graph: bool,
+
+    #[arg(long)]
+    /// group markdown documents by the `category` in their frontmatter
+    category_tree: bool,
+
+    #[arg(long)]
+    /// treat each target as a stream of YAML documents separated by `---`
+    /// lines and report every document as a frontmatter record
+    multi_doc: bool,
+
+    #[arg(long, value_name = "FILE")]
+    /// load a markdown file and explore it interactively with commands such
+    /// as `headings`, `links`, `fm title`, `wordcount` and `prose`
+    repl: Option<String>,
+
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    /// list markdown documents -- oldest first -- which have not been
+    /// modified within this period (e.g., `180d`, `6w` or `12h`)
+    stale: Option<Duration>,
+
+    #[arg(long)]
+    /// report only the markdown files which changed since the last run; the
+    /// hash of each file is kept in `.ctx-cache.json`
+    use_cache: bool,
+
+    #[arg(long)]
+    /// group markdown documents by the `category`, `layout` and `subject`
+    /// set in their frontmatter
+    taxonomy: bool,
+
+    #[arg(long)]
+    /// group markdown documents by their `requires_auth` frontmatter setting
+    auth_report: bool,
+
+    #[arg(long)]
+    /// ignore insignificant whitespace when hashing prose
+    hash_normalize_whitespace: bool,
+
+    #[arg(long, num_args = 2, value_names = ["OPEN", "CLOSE"])]
+    /// the opening and closing delimiters of a custom frontmatter fence
+    /// (the default `---` and `+++` fences are still recognized)
+    fm_fence: Option<Vec<String>>,
+
+    #[arg(long)]
+    /// print the JSON Schema of the output produced for each kind of target
+    schema: bool,
+
+    #[arg(long = "as", value_name = "FINGERPRINT", value_parser = parse_fingerprint)]
+    /// treat every target as this kind (e.g., `markdown`) rather than
+    /// fingerprinting it; see `--list-formats`
+    as_kind: Option<Fingerprint>,
+
+    #[arg(long, value_name = "FINGERPRINT", value_parser = parse_fingerprint)]
+    /// only process targets of this kind (e.g., `markdown`); may be repeated
+    /// and directories are expanded so that the files within them are
+    /// filtered too
+    only: Vec<Fingerprint>,
+
+    #[arg(long)]
+    /// list the kinds of targets which are recognized -- and the pattern
+    /// each is recognized by -- and exit
+    list_formats: bool,
+
+    #[arg(long)]
+    /// print the effective configuration -- and the source of each value --
+    /// and exit
+    print_config: bool,
+
+    #[arg(long)]
+    /// wrap the results in an envelope with tool metadata
+    envelope: bool,
+
+    #[arg(long, value_name = "N")]
+    /// pretty print JSON output with each level indented by this many
+    /// spaces (the output is compact by default)
+    indent: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// walk no more than this many directories below each directory target;
+    /// `0` processes only the files immediately within it
+    max_depth: Option<usize>,
+
+    #[arg(long)]
+    /// sort the keys of the JSON objects in the output
+    sort_keys: bool,
+
+    #[arg(long, value_enum)]
+    /// flatten multi-file output into a single list sorted by this field
+    sort: Option<SortField>,
+
+    #[arg(long)]
+    /// reverse the sort order (results missing the sort field stay last)
+    reverse: bool,
+
+    #[arg(long, value_name = "JSON_POINTER", value_parser = parse_json_pointer)]
+    /// print a single value -- selected by a JSON Pointer such as
+    /// `/fm/title` -- from each result as a `path<TAB>value` line
+    query: Option<String>,
+
+    #[arg(long, requires = "query")]
+    /// leave out the results in which the `--query` pointer finds nothing
+    skip_missing: bool,
+
+    #[arg(long)]
+    /// retry transient failures (connection errors, 5xx statuses) when
+    /// fetching remote (`http://` only) targets this many times with
+    /// exponential backoff
+    retries: Option<u32>,
+
+    #[arg(long)]
+    /// the timeout -- in seconds -- of each attempt to fetch a remote target
+    timeout: Option<u64>,
+
+    #[arg(long)]
+    /// fail (exit code 1) when a markdown document has fewer words than this
+    min_word_count: Option<usize>,
+
+    #[arg(long)]
+    /// fail (exit code 1) when a markdown document lost more than this
+    /// percentage of its words compared to its previous version (see
+    /// `--git-ref` and `--baseline`)
+    max_word_count_drop_percent: Option<f64>,
+
+    #[arg(long, conflicts_with = "baseline")]
+    /// the git ref (e.g., `HEAD~1`) which holds the previous version of each
+    /// markdown document
+    git_ref: Option<String>,
+
+    #[arg(long)]
+    /// a markdown file which is the previous version of the target
+    baseline: Option<String>,
+
+    #[arg(long)]
+    /// treat documents as entries of an Obsidian vault; `[[wikilinks]]` are
+    /// added to the reported links (with `is_wiki: true`)
+    obsidian: bool,
+
+    #[arg(long)]
+    /// report the number of words in each heading's section
+    section_words: bool,
+
+    #[arg(long)]
+    /// report the Flesch-Kincaid reading ease score of each document
+    readability: bool,
+
+    #[arg(long)]
+    /// replace invalid UTF-8 in markdown files rather than failing to load
+    /// them; such documents are reported with `lossy: true`
+    lossy: bool,
+
+    #[arg(long)]
+    /// inline the markdown files a document includes -- with
+    /// `{{ include: other.md }}` or `![[other.md]]` -- before analyzing it
+    resolve_includes: bool,
+
+    #[arg(long)]
+    /// warn about each document whose description is backfilled from its
+    /// first paragraph because the frontmatter has no `description`
+    backfill_fm: bool,
+
+    #[arg(long)]
+    /// remove a UTF-8 byte order mark from the start of each file before
+    /// its content is hashed and analyzed
+    strip_bom: bool,
+
+    #[arg(long)]
+    /// report the number of characters in each document, with and without
+    /// whitespace
+    char_count: bool,
+
+    #[arg(long)]
+    /// remove markdown syntax before counting characters
+    strip_syntax: bool,
+
+    #[arg(long)]
+    /// warn about documents whose prose is empty (or has fewer words than
+    /// `--min-words`); combine with `--strict` to fail on them
+    fail_on_empty_prose: bool,
+
+    #[arg(long, value_name = "N")]
+    /// the fewest words of prose a document may have before
+    /// `--fail-on-empty-prose` flags it (default 1)
+    min_words: Option<usize>,
+
+    #[arg(long)]
+    /// warn about each raw HTML block (e.g., `<div>` or `<details>`) in the
+    /// prose and fail (exit code 1) when any are found
+    fail_on_html: bool,
+
+    #[arg(long)]
+    /// cascade the frontmatter of each `_index.md` into the other documents
+    /// of its directory and those below it (their own values win)
+    cascade_fm: bool,
+
+    #[arg(long)]
+    /// skip hashing the content of files (for speed); hashes are omitted
+    /// from the output and `--use-cache` treats every file as changed
+    no_hash: bool,
+
+    #[arg(long, value_enum)]
+    /// normalize quotes and dashes in the prose (code is left untouched)
+    typography: Option<Typography>,
+
+    #[arg(long)]
+    /// write the transformed prose (see `--typography`) back to each
+    /// markdown file rather than reporting on it
+    write: bool,
+
+    #[arg(long)]
+    /// report lint issues (e.g., non-descriptive link text or skipped heading
+    /// levels) as warnings
+    lint: bool,
+
+    #[arg(long)]
+    /// extract the markdown in the doc comments of source files (`.rs`,
+    /// `.py` and `.js`) and report on it as a markdown document
+    extract_doc_comments: bool,
+
+    #[arg(long)]
+    /// coerce boolean-like strings (`"yes"`, `"on"`, `"1"`, ...) in typed
+    /// boolean frontmatter properties such as `requires_auth`; without it
+    /// such values are kept as they are and reported as warnings
+    lenient_fm: bool,
+
+    #[arg(long)]
+    /// the base URL the documents are served from; each document's
+    /// canonical URL is reported
+    url_base: Option<String>,
+
+    #[arg(long)]
+    /// the directory holding the site's content, relative to which canonical
+    /// URLs are derived (defaults to the current directory)
+    content_root: Option<String>,
+
+    #[arg(long, value_name = "DIR")]
+    /// cache remote responses in this directory and revalidate them with
+    /// `If-None-Match`/`If-Modified-Since` on later runs
+    cache: Option<String>,
 
-pub struct Target {
-    pub user_input: String,
-    pub kind: Fingerprint
+    #[arg(long)]
+    /// abort when the targets -- once directories are expanded -- number
+    /// more than this (default 10,000; 0 for no limit)
+    max_targets: Option<usize>,
+
+    #[arg(long)]
+    /// write each markdown document's report to a sidecar file next to it
+    /// (e.g., `foo.md` → `foo.md.ctx.json`) rather than to stdout
+    sidecar: bool,
+
+    #[arg(long, value_name = "DIR")]
+    /// write the sidecar files into this directory -- mirroring the tree of
+    /// the source files -- rather than next to each source file
+    sidecar_dir: Option<String>,
+
+    #[arg(long)]
+    /// write the results to this file rather than to stdout; a `.gz` suffix
+    /// implies `--compress gzip`
+    out: Option<String>,
+
+    #[arg(long, value_enum, requires = "out")]
+    /// compress the results written to `--out`
+    compress: Option<Compression>,
+
+    #[arg(long)]
+    /// exit with a non-zero exit code if any warnings were found
+    strict: bool,
+
+    #[arg(long)]
+    /// exit with a non-zero exit code if any target was not recognized
+    fail_on_unknown: bool,
+
+    /// items which you want context on
+    targets: Vec<String>
 }
 
-lazy_static! {
-    static ref MATCHERS: [Matcher; 2] = [
-        Matcher { kind: Fingerprint::MarkdownFile, re:  Regex::new(r"\w\.md$").unwrap() },
-        Matcher { kind: Fingerprint::HtmlFile, re:  Regex::new(r"\w\.htm(l){0,1}$").unwrap() }
-    ];
+/// parses the `--as` value into one of the supported fingerprints
+fn parse_fingerprint(name: &str) -> Result<Fingerprint, String> {
+    Fingerprint::from_name(name).ok_or_else(|| {
+        let supported: Vec<String> = Fingerprint::all_supported()
+            .iter()
+            .filter_map(|(kind, _)| serde_json::to_value(kind).ok()?.as_str().map(|k| k.to_string()))
+            .collect();
+        format!("must be one of: {}", supported.join(", "))
+    })
 }
 
 fn html_file(target: &Target) -> Result<Value> {
     eprintln!("- '{}' is being processed as a local HTML file", target.user_input);
+    let file = FileMeta::try_from(target.user_input.as_str())
+        .and_then(FileWithMeta::try_from)
+        .with_target(&target.user_input)?;
 
-    Ok(json!("{}"))
+    Ok(json!(HtmlDoc::from_file(file)))
 }
 
+fn notebook_file(target: &Target) -> Result<Value> {
+    eprintln!("- '{}' is being processed as a Jupyter notebook", target.user_input);
+    let file = FileMeta::try_from(target.user_input.as_str())
+        .and_then(FileWithMeta::try_from)
+        .with_target(&target.user_input)?;
 
+    Ok(json!(Notebook::from_file(&file).with_target(&target.user_input)?))
+}
 
 /// Takes a list of all fingerprints received from user and processes
-/// the _known_ fingerprints.
-fn process_known_fingerprints(targets: &Vec<Target>) -> Result<Value> {
-    let results: Vec<Result<Value>> = targets.iter().map(|t| {
+/// the _known_ fingerprints. Any target which fails to be processed is
+/// recorded as a warning.
+fn process_known_fingerprints(
+    targets: &[Target],
+    config: &Config,
+    progress: &Progress,
+    warnings: &mut Vec<Warning>
+) -> Value {
+    let mut outcomes: Vec<Value> = vec![];
+
+    for t in targets {
+        progress.working_on(&t.user_input);
+        progress.advance();
         let result = match t.kind {
             Fingerprint::HtmlFile => html_file(t),
-            Fingerprint::MarkdownFile => md_file(t),
-            Fingerprint::Unknown => Ok(json!({})),
+            Fingerprint::MarkdownFile => md_file(t, config, warnings),
+            Fingerprint::Directory => md_dir(t, config, warnings),
+            Fingerprint::RemoteUrl => remote_report(t, config, warnings),
+            Fingerprint::SourceFile if config.extract_doc_comments => source_file(t, config, warnings),
+            Fingerprint::SourceFile => {
+                eprintln!("- '{}' is a source file; use --extract-doc-comments to report on its doc comments", t.user_input);
+                continue;
+            }
+            Fingerprint::JupyterNotebook => notebook_file(t),
+            Fingerprint::Unknown => continue,
         };
 
-        result
-    }).collect();
+        match result {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => warnings.push(Warning::new(&t.user_input, &format!("could not be processed: {}", e))),
+        }
+    }
+    progress.finish();
 
-    let _errors = results
-        .iter() 
-        .filter(|i| i.is_err());
+    json!(outcomes)
+}
 
-        // .collect();
-    let outcomes: Vec<Value> = results
-        .into_iter()
-        .filter(|i| i.is_ok())
-        .map(|i| i.unwrap())
-        .collect();
+/// Provides a warning for each target whose fingerprint was not recognized
+fn unknown_fingerprint_warnings(targets: &[Target]) -> Vec<Warning> {
+    targets
+        .iter()
+        .filter(|t| t.kind == Fingerprint::Unknown)
+        .map(|t| Warning::unrecognized(&t.user_input))
+        .collect()
+}
+
+/// All the markdown documents which the targets refer to; targets which
+/// fail to load are recorded as warnings.
+fn collect_md_docs(targets: &[Target], config: &Config, warnings: &mut Vec<Warning>) -> Vec<MarkdownDoc> {
+    let mut docs = vec![];
+    for t in targets {
+        match md_docs_for(t, config, warnings) {
+            Ok(found) => docs.extend(found),
+            Err(e) => warnings.push(Warning::new(&t.user_input, &format!("could not be processed: {}", e))),
+        }
+    }
+
+    docs
+}
+
+/// The previous version of a document -- taken from either the `--baseline`
+/// file or the `--git-ref` -- if one was requested and could be loaded.
+fn previous_version(doc: &MarkdownDoc, args: &Cli, config: &Config, warnings: &mut Vec<Warning>) -> Option<MarkdownDoc> {
+    let file = doc.file.as_ref()?.filename();
+    let previous = match (&args.baseline, &args.git_ref) {
+        (Some(baseline), _) => load_md(baseline, config),
+        (_, Some(git_ref)) => read_at_git_ref(file, git_ref)
+            .map_err(|e| e.into())
+            .and_then(|raw| Ok(MarkdownDoc::parse_with(&raw, config)?)),
+        _ => return None,
+    };
+
+    previous
+        .map_err(|e| warnings.push(Warning::new(file, &format!("previous version could not be loaded: {}", e))))
+        .ok()
+}
 
-    Ok(json!(outcomes))
+/// The configuration layer provided by the CLI switches; switches which
+/// were not used leave the underlying configuration in place.
+fn cli_config(args: &Cli) -> PartialConfig {
+    let flag = |set: bool| if set { Some(true) } else { None };
+
+    PartialConfig {
+        draft_field: args.draft_field.clone(),
+        include_drafts: flag(args.include_drafts),
+        skip_requires_auth: flag(args.skip_requires_auth),
+        skip_expired: flag(args.skip_expired),
+        emoji: flag(args.emoji),
+        emoji_threshold: args.emoji_threshold,
+        hash_normalize_whitespace: flag(args.hash_normalize_whitespace),
+        fm_fences: args.fm_fence
+            .as_ref()
+            .map(|fence| [vec![FmFence::new(&fence[0], &fence[1])], default_fences()].concat()),
+        strict: flag(args.strict),
+        fail_on_unknown: flag(args.fail_on_unknown),
+        envelope: flag(args.envelope),
+        indent: args.indent,
+        max_depth: args.max_depth,
+        sort_keys: flag(args.sort_keys),
+        sort: args.sort,
+        reverse: flag(args.reverse),
+        retries: args.retries,
+        timeout: args.timeout,
+        lint: flag(args.lint),
+        hidden: flag(args.hidden),
+        obsidian: flag(args.obsidian),
+        section_words: flag(args.section_words),
+        readability: flag(args.readability),
+        lossy: flag(args.lossy),
+        resolve_includes: flag(args.resolve_includes),
+        backfill_fm: flag(args.backfill_fm),
+        strip_bom: flag(args.strip_bom),
+        char_count: flag(args.char_count),
+        strip_syntax: flag(args.strip_syntax),
+        fail_on_empty_prose: flag(args.fail_on_empty_prose),
+        min_words: args.min_words,
+        fail_on_html: flag(args.fail_on_html),
+        cascade_fm: flag(args.cascade_fm),
+        no_hash: flag(args.no_hash),
+        typography: args.typography,
+        url_base: args.url_base.clone(),
+        content_root: args.content_root.clone(),
+        cache: args.cache.clone(),
+        max_targets: args.max_targets,
+        lenient_fm: flag(args.lenient_fm),
+        extract_doc_comments: flag(args.extract_doc_comments),
+        quality_weights: None,
+        poor_link_text: None,
+    }
 }
 
-/**
- * Sends warning messages to stderr for any unknown fingerprints and returns
- * a boolean value indicating if _any_ of the fingerprints were unknown.
- * 
- * Note: a `true` return means there **were** unknown fingerprints
- */
-fn warn_about_unknown_fingerprints(targets: &Vec<Target>) -> bool {
-    let mut found = false;
-    targets.iter().for_each(|i| {
-        if let Fingerprint::Unknown = i.kind {
-            eprintln!("- '{0}' was not recognized and will be ignored!", i.user_input);
-            found = true;
+/// Lints each markdown document the targets refer to and prints the issues
+/// grouped by check; any issue (or target which can't be loaded) causes a
+/// non-zero exit code.
+fn lint(targets: &[String], checks: &[LintCheck], config: &Config) {
+    let targets: Vec<Target> = targets.iter().map(|t| sniff_target(&fingerprint(t))).collect();
+    let checks = if checks.is_empty() { LintCheck::ALL.to_vec() } else { checks.to_vec() };
+    let mut warnings: Vec<Warning> = vec![];
+    let docs = collect_md_docs(&targets, config, &mut warnings);
+    let summary = lint_docs(&docs, &checks, &config.poor_link_text);
+
+    println!("{}", render_json(&json!(summary), config));
+    warnings.iter().for_each(|w| eprintln!("{}", w));
+    if summary.issues > 0 || !warnings.is_empty() {
+        eprintln!("- {} lint issue(s) found in {} document(s)", summary.issues, summary.documents);
+        exit(1);
+    }
+}
+
+/// Prints the value of a frontmatter `field` for each markdown document
+/// the targets refer to; strings are printed as is and other values as JSON.
+fn get_field(field: &str, targets: &[String], required: bool, config: &Config) {
+    let targets: Vec<Target> = targets.iter().map(|t| sniff_target(&fingerprint(t))).collect();
+    let mut warnings: Vec<Warning> = vec![];
+    let mut missing = false;
+
+    for doc in collect_md_docs(&targets, config, &mut warnings) {
+        match doc.fm.as_ref().and_then(|fm| fm.get_path(field)) {
+            Some(Value::String(value)) => println!("{}", value),
+            Some(value) => println!("{}", value),
+            None => missing = true,
         }
-    });
+    }
+
+    warnings.iter().for_each(|w| eprintln!("{}", w));
+    if required && missing {
+        exit(1);
+    }
+}
+
+/// Applies `edit` to the raw content of a single document's file and
+/// writes the result back (or prints it when `dry_run` is set); returns
+/// whether the file changed.
+fn edit_file<F>(doc: &MarkdownDoc, dry_run: bool, edit: &F) -> Result<bool>
+where
+    F: Fn(&str) -> std::result::Result<String, MarkdownError>,
+{
+    let filename = doc.file.as_ref().map(|f| f.filename()).unwrap_or_default();
+    let mut file = FileWithMeta::try_from(FileMeta::try_from(filename)?)?;
+    let content = edit(&file.content)?;
+    if dry_run {
+        print!("{}", content);
+        return Ok(false);
+    }
+    if content == file.content {
+        return Ok(false);
+    }
 
-    found
+    file.hash = hash(&content);
+    file.content = content;
+    file.write_back()?;
+
+    Ok(true)
 }
 
-/// Tests whether the input string matches a known matcher pattern which will
-/// contextualize what a given "target" is. At most one match will be found as
-/// match conditions are evaluated lazily until a match is found.
-/// 
-/// For debugging purposes, you may want to try `matches(input)` function instead
-/// as it will return ALL matches.
-fn fingerprint(input: &str) -> Target {
-    let found = MATCHERS.iter().find(|m| {
-        if m.re.is_match(input) {
-            true
-        } else {
-            false
+/// Applies `edit` to the file of each markdown document the targets refer
+/// to, reporting each file which changed with `done`; exits with a non-zero
+/// exit code if any document couldn't be updated.
+fn edit_files<F>(targets: &[String], dry_run: bool, config: &Config, done: &str, edit: F)
+where
+    F: Fn(&str) -> std::result::Result<String, MarkdownError>,
+{
+    let targets: Vec<Target> = targets.iter().map(|t| sniff_target(&fingerprint(t))).collect();
+    let mut warnings: Vec<Warning> = vec![];
+    let docs = collect_md_docs(&targets, config, &mut warnings);
+
+    for doc in &docs {
+        let filename = doc.file.as_ref().map(|f| f.filename()).unwrap_or_default();
+        match edit_file(doc, dry_run, &edit) {
+            Ok(true) => eprintln!("- '{}' {}", filename, done),
+            Ok(false) => {}
+            Err(e) => warnings.push(Warning::new(filename, &format!("could not be updated: {}", e))),
         }
-    });
+    }
+
+    warnings.iter().for_each(|w| eprintln!("{}", w));
+    if !warnings.is_empty() {
+        exit(1);
+    }
+}
 
-    match found {
-        Some(m) => Target { kind: m.kind.clone(), user_input: input.to_string() },
-        None => Target { kind: Fingerprint::Unknown, user_input: input.to_string()}
+/// Reports all warnings to stderr and -- when in _strict_ mode -- exits with
+/// a non-zero exit code if there were any warnings at all; with
+/// `--fail-on-unknown` only unrecognized targets cause the run to fail.
+fn conclude(warnings: &[Warning], config: &Config) {
+    warnings.iter().for_each(|w| eprintln!("{}", w));
+
+    let unknown = warnings.iter().filter(|w| w.kind == WarningKind::Unrecognized).count();
+    if config.fail_on_unknown && unknown > 0 {
+        eprintln!("- {} unrecognized target(s) found with --fail-on-unknown", unknown);
+        exit(1);
+    }
+
+    let html = warnings.iter().filter(|w| w.kind == WarningKind::RawHtml).count();
+    if config.fail_on_html && html > 0 {
+        eprintln!("- {} raw HTML block(s) found with --fail-on-html", html);
+        exit(1);
+    }
+
+    if config.strict && !warnings.is_empty() {
+        eprintln!("- {} warning(s) found while in strict mode", warnings.len());
+        exit(1);
     }
 }
 
@@ -139,6 +740,55 @@ fn fingerprint(input: &str) -> Target {
 fn main() {
 
     let args = Cli::parse();
+    let config = match Config::resolve(cli_config(&args)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("- {}", e);
+            exit(1);
+        }
+    };
+
+    if let Some(Command::Get { field, required, targets }) = &args.command {
+        get_field(field, targets, *required, &config);
+        return;
+    }
+    if let Some(Command::Set { field, value, dry_run, targets }) = &args.command {
+        let done = format!("set '{}' to {}", field, value);
+        edit_files(targets, *dry_run, &config, &done, |raw| set_field(raw, &config.fm_fences, field, value));
+        return;
+    }
+    if let Some(Command::Lint { lint_select, targets }) = &args.command {
+        lint(targets, lint_select, &config);
+        return;
+    }
+    if let Some(Command::Unset { field, dry_run, targets }) = &args.command {
+        let done = format!("unset '{}'", field);
+        edit_files(targets, *dry_run, &config, &done, |raw| unset_field(raw, &config.fm_fences, field));
+        return;
+    }
+
+    if args.print_config {
+        println!("{}", serde_json::to_string_pretty(&json!({
+            "config": config,
+            "sources": config.sources,
+        })).unwrap());
+        return;
+    }
+
+    if args.list_formats {
+        let formats: Vec<Value> = Fingerprint::all_supported()
+            .into_iter()
+            .map(|(kind, pattern)| json!({ "kind": kind, "label": kind.label(), "pattern": pattern }))
+            .collect();
+        println!("{}", json!(formats));
+        return;
+    }
+
+    if args.schema {
+        println!("{}", serde_json::to_string_pretty(&json_schemas()).unwrap());
+        return;
+    }
+
     let multiple_targets = args.targets.len() > 1;
 
     if multiple_targets {
@@ -147,12 +797,332 @@ fn main() {
         eprintln!("Context CLI");
     }
     eprintln!("-----------------------------------");
-    eprintln!("");
-    println!("targets are: {:?}", args.targets);
+    eprintln!();
+    if args.v {
+        eprintln!("targets are: {:?}", args.targets);
+    }
+
+    let mut fingerprints: Vec<Target> = args.targets
+        .iter()
+        .map(|i| match &args.as_kind {
+            Some(kind) => Target { kind: kind.clone(), user_input: i.to_string() },
+            None => fingerprint(i),
+        })
+        .collect();
+    // directories are walked once -- to `--max-depth` -- and the expanded
+    // targets are shared by the target limit and the modes which work on
+    // individual files
+    let mut expanded = expand_targets_to_depth(&fingerprints, config.max_depth);
+    if config.max_targets > 0 {
+        let checked = match &expanded {
+            Ok(targets) => check_target_limit(targets, config.max_targets).map_err(|e| e.to_string()),
+            Err(e) => Err(format!("targets could not be expanded: {}", e)),
+        };
+        if let Err(e) = checked {
+            eprintln!("- {}", e);
+            exit(1);
+        }
+    }
+    if !args.only.is_empty() {
+        let (kept, skipped) = match expanded {
+            Ok(targets) => filter_kinds(targets, &args.only),
+            Err(e) => {
+                eprintln!("- targets could not be expanded: {}", e);
+                exit(1);
+            }
+        };
+        if args.v {
+            skipped.iter().for_each(|t| eprintln!("- '{}' was skipped as it is a {}", t.user_input, t.kind.label()));
+        }
+        fingerprints = kept.clone();
+        expanded = Ok(kept);
+    }
+
+    if let Some(file) = &args.repl {
+        match load_md(file, &config) {
+            Ok(doc) => {
+                eprintln!("- '{}' was loaded; type `help` for the list of commands", file);
+                if let Err(e) = repl::run(&doc, io::stdin().lock(), io::stdout()) {
+                    eprintln!("- the REPL failed: {}", e);
+                    exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("- '{}' could not be loaded: {}", file, e);
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(files) = &args.compare {
+        match (load_md(&files[0], &config), load_md(&files[1], &config)) {
+            (Ok(a), Ok(b)) => println!("{}", render_json(&json!(compare(&a, &b)), &config)),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("- unable to compare files: {}", e);
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.list_index_pages {
+        let mut warnings: Vec<Warning> = vec![];
+        for t in fingerprints.iter().filter(|t| t.kind == Fingerprint::Directory) {
+            match index_pages_in_dir(&t.user_input, &config, &mut warnings) {
+                Ok(pages) => pages.iter().for_each(|p| println!("{}", p)),
+                Err(e) => warnings.push(Warning::new(&t.user_input, &format!("could not be scanned: {}", e))),
+            }
+        }
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.multi_doc {
+        let mut warnings: Vec<Warning> = vec![];
+        let files: Vec<Value> = args.targets
+            .iter()
+            .filter_map(|target| {
+                let parsed = FileMeta::try_from(target.as_str())
+                    .and_then(FileWithMeta::try_from)
+                    .map_err(|e| e.to_string())
+                    .and_then(|file| Frontmatter::parse_documents(&file.content).map_err(|e| e.to_string()));
+                match parsed {
+                    Ok(records) => Some(json!({ "file": target, "records": records })),
+                    Err(e) => {
+                        warnings.push(Warning::new(target, &format!("could not be parsed as YAML documents: {}", e)));
+                        None
+                    }
+                }
+            })
+            .collect();
+        println!("{}", render_json(&json!(files), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    let mut warnings = unknown_fingerprint_warnings(&fingerprints);
+
+    if args.fingerprint_only {
+        match expanded {
+            Ok(expanded) => println!("{}", render_json(&json!(census(&expanded)), &config)),
+            Err(e) => warnings.push(Warning::new("", &format!("targets could not be expanded: {}", e))),
+        }
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.hash_dir {
+        let hashes: serde_json::Map<String, Value> = fingerprints
+            .iter()
+            .filter(|t| t.kind == Fingerprint::Directory)
+            .filter_map(|t| match hash_directory_with(&t.user_input, true, config.hidden) {
+                Ok(h) => Some((t.user_input.clone(), json!(h))),
+                Err(e) => {
+                    warnings.push(Warning::new(&t.user_input, &format!("could not be hashed: {}", e)));
+                    None
+                }
+            })
+            .collect();
+        println!("{}", render_json(&Value::Object(hashes), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.meta_only {
+        let expanded = expanded.unwrap_or_else(|e| {
+            warnings.push(Warning::new("", &format!("targets could not be expanded: {}", e)));
+            vec![]
+        });
+        let meta: Vec<FileMeta> = expanded
+            .iter()
+            .filter(|t| t.kind != Fingerprint::RemoteUrl)
+            .filter(|t| t.kind != Fingerprint::Unknown || Path::new(&t.user_input).is_file())
+            .filter_map(|t| match FileMeta::try_from(&t.user_input) {
+                Ok(meta) => Some(meta),
+                Err(e) => {
+                    warnings.push(Warning::new(&t.user_input, &format!("could not be processed: {}", e)));
+                    None
+                }
+            })
+            .collect();
+        println!("{}", render_json(&json!(meta), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    // the remaining modes load content anyway so the targets which weren't
+    // recognized by name are fingerprinted by their content
+    fingerprints = fingerprints.iter().map(sniff_target).collect();
+    warnings = unknown_fingerprint_warnings(&fingerprints);
+
+    if args.auth_report {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        println!("{}", render_json(&json!(auth_report(&docs)), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.write {
+        match config.typography {
+            Some(mode) => for doc in collect_md_docs(&fingerprints, &config, &mut warnings) {
+                let file = doc.file.as_ref().map(|f| f.filename().to_string()).unwrap_or_default();
+                match rewrite_prose(&doc, &config, |prose| prose.apply_typography(mode)) {
+                    Ok(true) => eprintln!("- '{}' was rewritten", file),
+                    Ok(false) => {}
+                    Err(e) => warnings.push(Warning::new(&file, &format!("could not be rewritten: {}", e))),
+                }
+            },
+            None => warnings.push(Warning::new("", "there is nothing to write without a `--typography` mode")),
+        }
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.outline {
+        for doc in collect_md_docs(&fingerprints, &config, &mut warnings) {
+            println!("{}\n", doc.file.as_ref().map(|f| f.filename()).unwrap_or_default());
+            println!("{}", doc.outline_markdown(args.line_numbers));
+        }
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.link_graph {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        println!("{}", render_json(&json!(link_graph(&docs)), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.category_tree {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        println!("{}", render_json(&json!(FrontmatterIndex::from_docs(&docs).category_tree()), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.sidecar || args.sidecar_dir.is_some() {
+        for doc in collect_md_docs(&fingerprints, &config, &mut warnings) {
+            let Some(source) = doc.file.as_ref().map(|f| f.filename().to_string()) else {
+                continue;
+            };
+            let path = sidecar_path(&source, args.sidecar_dir.as_deref());
+            let report = md_report(&doc, &config, &mut warnings);
+            let report = if config.sort_keys { sort_keys(report) } else { report };
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(|e| IoError::WriteFailedWith(path.display().to_string(), e))
+                .and_then(|_| write_output(&path.display().to_string(), &report, None, config.indent));
+            match written {
+                Ok(()) => eprintln!("- '{}' was written", path.display()),
+                Err(e) => warnings.push(Warning::new(&source, &format!("sidecar could not be written: {}", e))),
+            }
+        }
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if let Some(max_age) = args.stale {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        println!("{}", render_json(&json!(stale_docs(&docs, max_age, SystemTime::now())), &config));
+        conclude(&warnings, &config);
+        return;
+    }
 
-    let fingerprints: Vec<Target> = args.targets.iter().map(|i| fingerprint(&i)).collect();
-    let _results = process_known_fingerprints(&fingerprints);
-    let _had_unknown = warn_about_unknown_fingerprints(&fingerprints);
+    if args.taxonomy {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        println!("{}", render_json(&json!(taxonomy(&docs)), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.quality {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        let reports: Vec<_> = docs.iter().map(|doc| doc.quality(&config.quality_weights)).collect();
+        println!("{}", render_json(&json!({ "documents": reports, "summary": quality_summary(&reports) }), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.use_cache {
+        let mut cache = FileCache::load(FILE_CACHE).unwrap_or_else(|e| {
+            warnings.push(Warning::new(FILE_CACHE, &format!("could not be loaded so every file is processed: {}", e)));
+            FileCache::default()
+        });
+        let expanded = expanded.unwrap_or_else(|e| {
+            warnings.push(Warning::new("", &format!("targets could not be expanded: {}", e)));
+            vec![]
+        });
+        let mut reports: Vec<Value> = vec![];
+        for t in expanded.iter().filter(|t| t.kind == Fingerprint::MarkdownFile) {
+            let loaded = FileMeta::try_from(&t.user_input).map_err(|e| e.into()).and_then(|meta| load_file(meta, &config));
+            match loaded {
+                Ok(file) if !cache.has_changed(&file) => {
+                    eprintln!("- '{}' was skipped as it is unchanged since the last run", t.user_input);
+                }
+                Ok(file) => {
+                    cache.update(&file);
+                    match MarkdownDoc::from_file_with(file, &config) {
+                        Ok(doc) => reports.push(md_report(&doc, &config, &mut warnings)),
+                        Err(e) => warnings.push(Warning::new(&t.user_input, &format!("could not be processed: {}", e))),
+                    }
+                }
+                Err(e) => warnings.push(Warning::new(&t.user_input, &format!("could not be processed: {}", e))),
+            }
+        }
+        if let Err(e) = cache.save(FILE_CACHE) {
+            warnings.push(Warning::new(FILE_CACHE, &format!("could not be saved: {}", e)));
+        }
+        println!("{}", render_json(&json!(reports), &config));
+        conclude(&warnings, &config);
+        return;
+    }
+
+    if args.min_word_count.is_some() || args.max_word_count_drop_percent.is_some() {
+        let docs = collect_md_docs(&fingerprints, &config, &mut warnings);
+        let checks: Vec<_> = docs
+            .iter()
+            .map(|doc| {
+                let old = previous_version(doc, &args, &config, &mut warnings);
+                check_word_count(doc, old.as_ref(), args.min_word_count, args.max_word_count_drop_percent)
+            })
+            .collect();
+        println!("{}", render_json(&json!(checks), &config));
+        conclude(&warnings, &config);
+        if checks.iter().any(|c| !c.failures.is_empty()) {
+            checks
+                .iter()
+                .flat_map(|c| c.failures.iter().map(|f| (c.file.clone().unwrap_or_default(), f)))
+                .for_each(|(file, f)| eprintln!("- '{}' {}", file, f));
+            exit(1);
+        }
+        return;
+    }
+
+    let progress = Progress::stderr(fingerprints.len(), args.quiet);
+    let mut results = process_known_fingerprints(&fingerprints, &config, &progress, &mut warnings);
+    if let Some(field) = config.sort {
+        results = sort_results(results, field, config.reverse);
+    }
+    if let Some(pointer) = &args.query {
+        query_results(results, pointer, args.skip_missing).iter().for_each(|line| println!("{}", line));
+        conclude(&warnings, &config);
+        return;
+    }
+    if config.envelope {
+        results = envelope(results, &config);
+    }
+    match &args.out {
+        Some(path) => {
+            let results = if config.sort_keys { sort_keys(results) } else { results };
+            if let Err(e) = write_output(path, &results, args.compress, config.indent) {
+                warnings.push(Warning::new(path, &format!("output could not be written: {}", e)));
+            }
+        }
+        None => println!("{}", render_json(&results, &config)),
+    }
 
-    
+    conclude(&warnings, &config);
 }
@@ -0,0 +1,62 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use super::prose::Prose;
+
+lazy_static! {
+    /// GitHub style callouts: `> [!NOTE] optional title`
+    static ref GITHUB_CALLOUT: Regex = Regex::new(r"^\s*>\s*\[!([A-Za-z\-]+)\][+\-]?\s*(.*)$").unwrap();
+    /// Docusaurus style admonitions: `:::note optional title`
+    static ref DOCUSAURUS_CALLOUT: Regex = Regex::new(r"^\s*:::\s*([A-Za-z\-]+)\s*(.*)$").unwrap();
+}
+
+/// A callout (aka, admonition) block found in the prose
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Callout {
+    /// the lowercased label of the callout (e.g., `note`, `warning`, `tip`);
+    /// labels which are not well known are kept as they were written
+    pub kind: String,
+    /// an explicit title given to the callout
+    pub title: Option<String>,
+    /// the 1-based line number on which the callout starts
+    pub line: usize,
+}
+
+impl Prose {
+    /// Finds all GitHub (`> [!NOTE]`) and Docusaurus (`:::note`) style
+    /// callouts in the prose; fenced code blocks are ignored.
+    pub fn callouts(&self) -> Vec<Callout> {
+        self.lines_outside_fences()
+            .into_iter()
+            .filter_map(|(line, text)| {
+                let caps = GITHUB_CALLOUT
+                    .captures(text)
+                    .or_else(|| DOCUSAURUS_CALLOUT.captures(text))?;
+                let title = caps[2].trim();
+
+                Some(Callout {
+                    kind: caps[1].to_lowercase(),
+                    title: if title.is_empty() { None } else { Some(title.to_string()) },
+                    line,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn github_and_docusaurus_callouts_are_found() {
+        let prose = Prose::from("> [!NOTE]\n> hi\n\n:::warning Careful now\nstuff\n:::\n\n> [!SHINY] Custom\n");
+        let callouts = prose.callouts();
+
+        assert_eq!(callouts.len(), 3);
+        assert_eq!(callouts[0], Callout { kind: "note".to_string(), title: None, line: 1 });
+        assert_eq!(callouts[1], Callout { kind: "warning".to_string(), title: Some("Careful now".to_string()), line: 4 });
+        assert_eq!(callouts[2].kind, "shiny");
+    }
+}
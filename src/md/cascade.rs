@@ -0,0 +1,134 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use serde_json::Value;
+
+use crate::errors::md::MarkdownError;
+
+use super::{frontmatter::Frontmatter, markdown::MarkdownDoc};
+
+/// the name of the file whose frontmatter cascades to the other documents
+/// in its directory and the directories below it
+pub const INDEX_FILE: &str = "_index.md";
+
+/// frontmatter properties which identify a single page and so do not
+/// cascade from an index file
+const NOT_CASCADED: [&str; 4] = ["title", "aliases", "index", "slug"];
+
+impl Frontmatter {
+    /// A copy of the frontmatter where each property it doesn't set is
+    /// taken from `defaults`.
+    pub fn with_defaults(&self, defaults: &Frontmatter) -> Result<Frontmatter, MarkdownError> {
+        let mut merged = serde_json::to_value(defaults)?;
+        if let (Some(merged), Value::Object(own)) = (merged.as_object_mut(), serde_json::to_value(self)?) {
+            merged.extend(own);
+        }
+
+        Frontmatter::new(Some(merged))
+    }
+
+    /// the frontmatter which cascades from an index file: all of its
+    /// properties other than those which identify the page itself
+    fn cascaded(&self) -> Result<Frontmatter, MarkdownError> {
+        let mut json = serde_json::to_value(self)?;
+        if let Some(props) = json.as_object_mut() {
+            NOT_CASCADED.iter().for_each(|k| { props.remove(*k); });
+        }
+
+        Frontmatter::new(Some(json))
+    }
+}
+
+impl MarkdownDoc {
+    /// Fills in the frontmatter properties the document doesn't set from
+    /// `defaults`; a document without frontmatter takes all of them.
+    pub fn inherit_frontmatter(&mut self, defaults: &Frontmatter) -> Result<(), MarkdownError> {
+        let fm = self.fm.take().unwrap_or_default().with_defaults(defaults)?;
+        self.fm = Some(fm);
+        self.frontmatter_changed();
+
+        Ok(())
+    }
+}
+
+/// Cascades the frontmatter of each `_index.md` found among the documents
+/// of the directory `root` into every other document in its directory and
+/// the directories below it. Index files closer to a document override
+/// those further up the tree while the document's own frontmatter always
+/// wins; an index file inherits from the index files above it.
+pub fn cascade_index_frontmatter(docs: &mut [MarkdownDoc], root: &str) -> Result<(), MarkdownError> {
+    let path_of = |doc: &MarkdownDoc| doc.file.as_ref().map(|f| PathBuf::from(f.filename()));
+    let mut indexes: HashMap<PathBuf, Frontmatter> = HashMap::new();
+    for doc in docs.iter() {
+        let Some(path) = path_of(doc) else { continue };
+        if let (Some(true), Some(dir), Some(fm)) = (path.file_name().map(|n| n == INDEX_FILE), path.parent(), &doc.fm) {
+            indexes.insert(dir.to_path_buf(), fm.cascaded()?);
+        }
+    }
+    if indexes.is_empty() {
+        return Ok(());
+    }
+
+    let root = Path::new(root);
+    for doc in docs.iter_mut() {
+        let Some(path) = path_of(doc) else { continue };
+        let Some(dir) = path.parent() else { continue };
+        // an index file inherits from the directories above its own
+        let start = match path.file_name().is_some_and(|n| n == INDEX_FILE) {
+            true => dir.parent(),
+            false => Some(dir),
+        };
+        let mut cascade: Option<Frontmatter> = None;
+        for ancestor in start.into_iter().flat_map(|d| d.ancestors()).take_while(|d| d.starts_with(root)) {
+            if let Some(index) = indexes.get(ancestor) {
+                cascade = Some(match cascade {
+                    Some(closer) => closer.with_defaults(index)?,
+                    None => index.clone(),
+                });
+            }
+        }
+        if let Some(defaults) = cascade {
+            doc.inherit_frontmatter(&defaults)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, md::reporting::md_docs_in_dir};
+
+    #[test]
+    fn section_index_frontmatter_cascades_to_child_docs() {
+        let dir = std::env::temp_dir().join(format!("ctx-cascade-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("guides/advanced")).unwrap();
+        let write = |file: &str, content: &str| std::fs::write(dir.join(file), content).unwrap();
+        write("_index.md", "---\ntitle: Docs\ncategory: Docs\nlayout: page\n---\n# Docs\n");
+        write("guides/_index.md", "---\ntitle: Guides\ncategory: Guides\n---\n# Guides\n");
+        write("guides/felling.md", "---\ntitle: Felling\n---\n# Felling\n");
+        write("guides/advanced/limbing.md", "---\ncategory: Advanced\n---\n# Limbing\n");
+        write("about.md", "# About\n");
+
+        let config = Config { cascade_fm: true, ..Config::default() };
+        let docs = md_docs_in_dir(&dir.to_string_lossy(), &config, &mut vec![]).unwrap();
+        let uncascaded = md_docs_in_dir(&dir.to_string_lossy(), &Config::default(), &mut vec![]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let find = |docs: &[MarkdownDoc], name: &str| docs
+            .iter()
+            .find(|d| d.file.as_ref().is_some_and(|f| f.filename().ends_with(name)))
+            .and_then(|d| d.fm.clone())
+            .unwrap_or_default();
+        let fm = |name: &str| find(&docs, name);
+        assert_eq!(fm("guides/felling.md").category.as_deref(), Some("Guides"));
+        assert_eq!(fm("guides/felling.md").title.as_deref(), Some("Felling"));
+        assert_eq!(fm("guides/felling.md").layout.as_deref(), Some("page"));
+        assert_eq!(fm("advanced/limbing.md").category.as_deref(), Some("Advanced"));
+        assert_eq!(fm("advanced/limbing.md").title, None);
+        assert_eq!(fm("guides/_index.md").category.as_deref(), Some("Guides"));
+        assert_eq!(fm("guides/_index.md").layout.as_deref(), Some("page"));
+        assert_eq!(fm("about.md").category.as_deref(), Some("Docs"));
+        assert_eq!(find(&uncascaded, "guides/felling.md").category, None);
+    }
+}
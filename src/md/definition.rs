@@ -0,0 +1,128 @@
+use serde::{Serialize, Deserialize};
+
+use super::prose::Prose;
+
+/// A term and its definition from a definition list
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Definition {
+    pub term: String,
+    pub body: String,
+}
+
+/// A term from a definition list along with all of its definitions
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DefinitionItem {
+    pub term: String,
+    pub definitions: Vec<String>,
+}
+
+impl Prose {
+    /// Extracts the PHP Markdown Extra style definition lists -- one or
+    /// more term lines followed by `: definition` lines -- from the prose.
+    /// A term with several definitions (or several terms sharing one
+    /// definition) produces a `Definition` for each pairing; indented lines
+    /// continue the definition above them.
+    pub fn extract_definitions(&self) -> Vec<Definition> {
+        let mut definitions: Vec<Definition> = vec![];
+        let mut candidates: Vec<String> = vec![];
+        let mut terms: Vec<String> = vec![];
+        let mut body: Option<String> = None;
+        let mut after_blank = false;
+
+        let mut flush = |body: &mut Option<String>, terms: &[String]| {
+            if let Some(body) = body.take() {
+                terms.iter().for_each(|t| definitions.push(Definition { term: t.clone(), body: body.clone() }));
+            }
+        };
+
+        for (_, line) in self.lines_outside_fences() {
+            if let Some(definition) = line.strip_prefix(": ").or_else(|| line.strip_prefix(":\t")) {
+                flush(&mut body, &terms);
+                if !candidates.is_empty() {
+                    terms = std::mem::take(&mut candidates);
+                }
+                if !terms.is_empty() {
+                    body = Some(definition.trim().to_string());
+                }
+                after_blank = false;
+            } else if line.trim().is_empty() {
+                after_blank = true;
+            } else if body.is_some() && line.starts_with([' ', '\t']) {
+                if let Some(b) = body.as_mut() {
+                    b.push(' ');
+                    b.push_str(line.trim());
+                }
+            } else {
+                flush(&mut body, &terms);
+                terms.clear();
+                if after_blank {
+                    candidates.clear();
+                }
+                candidates.push(line.trim().to_string());
+                after_blank = false;
+            }
+        }
+        flush(&mut body, &terms);
+
+        definitions
+    }
+
+    /// The terms of the pandoc style definition lists in the prose -- in the
+    /// order they are first defined -- each with all of its definitions;
+    /// fenced code blocks are ignored.
+    pub fn definition_lists(&self) -> Vec<DefinitionItem> {
+        let mut items: Vec<DefinitionItem> = vec![];
+        for Definition { term, body } in self.extract_definitions() {
+            match items.iter_mut().find(|i| i.term == term) {
+                Some(item) => item.definitions.push(body),
+                None => items.push(DefinitionItem { term, definitions: vec![body] }),
+            }
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definition_lists_are_extracted() {
+        let prose = Prose::from("# Glossary\n\nBuck\n: To cut a felled tree\n  into logs.\n\nLimb\nDelimb\n\n: To remove branches.\n: To trim.\n\nJust a paragraph.\n\n```\nCode\n: not a definition\n```\n");
+        let definitions = prose.extract_definitions();
+        let pairs: Vec<(&str, &str)> = definitions.iter().map(|d| (d.term.as_str(), d.body.as_str())).collect();
+
+        assert_eq!(pairs, vec![
+            ("Buck", "To cut a felled tree into logs."),
+            ("Limb", "To remove branches."),
+            ("Delimb", "To remove branches."),
+            ("Limb", "To trim."),
+            ("Delimb", "To trim."),
+        ]);
+    }
+
+    #[test]
+    fn definitions_are_grouped_by_term() {
+        let prose = Prose::from("Fell
+: To cut down a tree.
+: To knock down.
+
+Buck
+: To cut into logs.
+
+```
+Log
+: not a definition
+```
+");
+
+        assert_eq!(prose.definition_lists(), vec![
+            DefinitionItem {
+                term: "Fell".to_string(),
+                definitions: vec!["To cut down a tree.".to_string(), "To knock down.".to_string()],
+            },
+            DefinitionItem { term: "Buck".to_string(), definitions: vec!["To cut into logs.".to_string()] },
+        ]);
+    }
+}
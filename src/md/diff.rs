@@ -0,0 +1,109 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value, json};
+
+use super::markdown::MarkdownDoc;
+
+/// A change to a single frontmatter property between two documents; a
+/// `None` value indicates the property was not present on that side.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FmChange {
+    pub key: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// A structural -- rather than textual -- diff between two markdown documents
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarkdownDiff {
+    /// headings found in the second document but not the first
+    pub added_headings: Vec<String>,
+    /// headings found in the first document but not the second
+    pub removed_headings: Vec<String>,
+    /// frontmatter properties which were added, removed or changed
+    pub fm_changes: Vec<FmChange>,
+    /// whether the prose content changed
+    pub prose_changed: bool,
+}
+
+impl MarkdownDiff {
+    /// whether the two documents compared were structurally identical
+    pub fn is_empty(&self) -> bool {
+        self.added_headings.is_empty()
+            && self.removed_headings.is_empty()
+            && self.fm_changes.is_empty()
+            && !self.prose_changed
+    }
+}
+
+fn fm_map(doc: &MarkdownDoc) -> Map<String, Value> {
+    match doc.fm.as_ref().map(|fm| json!(fm)) {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn heading_texts(doc: &MarkdownDoc) -> Vec<String> {
+    doc.prose.headings().into_iter().map(|h| h.text).collect()
+}
+
+/// Compares two markdown documents and reports on the structural differences
+/// between them: added/removed headings, frontmatter changes and whether the
+/// prose changed.
+pub fn compare(a: &MarkdownDoc, b: &MarkdownDoc) -> MarkdownDiff {
+    let a_headings = heading_texts(a);
+    let b_headings = heading_texts(b);
+
+    let a_fm = fm_map(a);
+    let b_fm = fm_map(b);
+    let mut keys: Vec<&String> = a_fm.keys().chain(b_fm.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let fm_changes = keys
+        .into_iter()
+        .filter(|k| a_fm.get(*k) != b_fm.get(*k))
+        .map(|k| FmChange {
+            key: k.clone(),
+            before: a_fm.get(k).cloned(),
+            after: b_fm.get(k).cloned(),
+        })
+        .collect();
+
+    MarkdownDiff {
+        added_headings: b_headings.iter().filter(|h| !a_headings.contains(h)).cloned().collect(),
+        removed_headings: a_headings.iter().filter(|h| !b_headings.contains(h)).cloned().collect(),
+        fm_changes,
+        prose_changed: a.prose.content != b.prose.content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = "---\ntitle: Before\nfoo: 1\n---\n# Intro\n\n## Setup\n\ntext\n";
+    const AFTER: &str = "---\ntitle: Before\nfoo: 2\n---\n# Intro\n\n## Install\n\ntext\n";
+
+    #[test]
+    fn heading_and_fm_changes_are_reported() {
+        let a = MarkdownDoc::try_from(BEFORE).unwrap();
+        let b = MarkdownDoc::try_from(AFTER).unwrap();
+        let diff = compare(&a, &b);
+
+        assert_eq!(diff.added_headings, vec!["Install".to_string()]);
+        assert_eq!(diff.removed_headings, vec!["Setup".to_string()]);
+        assert_eq!(
+            diff.fm_changes,
+            vec![FmChange { key: "foo".to_string(), before: Some(json!(1)), after: Some(json!(2)) }]
+        );
+        assert!(diff.prose_changed);
+    }
+
+    #[test]
+    fn identical_docs_have_empty_diff() {
+        let a = MarkdownDoc::try_from(BEFORE).unwrap();
+        let b = MarkdownDoc::try_from(BEFORE).unwrap();
+
+        assert!(compare(&a, &b).is_empty());
+    }
+}
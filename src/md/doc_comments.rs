@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+/// The languages of source files whose comments can hold markdown
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceLanguage {
+    /// `///` and `//!` line comments as well as `/** ... */` blocks
+    Rust,
+    /// `#` line comments (a leading `#!` shebang is ignored)
+    Python,
+    /// `/** ... */` (JSDoc) blocks
+    JavaScript,
+}
+
+impl SourceLanguage {
+    /// the language implied by the extension of a source file
+    pub fn from_path(path: &str) -> Option<Self> {
+        match Path::new(path).extension()?.to_str()? {
+            "rs" => Some(SourceLanguage::Rust),
+            "py" => Some(SourceLanguage::Python),
+            "js" => Some(SourceLanguage::JavaScript),
+            _ => None,
+        }
+    }
+
+    /// the prefixes of the line comments which hold markdown
+    fn line_prefixes(&self) -> &'static [&'static str] {
+        match self {
+            SourceLanguage::Rust => &["///", "//!"],
+            SourceLanguage::Python => &["#"],
+            SourceLanguage::JavaScript => &[],
+        }
+    }
+
+    /// whether `/** ... */` block comments hold markdown
+    fn has_doc_blocks(&self) -> bool {
+        matches!(self, SourceLanguage::Rust | SourceLanguage::JavaScript)
+    }
+}
+
+/// the text of a line comment once its prefix and a single space are removed
+fn strip_prefix<'a>(line: &'a str, prefix: &str) -> &'a str {
+    let text = &line[prefix.len()..];
+    text.strip_prefix(' ').unwrap_or(text)
+}
+
+/// the text of a line within a `/** ... */` block once the conventional
+/// leading `*` (and a single space) is removed
+fn strip_block_line(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    match trimmed.strip_prefix('*') {
+        Some(text) => text.strip_prefix(' ').unwrap_or(text),
+        None => trimmed,
+    }
+}
+
+/// Extracts the markdown held in the doc comments of source code; each
+/// run of comment lines (or comment block) becomes a paragraph-separated
+/// block of the returned markdown.
+pub fn extract_doc_comments(source: &str, language: SourceLanguage) -> String {
+    let mut lines: Vec<&str> = vec![];
+    let mut in_block = false;
+    let end_run = |lines: &mut Vec<&str>| {
+        if lines.last().is_some_and(|l| !l.is_empty()) {
+            lines.push("");
+        }
+    };
+
+    for (idx, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        if in_block {
+            match trimmed.find("*/") {
+                Some(end) => {
+                    let text = strip_block_line(&trimmed[..end]).trim_end();
+                    if !text.is_empty() {
+                        lines.push(text);
+                    }
+                    in_block = false;
+                    end_run(&mut lines);
+                }
+                None => lines.push(strip_block_line(line).trim_end()),
+            }
+        } else if language.has_doc_blocks() && trimmed.starts_with("/**") && !trimmed.starts_with("/**/") {
+            let rest = &trimmed[3..];
+            match rest.find("*/") {
+                Some(end) => {
+                    lines.push(rest[..end].trim());
+                    end_run(&mut lines);
+                }
+                None => {
+                    in_block = true;
+                    if !rest.trim().is_empty() {
+                        lines.push(rest.trim());
+                    }
+                }
+            }
+        } else if let Some(prefix) = language.line_prefixes().iter().find(|p| trimmed.starts_with(**p)) {
+            let shebang = idx == 0 && trimmed.starts_with("#!");
+            if !shebang {
+                lines.push(strip_prefix(trimmed, prefix).trim_end());
+            }
+        } else {
+            end_run(&mut lines);
+        }
+    }
+
+    let markdown = lines.join("\n");
+    match markdown.trim_matches('\n') {
+        "" => String::new(),
+        markdown => format!("{}\n", markdown),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_doc_comments_are_extracted() {
+        let source = "//! # Crate\n//!\n//! About it\n\nuse x;\n\n/// Adds `a` to `b`\n// not a doc comment\nfn add() {}\n\n/**\n * - one\n * - two\n */\nfn list() {}\n";
+
+        assert_eq!(
+            extract_doc_comments(source, SourceLanguage::Rust),
+            "# Crate\n\nAbout it\n\nAdds `a` to `b`\n\n- one\n- two\n"
+        );
+    }
+
+    #[test]
+    fn python_and_javascript_comment_syntax() {
+        let python = "#!/usr/bin/env python\n# ## Usage\n#\n# Run it\nimport os\n";
+        let javascript = "/** # Title */\nconst a = 1; // trailing\n/**\n * Some *emphasis*\n */\n";
+
+        assert_eq!(extract_doc_comments(python, SourceLanguage::Python), "## Usage\n\nRun it\n");
+        assert_eq!(extract_doc_comments(javascript, SourceLanguage::JavaScript), "# Title\n\nSome *emphasis*\n");
+        assert_eq!(SourceLanguage::from_path("src/lib.rs"), Some(SourceLanguage::Rust));
+        assert_eq!(SourceLanguage::from_path("notes.txt"), None);
+    }
+}
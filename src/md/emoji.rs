@@ -0,0 +1,74 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use super::prose::Prose;
+
+lazy_static! {
+    static ref SHORTCODE: Regex = Regex::new(r":[a-z0-9_+\-]+:").unwrap();
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmojiKind {
+    /// an emoji expressed as a `:shortcode:`
+    Shortcode,
+    /// a raw Unicode emoji character
+    Unicode,
+}
+
+/// A single use of an emoji within the prose of a document
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EmojiUse {
+    pub kind: EmojiKind,
+    pub value: String,
+    /// the 1-based line number where the emoji was found
+    pub line: usize,
+}
+
+/// whether the character falls into one of the Unicode blocks which are
+/// predominantly made up of emoji
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B50 | 0x2B55 | 0x2934 | 0x2935
+    )
+}
+
+impl Prose {
+    /// Finds all `:shortcode:` and raw Unicode emoji used in the prose;
+    /// content within fenced code blocks is ignored.
+    pub fn emoji(&self) -> Vec<EmojiUse> {
+        let mut found: Vec<EmojiUse> = vec![];
+
+        for (line, text) in self.lines_outside_fences() {
+            SHORTCODE.find_iter(text).for_each(|m| found.push(EmojiUse {
+                kind: EmojiKind::Shortcode,
+                value: m.as_str().to_string(),
+                line,
+            }));
+            text.chars().filter(|c| is_emoji(*c)).for_each(|c| found.push(EmojiUse {
+                kind: EmojiKind::Unicode,
+                value: c.to_string(),
+                line,
+            }));
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcodes_and_unicode_found_with_lines() {
+        let prose = Prose::from("# Launch :rocket:\n\nShip it 🚀\n\n```\nlet a = :not_me:;\n```\n");
+        let emoji = prose.emoji();
+
+        assert_eq!(emoji.len(), 2);
+        assert_eq!(emoji[0], EmojiUse { kind: EmojiKind::Shortcode, value: ":rocket:".to_string(), line: 1 });
+        assert_eq!(emoji[1], EmojiUse { kind: EmojiKind::Unicode, value: "🚀".to_string(), line: 3 });
+    }
+}
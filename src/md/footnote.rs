@@ -0,0 +1,91 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use super::prose::Prose;
+
+lazy_static! {
+    /// a footnote definition: `[^label]: text`
+    static ref FOOTNOTE_DEF: Regex = Regex::new(r"^\s{0,3}\[\^([^\]\s]+)\]:\s*(.*)$").unwrap();
+    /// a footnote reference: `[^label]`
+    static ref FOOTNOTE_REF: Regex = Regex::new(r"\[\^([^\]\s]+)\]").unwrap();
+}
+
+/// A GFM style footnote found in the prose
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Footnote {
+    pub label: String,
+    /// the text of the footnote's definition; `None` when the footnote is
+    /// referenced but never defined
+    pub definition: Option<String>,
+    /// the number of times the footnote is referenced; a definition which
+    /// is never referenced has zero references
+    pub references: usize,
+}
+
+impl Footnote {
+    /// whether the footnote is referenced without being defined
+    pub fn is_undefined(&self) -> bool {
+        self.definition.is_none()
+    }
+
+    /// whether the footnote is defined without ever being referenced
+    pub fn is_unreferenced(&self) -> bool {
+        self.references == 0
+    }
+}
+
+impl Prose {
+    /// All footnotes -- referenced and/or defined -- in the order they are
+    /// first seen; fenced code blocks are ignored.
+    pub fn footnotes(&self) -> Vec<Footnote> {
+        let mut footnotes: Vec<Footnote> = vec![];
+        let mut entry = |label: &str| -> usize {
+            match footnotes.iter().position(|f| f.label == label) {
+                Some(idx) => idx,
+                None => {
+                    footnotes.push(Footnote { label: label.to_string(), definition: None, references: 0 });
+                    footnotes.len() - 1
+                }
+            }
+        };
+        let mut definitions: Vec<(usize, String)> = vec![];
+        let mut references: Vec<usize> = vec![];
+
+        for (_, line) in self.lines_outside_fences() {
+            let text = match FOOTNOTE_DEF.captures(line) {
+                Some(def) => {
+                    definitions.push((entry(&def[1]), def[2].trim().to_string()));
+                    def.get(2).map(|m| m.as_str()).unwrap_or_default()
+                }
+                None => line,
+            };
+            references.extend(FOOTNOTE_REF.captures_iter(text).map(|r| entry(&r[1])));
+        }
+
+        for (idx, definition) in definitions {
+            footnotes[idx].definition.get_or_insert(definition);
+        }
+        references.into_iter().for_each(|idx| footnotes[idx].references += 1);
+
+        footnotes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undefined_and_unreferenced_footnotes_are_reported() {
+        let prose = Prose::from("A claim[^1] and another[^missing], again[^1].\n\n```\n[^code]\n```\n\n[^1]: The source.\n[^orphan]: Never used.\n");
+        let footnotes = prose.footnotes();
+
+        assert_eq!(footnotes.len(), 3);
+        assert_eq!(footnotes[0], Footnote { label: "1".to_string(), definition: Some("The source.".to_string()), references: 2 });
+        assert!(footnotes[1].is_undefined());
+        assert_eq!(footnotes[1].references, 1);
+        assert!(footnotes[2].is_unreferenced());
+        assert_eq!(footnotes[2].definition, Some("Never used.".to_string()));
+    }
+}
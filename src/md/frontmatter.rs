@@ -2,20 +2,311 @@
 
 use crate::errors::md::MarkdownError;
 use tracing::{debug, instrument};
-use gray_matter::engine::YAML;
+use gray_matter::engine::{Engine, JSON, TOML, YAML};
 use gray_matter::Matter;
+use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FrontmatterEngineType {
     YAML,
     JSON,
     TOML,
 }
 
+/// The opening and closing delimiters which surround a frontmatter block
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FmFence {
+    pub open: String,
+    pub close: String,
+}
+
+impl FmFence {
+    pub fn new(open: &str, close: &str) -> Self {
+        FmFence {
+            open: open.to_string(),
+            close: close.to_string(),
+        }
+    }
+
+    /// the engine used to parse frontmatter within this fence; `+++` fences
+    /// hold TOML while all others are expected to hold YAML
+    pub fn engine(&self) -> FrontmatterEngineType {
+        match self.open.as_str() {
+            "+++" => FrontmatterEngineType::TOML,
+            _ => FrontmatterEngineType::YAML,
+        }
+    }
+
+    fn regex(&self) -> Regex {
+        Regex::new(&format!(
+            r"(?s)^{}[ \t]*\r?\n(?:(.*?)\r?\n)?{}[ \t]*(?:\r?\n|$)(.*)",
+            regex::escape(&self.open),
+            regex::escape(&self.close)
+        )).unwrap()
+    }
+
+    /// Splits the raw content of a markdown file into the text _inside_ of
+    /// the frontmatter fence and the remaining content; `None` is returned
+    /// when the content does not start with this fence.
+    pub fn split<'a>(&self, raw: &'a str) -> Option<(&'a str, &'a str)> {
+        self.regex().captures(raw).map(|c| (
+            c.get(1).map(|m| m.as_str()).unwrap_or_default(),
+            c.get(2).map(|m| m.as_str()).unwrap_or_default(),
+        ))
+    }
+}
+
+/// the fences recognized when none are configured: `---` (YAML) and
+/// `+++` (TOML)
+pub fn default_fences() -> Vec<FmFence> {
+    vec![FmFence::new("---", "---"), FmFence::new("+++", "+++")]
+}
+
+/// the typed boolean properties -- in both their camelCase and snake_case
+/// spellings -- which lenient parsing will coerce from strings
+pub const TYPED_BOOLEAN_FIELDS: [&str; 2] = ["requiresAuth", "requires_auth"];
+
+/// Interprets the common truthy (`true`, `yes`, `on`, `1`) and falsy
+/// (`false`, `no`, `off`, `0`) strings -- ignoring case and surrounding
+/// whitespace -- as a boolean.
+pub fn coerce_bool(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// the typed properties which hold a single string
+const STRING_FIELDS: [&str; 11] = [
+    "title", "description", "subject", "category", "name", "excerpt", "image", "icon", "layout", "language",
+    "expires",
+];
+
+/// Converts the textual `value` given for `field` into a JSON value: typed
+/// properties take the value as their own type (lists are comma separated)
+/// while the type of any other property is inferred as a boolean, number
+/// or -- failing those -- a string.
+pub fn typed_value(field: &str, value: &str) -> Result<Value, MarkdownError> {
+    if STRING_FIELDS.contains(&field) {
+        return Ok(json!(value));
+    }
+    if field == "tags" || field == "aliases" {
+        let list: Vec<&str> = value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()).collect();
+        return Ok(json!(list));
+    }
+    if TYPED_BOOLEAN_FIELDS.contains(&field) {
+        return coerce_bool(value)
+            .map(Value::Bool)
+            .ok_or_else(|| MarkdownError::PropertyIsWrongType(field.to_string(), "boolean".to_string()));
+    }
+
+    Ok(match value {
+        "true" => json!(true),
+        "false" => json!(false),
+        _ => value.parse::<i64>().map(|n| json!(n))
+            .or_else(|_| value.parse::<f64>().map(|n| json!(n)))
+            .unwrap_or_else(|_| json!(value)),
+    })
+}
+
+fn to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(to_yaml).collect()),
+        Value::Object(props) => Yaml::Hash(
+            props.iter().map(|(k, v)| (Yaml::String(k.clone()), to_yaml(v))).collect()
+        ),
+    }
+}
+
+/// Sets the top level property `key` to `value` -- or removes it when there
+/// is no value -- in the text found _inside_ of a frontmatter fence and
+/// returns the re-serialized text; the order of YAML properties is kept
+/// (TOML properties are sorted) but comments are not.
+fn update_text(text: &str, engine: FrontmatterEngineType, key: &str, value: Option<&Value>) -> Result<String, MarkdownError> {
+    let unparsable = |e: String| MarkdownError::UnparsableFrontmatter(e);
+
+    match engine {
+        FrontmatterEngineType::TOML => {
+            let mut table: toml::value::Table = toml::from_str(text).map_err(|e| unparsable(e.to_string()))?;
+            match value {
+                Some(value) => {
+                    let value = toml::Value::try_from(value).map_err(|e| unparsable(e.to_string()))?;
+                    table.insert(key.to_string(), value);
+                }
+                None => {
+                    table.remove(key);
+                }
+            }
+            Ok(toml::to_string(&table).map_err(|e| unparsable(e.to_string()))?.trim_end().to_string())
+        }
+        _ => {
+            let docs = YamlLoader::load_from_str(text).map_err(|e| unparsable(e.to_string()))?;
+            let mut props = match docs.into_iter().next() {
+                Some(Yaml::Hash(props)) => props,
+                None | Some(Yaml::Null) => Default::default(),
+                Some(_) => return Err(unparsable("the frontmatter is not a set of properties".to_string())),
+            };
+            match value {
+                Some(value) => props.insert(Yaml::String(key.to_string()), to_yaml(value)),
+                None => props.remove(&Yaml::String(key.to_string())),
+            };
+            if props.is_empty() {
+                return Ok(String::new());
+            }
+
+            let mut out = String::new();
+            YamlEmitter::new(&mut out).dump(&Yaml::Hash(props)).map_err(|e| unparsable(format!("{:?}", e)))?;
+            Ok(out.trim_start_matches("---").trim_start_matches('\n').to_string())
+        }
+    }
+}
+
+/// puts the frontmatter `text` back inside of its fence ahead of the prose
+fn with_fence(fence: &FmFence, text: &str, rest: &str) -> String {
+    match text {
+        "" => format!("{}\n{}\n{}", fence.open, fence.close, rest),
+        text => format!("{}\n{}\n{}\n{}", fence.open, text, fence.close, rest),
+    }
+}
+
+/// Sets the frontmatter property `field` of the raw markdown content to
+/// `value` (see `typed_value`) and returns the updated content; the prose
+/// is left untouched and a `---` fence is added when the content has no
+/// frontmatter. An existing `requiresAuth`/`requires_auth` spelling is kept.
+pub fn set_field(raw: &str, fences: &[FmFence], field: &str, value: &str) -> Result<String, MarkdownError> {
+    let value = typed_value(field, value)?;
+    let (fence, text, rest) = match fences.iter().find_map(|f| f.split(raw).map(|(fm, rest)| (f.clone(), fm, rest))) {
+        Some(found) => found,
+        None => (FmFence::new("---", "---"), "", raw),
+    };
+    let key = match TYPED_BOOLEAN_FIELDS.contains(&field) {
+        true => TYPED_BOOLEAN_FIELDS
+            .into_iter()
+            .find(|k| text.lines().any(|l| l.starts_with(&format!("{}:", k)) || l.starts_with(&format!("{} =", k))))
+            .unwrap_or(field),
+        false => field,
+    };
+
+    let text = update_text(text, fence.engine(), key, Some(&value))?;
+    // the result must still be valid frontmatter (e.g., for typed properties)
+    Frontmatter::parse(&text, fence.engine())?;
+
+    Ok(with_fence(&fence, &text, rest))
+}
+
+/// Removes the frontmatter property `field` -- in any of its spellings for
+/// `requires_auth` -- from the raw markdown content and returns the updated
+/// content; content which doesn't set the property is returned unchanged.
+pub fn unset_field(raw: &str, fences: &[FmFence], field: &str) -> Result<String, MarkdownError> {
+    let Some((fence, (text, rest))) = fences.iter().find_map(|f| f.split(raw).map(|s| (f, s))) else {
+        return Ok(raw.to_string());
+    };
+    let keys: Vec<&str> = match TYPED_BOOLEAN_FIELDS.contains(&field) {
+        true => TYPED_BOOLEAN_FIELDS.to_vec(),
+        false => vec![field],
+    };
+    let props = Frontmatter::parse_json(text, fence.engine())?;
+    let present: Vec<&str> = keys.into_iter().filter(|k| props.get(k).is_some()).collect();
+    if present.is_empty() {
+        return Ok(raw.to_string());
+    }
+
+    let mut text = text.to_string();
+    for key in present {
+        text = update_text(&text, fence.engine(), key, None)?;
+    }
+
+    Ok(with_fence(fence, &text, rest))
+}
+
+/// The comment delimiters to use when embedding frontmatter as a header
+/// comment in a source file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentStyle {
+    /// a `/* ... */` block comment
+    SlashStar,
+    /// `--` line comments (e.g., SQL, Lua)
+    DashDash,
+    /// `#` line comments (e.g., shell, Python, YAML)
+    Hash,
+}
+
+/// The type of a frontmatter field registered with a `FrontmatterSchema`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    StringList,
+    /// any JSON value is accepted
+    Any,
+}
+
+impl FieldType {
+    /// whether the value is of this type
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Number => value.is_number(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::StringList => value.as_array().is_some_and(|a| a.iter().all(|v| v.is_string())),
+            FieldType::Any => true,
+        }
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FieldType::String => "string",
+            FieldType::Number => "number",
+            FieldType::Integer => "integer",
+            FieldType::Boolean => "boolean",
+            FieldType::StringList => "list of strings",
+            FieldType::Any => "any value",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The definition of a custom frontmatter field
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldDef {
+    pub name: String,
+    pub kind: FieldType,
+    pub required: bool,
+}
+
+impl FieldDef {
+    pub fn new(name: &str, kind: FieldType, required: bool) -> Self {
+        FieldDef { name: name.to_string(), kind, required }
+    }
+}
+
+/// Custom frontmatter fields which a library user wants treated as first
+/// class fields -- with type validation -- rather than untyped `other` values
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct FrontmatterSchema {
+    pub fields: Vec<FieldDef>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FmHashValues {
     /// A hash value representing the frontmatter immediately after it is
@@ -29,6 +320,52 @@ pub struct FmHashValues {
     pub overrides_applied: Option<u64>,
 }
 
+/// The number of days since the Unix epoch of an ISO 8601 date (e.g.,
+/// `2024-03-01`); anything following the date -- such as a time or UTC
+/// offset -- is ignored, so a datetime is treated as its date alone. The
+/// day is only checked to be within `1..=31` (there is no `chrono`).
+pub fn iso_date_to_days(text: &str) -> Option<i64> {
+    let date = text.trim().get(..10)?;
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // days-from-civil (Howard Hinnant's algorithm)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    Some(era * 146_097 + doe - 719_468)
+}
+
+/// Deserializes a list of strings which may also be written as a single
+/// comma separated string (e.g., `aliases: "/old, /older"`) or a lone
+/// scalar; each entry is trimmed and empty entries are dropped.
+fn comma_separated_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let entry = |v: &Value| match v {
+        Value::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    };
+    let list: Vec<String> = match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::String(s)) => s.split(',').map(|s| s.trim().to_string()).collect(),
+        Some(Value::Array(items)) => items.iter().map(entry).collect(),
+        Some(Value::Object(_)) => return Err(serde::de::Error::custom("expected a list or a comma separated string")),
+        Some(scalar) => vec![entry(&scalar)],
+    };
+
+    Ok(Some(list.into_iter().filter(|s| !s.is_empty()).collect()))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(
     rename_all(serialize = "camelCase", deserialize = "camelCase"),
@@ -37,7 +374,7 @@ pub struct FmHashValues {
 pub struct Frontmatter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "comma_separated_list")]
     pub aliases: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
@@ -58,7 +395,12 @@ pub struct Frontmatter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", alias = "requires_auth")]
     pub requires_auth: Option<bool>,
+    /// the ISO 8601 date (e.g., `2024-03-01`) on which the content expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
     /// Other properties who's type are not known until run time
     #[serde(flatten)]
     pub other: HashMap<String, Value>,
@@ -78,10 +420,10 @@ impl Display for Frontmatter {
     }
 }
 
-impl Into<String> for Frontmatter {
+impl From<Frontmatter> for String {
     #[instrument]
-    fn into(self) -> String {
-        json!(self).to_string()
+    fn from(fm: Frontmatter) -> String {
+        json!(fm).to_string()
     }
 }
 
@@ -89,11 +431,7 @@ impl TryFrom<String> for Frontmatter {
     type Error = MarkdownError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let matter = Matter::<YAML>::new();
-        let fm = Frontmatter::new(
-            matter.parse(&value).data.unwrap().deserialize().unwrap()
-        )?;
-        Ok(fm)
+        Frontmatter::from_document(&value, true)
     }
 }
 
@@ -130,18 +468,236 @@ impl Frontmatter {
                 image: None,
                 icon: None,
                 layout: None,
+                language: None,
                 requires_auth: None,
+                expires: None,
                 other: HashMap::new(),
             })
         }
     }
 
+    /// Sets `field` to the textual `value` -- typed as `typed_value` does --
+    /// replacing any value the field already has.
+    pub fn update(&mut self, field: &str, value: &str) -> Result<(), MarkdownError> {
+        let typed = typed_value(field, value)?;
+        let mut json = serde_json::to_value(&*self)?;
+        if let Some(props) = json.as_object_mut() {
+            let key = match TYPED_BOOLEAN_FIELDS.contains(&field) {
+                true => {
+                    TYPED_BOOLEAN_FIELDS.iter().for_each(|k| { props.remove(*k); });
+                    "requiresAuth"
+                }
+                false => field,
+            };
+            props.insert(key.to_string(), typed);
+        }
+        *self = Frontmatter::new(Some(json))?;
+
+        Ok(())
+    }
+
+    /// Whether the `expires` date has been reached; content without an
+    /// `expires` date (or one which isn't an ISO 8601 date) never expires.
+    /// The date is reached at midnight UTC rather than in the local time
+    /// zone and the time of a datetime value is ignored.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(SystemTime::now())
+    }
+
+    /// whether the `expires` date had been reached at the given time
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        let today = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs() / 86_400).unwrap_or(0) as i64;
+        self.expires
+            .as_deref()
+            .and_then(iso_date_to_days)
+            .is_some_and(|expires| today >= expires)
+    }
+
+    /// Parses the YAML frontmatter of a whole document. When there is no
+    /// data to parse (e.g., an empty `---` fence or one holding only
+    /// comments) an empty `Frontmatter` is provided if `lenient` and a
+    /// `MarkdownError::FrontmatterEmpty` error otherwise.
+    pub fn from_document(value: &str, lenient: bool) -> Result<Self, MarkdownError> {
+        let matter = Matter::<YAML>::new();
+        match matter.parse(value).data {
+            Some(data) => Ok(Frontmatter::from_json(data.deserialize()?, false)?.0),
+            None if lenient => Frontmatter::new(None),
+            None => Err(MarkdownError::FrontmatterEmpty),
+        }
+    }
+
+    /// Parses the text found _inside_ of a frontmatter fence with the
+    /// given engine. A typed boolean property (e.g., `requires_auth`) which
+    /// doesn't hold a boolean is left untouched in `other` rather than
+    /// being an error.
+    pub fn parse(text: &str, engine: FrontmatterEngineType) -> Result<Self, MarkdownError> {
+        Ok(Frontmatter::from_json(Frontmatter::parse_json(text, engine)?, false)?.0)
+    }
+
+    /// Parses the text found _inside_ of a frontmatter fence like `parse`
+    /// but coerces truthy/falsy strings (e.g., `requires_auth: "yes"`) in
+    /// the typed boolean properties. Strings which aren't recognizably
+    /// boolean are left untouched in `other` and their property names are
+    /// returned alongside the frontmatter.
+    pub fn parse_lenient(
+        text: &str,
+        engine: FrontmatterEngineType
+    ) -> Result<(Self, Vec<String>), MarkdownError> {
+        Frontmatter::from_json(Frontmatter::parse_json(text, engine)?, true)
+    }
+
+    /// Builds the frontmatter from parsed JSON; values of the typed boolean
+    /// properties which aren't booleans -- after coercing boolean-like
+    /// strings when `coerce` is set -- are moved to `other` and their
+    /// property names returned.
+    fn from_json(mut json: Value, coerce: bool) -> Result<(Self, Vec<String>), MarkdownError> {
+        let mut rejected: Vec<(String, Value)> = vec![];
+
+        if let Some(props) = json.as_object_mut() {
+            for key in TYPED_BOOLEAN_FIELDS {
+                let coerced = match props.get(key) {
+                    Some(Value::Bool(_)) | Some(Value::Null) | None => continue,
+                    Some(Value::String(s)) if coerce => coerce_bool(s),
+                    Some(_) => None,
+                };
+                match coerced {
+                    Some(b) => {
+                        props.insert(key.to_string(), Value::Bool(b));
+                    }
+                    None => rejected.extend(props.remove_entry(key)),
+                }
+            }
+        }
+
+        let mut fm = match json {
+            Value::Null => Frontmatter::new(None)?,
+            json => Frontmatter::new(Some(json))?,
+        };
+        let names = rejected.iter().map(|(k, _)| k.clone()).collect();
+        fm.other.extend(rejected);
+
+        Ok((fm, names))
+    }
+
+    fn parse_json(text: &str, engine: FrontmatterEngineType) -> Result<Value, MarkdownError> {
+        let pod = match engine {
+            FrontmatterEngineType::YAML => YAML::parse(text),
+            FrontmatterEngineType::JSON => JSON::parse(text),
+            FrontmatterEngineType::TOML => TOML::parse(text),
+        };
+
+        Ok(pod.deserialize::<Value>()?)
+    }
+
+    /// Parses a stream of YAML documents separated by `---` lines -- as
+    /// found in YAML data files -- with each document becoming a record. A
+    /// leading `---` (and any `...` document end marker) does not start a
+    /// record and documents without any content are skipped.
+    pub fn parse_documents(text: &str) -> Result<Vec<Self>, MarkdownError> {
+        let mut documents: Vec<Vec<&str>> = vec![vec![]];
+        for line in text.lines() {
+            match line.trim_end() {
+                "---" => documents.push(vec![]),
+                "..." => {}
+                _ => documents.last_mut().into_iter().for_each(|d| d.push(line)),
+            }
+        }
+
+        documents
+            .iter()
+            .map(|lines| lines.join("\n"))
+            .filter(|doc| doc.lines().any(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#')))
+            .map(|doc| Frontmatter::parse(&doc, FrontmatterEngineType::YAML))
+            .collect()
+    }
+
+    /// Parses the frontmatter of the raw markdown content and validates the
+    /// custom fields registered in `schema`. The validated custom fields are
+    /// moved out of `other` and returned alongside the frontmatter.
+    pub fn parse_with_schema(
+        raw: &str,
+        schema: &FrontmatterSchema
+    ) -> Result<(Frontmatter, HashMap<String, Value>), MarkdownError> {
+        let mut fm = match default_fences().iter().find_map(|f| f.split(raw).map(|s| (f, s.0))) {
+            Some((fence, text)) => Frontmatter::parse(text, fence.engine())?,
+            None => Frontmatter::new(None)?,
+        };
+
+        let mut custom: HashMap<String, Value> = HashMap::new();
+        for field in &schema.fields {
+            match fm.other.remove(&field.name) {
+                Some(value) if field.kind.accepts(&value) => {
+                    custom.insert(field.name.clone(), value);
+                }
+                Some(_) => {
+                    return Err(MarkdownError::PropertyIsWrongType(field.name.clone(), field.kind.to_string()));
+                }
+                None if field.required => {
+                    return Err(MarkdownError::MissingRequiredProperty(field.name.clone()));
+                }
+                None => {}
+            }
+        }
+
+        Ok((fm, custom))
+    }
+
+    /// Gets the value at a dotted path (e.g., `title` or `author.name`) where
+    /// the first segment is either a typed property or a property in `other`
+    /// and later segments index into objects (or arrays by position).
+    pub fn get_path(&self, path: &str) -> Option<Value> {
+        let json = json!(self);
+        let mut segments = path.split('.');
+        // typed properties are serialized in camelCase (e.g., `requiresAuth`)
+        let first = match segments.next()? {
+            "requires_auth" => "requiresAuth",
+            key => key,
+        };
+        let mut value = json.get(first)?;
+        for segment in segments {
+            value = match segment.parse::<usize>() {
+                Ok(idx) if value.is_array() => value.get(idx)?,
+                _ => value.get(segment)?,
+            };
+        }
+
+        Some(value.clone())
+    }
+
+    /// Gets the value of the `key` property from the untyped properties as a
+    /// number; both actual numbers and numeric strings (e.g., `order: "3"`)
+    /// are coerced while any other value results in `None`.
+    pub fn get_number(&self, key: &str) -> Option<f64> {
+        match self.other.get(key)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
+    /// Serializes the frontmatter and wraps it in the comment delimiters of
+    /// the given `CommentStyle` so that it can be embedded at the top of a
+    /// source file.
+    pub fn to_header_comment(&self, style: CommentStyle) -> String {
+        let body = serde_json::to_string_pretty(self).unwrap();
+        let lines = body.lines();
+
+        match style {
+            CommentStyle::SlashStar => {
+                let inner: Vec<String> = lines.map(|l| format!(" * {}", l)).collect();
+                format!("/*\n{}\n */", inner.join("\n"))
+            }
+            CommentStyle::DashDash => lines.map(|l| format!("-- {}", l)).collect::<Vec<_>>().join("\n"),
+            CommentStyle::Hash => lines.map(|l| format!("# {}", l)).collect::<Vec<_>>().join("\n"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use tracing::{info, Level};
-    use tracing_subscriber;
+    use super::*;
+    use tracing::Level;
+    use crate::test_support::init_tracing_once;
 
     const SIMPLE_MD: &str = r#"---
 title: testing
@@ -153,18 +709,231 @@ baz: 42
 this is a test 
 "#;
 
+    #[allow(dead_code)]
     fn trace(lvl: Level) {
-        let collector = tracing_subscriber::fmt()
-            // filter spans/events with level TRACE or higher.
-            .with_max_level(lvl)
-            .pretty()
-            // build but do not install the subscriber.
-            .finish();
+        init_tracing_once(lvl);
+    }
+
+    #[test]
+    fn expiry_is_compared_with_the_current_date() {
+        let fm = Frontmatter::parse("expires: 2024-03-01", FrontmatterEngineType::YAML).unwrap();
+        let day = |days: u64| UNIX_EPOCH + std::time::Duration::from_secs(days * 86_400 + 3_600);
+
+        assert_eq!(iso_date_to_days("1970-01-02"), Some(1));
+        assert_eq!(iso_date_to_days("2024-03-01T10:00:00Z"), Some(19_783));
+        assert_eq!(iso_date_to_days("soon"), None);
+        assert!(!fm.is_expired_at(day(19_782)));
+        assert!(fm.is_expired_at(day(19_783)));
+        assert!(!Frontmatter::default().is_expired());
+    }
+
+    #[test]
+    fn empty_frontmatter_blocks_do_not_panic() {
+        let fm = Frontmatter::try_from("---\n---\n# Hello\n").unwrap();
+        assert!(fm.title.is_none() && fm.other.is_empty());
+
+        assert!(Frontmatter::from_document("---\n# a comment\n---\n", true).is_ok());
+        assert!(matches!(Frontmatter::from_document("---\n---\n", false), Err(MarkdownError::FrontmatterEmpty)));
+    }
+
+    #[test]
+    fn yaml_streams_are_parsed_as_records() {
+        let stream = "---\ntitle: First\ntags: [a]\n---\n# just a comment\n---\ntitle: Second\nrank: 2\n...\n";
+        let records = Frontmatter::parse_documents(stream).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].title.as_deref(), Some("First"));
+        assert_eq!(records[0].tags, Some(vec!["a".to_string()]));
+        assert_eq!(records[1].title.as_deref(), Some("Second"));
+        assert_eq!(records[1].other.get("rank"), Some(&json!(2)));
+        assert_eq!(Frontmatter::parse_documents("title: Only\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn aliases_may_be_a_comma_separated_string() {
+        let fm = Frontmatter::parse("aliases: \"/old, /older ,\"", FrontmatterEngineType::YAML).unwrap();
+        assert_eq!(fm.aliases, Some(vec!["/old".to_string(), "/older".to_string()]));
+
+        let fm = Frontmatter::parse("aliases:\n  - /old\n  - /old, still one\n", FrontmatterEngineType::YAML).unwrap();
+        assert_eq!(fm.aliases, Some(vec!["/old".to_string(), "/old, still one".to_string()]));
 
-        tracing::subscriber::set_global_default(collector).unwrap();
+        let fm = Frontmatter::parse("aliases: /legacy", FrontmatterEngineType::YAML).unwrap();
+        assert_eq!(fm.aliases, Some(vec!["/legacy".to_string()]));
+        assert_eq!(Frontmatter::parse("title: x", FrontmatterEngineType::YAML).unwrap().aliases, None);
+    }
+
+    #[test]
+    fn simple_md_parses_title_and_other_props() {
+        let fm = Frontmatter::try_from(SIMPLE_MD).unwrap();
+
+        assert_eq!(fm.title, Some("testing".to_string()));
+        assert_eq!(fm.other.get("foo"), Some(&json!("bar")));
+        assert_eq!(fm.other.get("bar"), Some(&json!(true)));
+        assert_eq!(fm.other.get("baz"), Some(&json!(42)));
+    }
+
+    #[test]
+    fn header_comment_uses_style_delimiters() {
+        let fm = Frontmatter::try_from(SIMPLE_MD).unwrap();
+
+        let slash = fm.to_header_comment(CommentStyle::SlashStar);
+        assert!(slash.starts_with("/*\n * {"));
+        assert!(slash.ends_with("\n */"));
+        assert!(slash.contains(" *   \"title\": \"testing\""));
+
+        let hash = fm.to_header_comment(CommentStyle::Hash);
+        assert!(hash.lines().all(|l| l.starts_with("# ")));
+
+        let dash = fm.to_header_comment(CommentStyle::DashDash);
+        assert!(dash.lines().all(|l| l.starts_with("-- ")));
+    }
+
+    #[test]
+    fn get_number_coerces_numeric_values() {
+        let fm = Frontmatter::try_from("---\norder: 3\nweight: \"2.5\"\nname: foo\nlabel: \"abc\"\n---\n").unwrap();
+
+        assert_eq!(fm.get_number("order"), Some(3.0));
+        assert_eq!(fm.get_number("weight"), Some(2.5));
+        assert_eq!(fm.get_number("label"), None);
+        assert_eq!(fm.get_number("missing"), None);
+    }
+
+    #[test]
+    fn lenient_parsing_coerces_boolean_strings() {
+        for truthy in ["true", "yes", "on", "1", " Yes "] {
+            let text = format!("requires_auth: \"{}\"", truthy);
+            let (fm, rejected) = Frontmatter::parse_lenient(&text, FrontmatterEngineType::YAML).unwrap();
+            assert_eq!(fm.requires_auth, Some(true), "{}", truthy);
+            assert!(rejected.is_empty());
+        }
+        for falsy in ["false", "no", "off", "0"] {
+            let text = format!("requiresAuth: \"{}\"", falsy);
+            let (fm, _) = Frontmatter::parse_lenient(&text, FrontmatterEngineType::YAML).unwrap();
+            assert_eq!(fm.requires_auth, Some(false), "{}", falsy);
+        }
+    }
+
+    #[test]
+    fn lenient_parsing_leaves_non_boolean_strings_untouched() {
+        let text = "requires_auth: maybe\nflag: \"yes\"";
+        let (fm, rejected) = Frontmatter::parse_lenient(text, FrontmatterEngineType::YAML).unwrap();
+
+        assert_eq!(fm.requires_auth, None);
+        assert_eq!(fm.other.get("requires_auth"), Some(&json!("maybe")));
+        assert_eq!(fm.other.get("flag"), Some(&json!("yes")));
+        assert_eq!(rejected, vec!["requires_auth".to_string()]);
+    }
+
+    #[test]
+    fn non_boolean_values_are_kept_in_other_by_default() {
+        let fm = Frontmatter::parse("requires_auth: \"yes\"\ntitle: Hi", FrontmatterEngineType::YAML).unwrap();
+        assert_eq!(fm.requires_auth, None);
+        assert_eq!(fm.other.get("requires_auth"), Some(&json!("yes")));
+        assert_eq!(fm.title, Some("Hi".to_string()));
+
+        let fm = Frontmatter::try_from("---\nrequiresAuth: 1\n---\n").unwrap();
+        assert_eq!(fm.other.get("requiresAuth"), Some(&json!(1)));
+    }
+
+    #[test]
+    fn set_field_infers_types_and_keeps_the_prose() {
+        let raw = "---\ntitle: Old\nrequiresAuth: false\n---\n# Body\n";
+        let updated = set_field(raw, &default_fences(), "layout", "post").unwrap();
+        let updated = set_field(&updated, &default_fences(), "order", "3").unwrap();
+        let updated = set_field(&updated, &default_fences(), "requires_auth", "yes").unwrap();
+        let fm = Frontmatter::try_from(updated.as_str()).unwrap();
 
-        info!("Tracing for tests enabled");
+        assert!(updated.ends_with("---\n# Body\n"));
+        assert_eq!(fm.title, Some("Old".to_string()));
+        assert_eq!(fm.layout, Some("post".to_string()));
+        assert_eq!(fm.other.get("order"), Some(&json!(3)));
+        assert_eq!(fm.requires_auth, Some(true));
+        assert!(updated.contains("requiresAuth: true"));
+        assert!(set_field(raw, &default_fences(), "requires_auth", "maybe").is_err());
     }
 
+    #[test]
+    fn set_field_adds_missing_frontmatter() {
+        let updated = set_field("# Body\n", &default_fences(), "tags", "a, b").unwrap();
+        let toml = set_field("+++\ntitle = \"T\"\n+++\nBody\n", &default_fences(), "draft", "true").unwrap();
 
+        assert_eq!(updated, "---\ntags:\n  - a\n  - b\n---\n# Body\n");
+        assert_eq!(toml, "+++\ndraft = true\ntitle = \"T\"\n+++\nBody\n");
+    }
+
+    #[test]
+    fn unset_field_removes_the_property() {
+        let raw = "---\ntitle: Keep\nlegacy_id: 42\nrequiresAuth: true\n---\n# Body\n";
+        let updated = unset_field(raw, &default_fences(), "legacy_id").unwrap();
+        let updated = unset_field(&updated, &default_fences(), "requires_auth").unwrap();
+
+        assert_eq!(updated, "---\ntitle: Keep\n---\n# Body\n");
+        assert_eq!(unset_field(raw, &default_fences(), "missing").unwrap(), raw);
+        assert_eq!(unset_field("# Body\n", &default_fences(), "title").unwrap(), "# Body\n");
+        assert_eq!(unset_field("---\ntitle: Gone\n---\nBody\n", &default_fences(), "title").unwrap(), "---\n---\nBody\n");
+    }
+
+    #[test]
+    fn custom_fences_split_frontmatter() {
+        let fence = FmFence::new("<!--meta", "-->");
+        let (fm, rest) = fence.split("<!--meta\ntitle: custom\n-->\n# Body\n").unwrap();
+
+        assert_eq!(fm, "title: custom");
+        assert_eq!(rest, "# Body\n");
+        assert!(fence.split("---\ntitle: yaml\n---\n").is_none());
+    }
+
+    #[test]
+    fn toml_fence_is_parsed_with_toml_engine() {
+        let fence = FmFence::new("+++", "+++");
+        let (text, _) = fence.split("+++\ntitle = \"toml\"\n+++\n# Body\n").unwrap();
+        let fm = Frontmatter::parse(text, fence.engine()).unwrap();
+
+        assert_eq!(fm.title, Some("toml".to_string()));
+    }
+
+    #[test]
+    fn dotted_paths_reach_into_other_properties() {
+        let fm = Frontmatter::parse("title: Hi\nrequires_auth: true\nauthor:\n  name: Ken\n  links: [a, b]\n", FrontmatterEngineType::YAML).unwrap();
+
+        assert_eq!(fm.get_path("title"), Some(json!("Hi")));
+        assert_eq!(fm.get_path("requires_auth"), Some(json!(true)));
+        assert_eq!(fm.get_path("author.name"), Some(json!("Ken")));
+        assert_eq!(fm.get_path("author.links.1"), Some(json!("b")));
+        assert_eq!(fm.get_path("author.missing"), None);
+        assert_eq!(fm.get_path("description"), None);
+    }
+
+    fn schema() -> FrontmatterSchema {
+        FrontmatterSchema {
+            fields: vec![
+                FieldDef::new("draft", FieldType::Boolean, false),
+                FieldDef::new("weight", FieldType::Integer, true),
+                FieldDef::new("custom_id", FieldType::String, false),
+            ],
+        }
+    }
+
+    #[test]
+    fn schema_fields_are_validated_and_separated() {
+        let (fm, custom) = Frontmatter::parse_with_schema(
+            "---\ntitle: hi\ndraft: true\nweight: 3\ncustom_id: abc\nfoo: bar\n---\n# Hi\n",
+            &schema()
+        ).unwrap();
+
+        assert_eq!(custom.get("draft"), Some(&json!(true)));
+        assert_eq!(custom.get("weight"), Some(&json!(3)));
+        assert_eq!(custom.get("custom_id"), Some(&json!("abc")));
+        assert!(!fm.other.contains_key("weight"));
+        assert_eq!(fm.other.get("foo"), Some(&json!("bar")));
+    }
+
+    #[test]
+    fn schema_rejects_wrong_types_and_missing_required() {
+        let wrong = Frontmatter::parse_with_schema("---\nweight: heavy\n---\n", &schema());
+        let missing = Frontmatter::parse_with_schema("---\ndraft: false\n---\n", &schema());
+
+        assert!(matches!(wrong, Err(MarkdownError::PropertyIsWrongType(name, _)) if name == "weight"));
+        assert!(matches!(missing, Err(MarkdownError::MissingRequiredProperty(name)) if name == "weight"));
+    }
 }
@@ -0,0 +1,184 @@
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde::{Serialize, Deserialize};
+
+/// A heading found in the prose of a markdown document
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Heading {
+    /// the heading level where `1` is an `h1`, `2` an `h2`, etc.
+    pub level: u8,
+    /// the visible text of the heading
+    pub text: String,
+    /// the anchor for the heading; an explicit `{#id}` on the heading is
+    /// used when present, otherwise this is derived from the text
+    pub slug: String,
+    /// the 1-based line number on which the heading starts
+    pub line: usize,
+}
+
+/// A heading which is more than one level deeper than the heading before
+/// it (e.g., an `h2` followed directly by an `h4`)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HeadingJump {
+    /// the level of the preceding heading
+    pub from: u8,
+    /// the level of the heading which skipped a level
+    pub to: u8,
+    /// the 1-based line number of the heading which skipped a level
+    pub line: usize,
+}
+
+/// the 1-based line number of the given byte offset in `content`
+pub fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Converts heading text into a URL friendly anchor in the same manner
+/// as GitHub does (lowercase, spaces to hyphens, punctuation removed).
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' | '-' => Some('-'),
+            c if c.is_alphanumeric() || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parses the markdown `content` and returns all of the headings found in
+/// document order.
+pub fn extract_headings(content: &str) -> Vec<Heading> {
+    let mut headings: Vec<Heading> = vec![];
+    let mut current: Option<Heading> = None;
+    let parser = Parser::new_ext(content, Options::ENABLE_HEADING_ATTRIBUTES);
+
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(level, id, _)) => {
+                current = Some(Heading {
+                    level: level as u8,
+                    text: String::new(),
+                    slug: id.unwrap_or_default().to_string(),
+                    line: line_of(content, range.start),
+                });
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some(mut h) = current.take() {
+                    h.text = h.text.trim().to_string();
+                    if h.slug.is_empty() {
+                        h.slug = slugify(&h.text);
+                    }
+                    headings.push(h);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(h) = current.as_mut() {
+                    h.text.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    headings
+}
+
+/// Numbers the headings hierarchically (e.g., `1`, `1.1`, `1.2.1`) and
+/// returns `(number, heading_text)` pairs. Numbering depth follows the
+/// _nesting_ of the headings rather than their absolute level so a jump
+/// from an `h1` directly to an `h3` yields `1.1` rather than `1.0.1`.
+pub fn number_headings(headings: &[Heading]) -> Vec<(String, String)> {
+    // each entry is the heading level at that depth and its current count
+    let mut stack: Vec<(u8, usize)> = vec![];
+
+    headings
+        .iter()
+        .map(|h| {
+            let mut popped: Option<usize> = None;
+            while stack.last().is_some_and(|(level, _)| *level > h.level) {
+                popped = stack.pop().map(|(_, count)| count);
+            }
+
+            match stack.last_mut() {
+                Some((level, count)) if *level == h.level => *count += 1,
+                _ => stack.push((h.level, popped.map(|c| c + 1).unwrap_or(1))),
+            }
+
+            let number: Vec<String> = stack.iter().map(|(_, count)| count.to_string()).collect();
+            (number.join("."), h.text.clone())
+        })
+        .collect()
+}
+
+/// Finds the headings which skip one or more levels relative to the heading
+/// preceding them; moving back up to a shallower level is never a jump.
+pub fn heading_jumps(headings: &[Heading]) -> Vec<HeadingJump> {
+    headings
+        .windows(2)
+        .filter(|pair| pair[1].level > pair[0].level + 1)
+        .map(|pair| HeadingJump { from: pair[0].level, to: pair[1].level, line: pair[1].line })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_heading_levels_are_jumps() {
+        let headings = extract_headings("# Guide
+## Setup
+#### Deep
+## Usage
+# Reference
+### Jumped
+");
+
+        assert_eq!(
+            heading_jumps(&headings),
+            vec![
+                HeadingJump { from: 2, to: 4, line: 3 },
+                HeadingJump { from: 1, to: 3, line: 6 },
+            ]
+        );
+        assert!(heading_jumps(&extract_headings("# A
+## B
+### C
+# D
+")).is_empty());
+    }
+
+    #[test]
+    fn headings_are_extracted_with_level_and_line() {
+        let headings = extract_headings("# Hello\n\ntext\n\n## World `code`\n");
+
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[0].level, 1);
+        assert_eq!(headings[0].text, "Hello");
+        assert_eq!(headings[0].line, 1);
+        assert_eq!(headings[1].level, 2);
+        assert_eq!(headings[1].text, "World code");
+        assert_eq!(headings[1].slug, "world-code");
+        assert_eq!(headings[1].line, 5);
+    }
+
+    #[test]
+    fn explicit_id_overrides_slug_and_is_excluded_from_text() {
+        let headings = extract_headings("## Getting Started {#start}\n");
+
+        assert_eq!(headings[0].text, "Getting Started");
+        assert_eq!(headings[0].slug, "start");
+    }
+
+    #[test]
+    fn nested_headings_are_numbered() {
+        let headings = extract_headings(
+            "# Guide\n## Setup\n### Installation\n### Config\n## Usage\n# Reference\n### Jumped\n## After\n"
+        );
+        let numbers: Vec<String> = number_headings(&headings).into_iter().map(|(n, _)| n).collect();
+
+        assert_eq!(numbers, vec!["1", "1.1", "1.1.1", "1.1.2", "1.2", "2", "2.1", "2.2"]);
+        assert_eq!(number_headings(&headings)[2], ("1.1.1".to_string(), "Installation".to_string()));
+    }
+}
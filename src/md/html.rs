@@ -0,0 +1,265 @@
+use std::ops::Range;
+
+use lazy_static::lazy_static;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+use super::{heading::line_of, markdown::MarkdownDoc, prose::Prose};
+
+lazy_static! {
+    /// the opening tag of an HTML element
+    static ref OPENING_TAG: Regex = Regex::new(r"<([A-Za-z][A-Za-z0-9\-]*)(?:\s[^>]*)?/?>").unwrap();
+    /// the start of a CommonMark HTML block which is not a tag: a comment,
+    /// processing instruction, CDATA section or declaration
+    static ref SPECIAL_BLOCK: Regex = Regex::new(r"^<(!--|\?|!\[CDATA\[|![A-Za-z]+)").unwrap();
+    /// an opening or closing tag at the start of a line (its name captured)
+    static ref BLOCK_TAG: Regex = Regex::new(r"^</?([A-Za-z][A-Za-z0-9\-]*)(?:\s|/?>|$)").unwrap();
+    /// a complete opening or closing tag alone on its line
+    static ref COMPLETE_TAG: Regex = Regex::new(
+        r#"^(?:<[A-Za-z][A-Za-z0-9\-]*(?:\s+[A-Za-z_:][\w.:\-]*(?:\s*=\s*(?:[^\s"'=<>`]+|'[^']*'|"[^"]*"))?)*\s*/?>|</[A-Za-z][A-Za-z0-9\-]*\s*>)\s*$"#
+    ).unwrap();
+}
+
+/// tags which can execute code or embed external content
+const DANGEROUS_TAGS: [&str; 4] = ["script", "iframe", "object", "embed"];
+/// tags which never have a closing tag
+const VOID_TAGS: [&str; 8] = ["br", "hr", "img", "input", "meta", "link", "source", "wbr"];
+/// tags whose content is raw text; they start an HTML block (CommonMark
+/// start condition 1)
+const RAW_TEXT_TAGS: [&str; 4] = ["script", "pre", "style", "textarea"];
+/// tags which start an HTML block wherever they appear at the start of a
+/// line (CommonMark start condition 6)
+const BLOCK_TAGS: [&str; 62] = [
+    "address", "article", "aside", "base", "basefont", "blockquote", "body", "caption", "center", "col",
+    "colgroup", "dd", "details", "dialog", "dir", "div", "dl", "dt", "fieldset", "figcaption", "figure",
+    "footer", "form", "frame", "frameset", "h1", "h2", "h3", "h4", "h5", "h6", "head", "header", "hr", "html",
+    "iframe", "legend", "li", "link", "main", "menu", "menuitem", "nav", "noframes", "ol", "optgroup", "option",
+    "p", "param", "search", "section", "summary", "table", "tbody", "td", "tfoot", "th", "thead", "title", "tr",
+    "track", "ul",
+];
+
+/// The tag which starts a CommonMark HTML block on this line, if any;
+/// comments are `!--`, processing instructions `?` and declarations (e.g.,
+/// `!doctype`) keep their `!`. A block started by a closing tag (e.g., the
+/// `</details>` ending an element) is not an element of its own so has no
+/// tag.
+fn html_block_tag(line: &str) -> Option<String> {
+    if let Some(special) = SPECIAL_BLOCK.captures(line) {
+        return Some(special[1].to_ascii_lowercase());
+    }
+    if line.starts_with("</") {
+        return None;
+    }
+    let tag = BLOCK_TAG.captures(line)?[1].to_ascii_lowercase();
+    let starts_block = RAW_TEXT_TAGS.contains(&tag.as_str())
+        || BLOCK_TAGS.contains(&tag.as_str())
+        || COMPLETE_TAG.is_match(line);
+
+    starts_block.then_some(tag)
+}
+
+/// A run of raw HTML in the prose as the markdown parser reports it
+struct HtmlEvent {
+    range: Range<usize>,
+    /// the tag of the HTML block this run starts; `None` for inline HTML
+    /// and the later lines of a block
+    block_tag: Option<String>,
+}
+
+/// A block of raw HTML in the prose
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HtmlBlock {
+    /// the lowercased tag which opens the block (e.g., `div`, `details`)
+    pub tag: String,
+    /// the 1-based line number on which the block starts
+    pub line: usize,
+}
+
+/// A raw HTML element found in the prose
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RawHtml {
+    /// the lowercased tag name (e.g., `div`, `script`)
+    pub tag: String,
+    /// the element's markup from its opening tag through to its closing tag
+    /// (or just the opening tag when there is no closing tag)
+    pub content: String,
+    /// the 1-based line number on which the element starts
+    pub line: usize,
+    /// whether the tag is potentially dangerous (e.g., `script`, `iframe`)
+    pub dangerous: bool,
+}
+
+impl Prose {
+    /// The raw HTML in the prose -- HTML inside code blocks and code spans
+    /// is not considered -- noting which runs start an HTML block.
+    fn html_events(&self) -> Vec<HtmlEvent> {
+        let content = self.content.as_str();
+        let mut events: Vec<HtmlEvent> = vec![];
+        let mut inline_depth = 0;
+        // where the last line of HTML ended; a block continues on the next line
+        let mut html_end: Option<usize> = None;
+
+        for (event, range) in Parser::new_ext(content, Options::ENABLE_TABLES).into_offset_iter() {
+            match event {
+                Event::Start(Tag::Paragraph | Tag::Heading(..) | Tag::TableCell) => inline_depth += 1,
+                Event::End(Tag::Paragraph | Tag::Heading(..) | Tag::TableCell) => inline_depth -= 1,
+                Event::Html(_) if inline_depth == 0 => {
+                    let line_start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+                    let line_end = content[range.start..].find('\n').map_or(content.len(), |i| range.start + i);
+                    let at_line_start = content[line_start..range.start]
+                        .chars()
+                        .all(|c| c.is_whitespace() || ">-*+.)".contains(c) || c.is_ascii_digit());
+                    let block_tag = match html_end != Some(range.start) && at_line_start {
+                        true => html_block_tag(content[range.start..line_end].trim_start()),
+                        false => None,
+                    };
+                    html_end = Some(range.end);
+                    events.push(HtmlEvent { range, block_tag });
+                }
+                Event::Html(_) => events.push(HtmlEvent { range, block_tag: None }),
+                _ => html_end = None,
+            }
+        }
+
+        events
+    }
+
+    /// All raw HTML elements -- both block level and inline -- in the prose;
+    /// HTML inside fenced code blocks and code spans is not considered.
+    pub fn raw_html_blocks(&self) -> Vec<RawHtml> {
+        let content = self.content.as_str();
+        let mut found: Vec<RawHtml> = vec![];
+
+        for HtmlEvent { range, .. } in self.html_events() {
+            let html = &content[range.clone()];
+            for m in OPENING_TAG.captures_iter(html) {
+                let whole = m.get(0).unwrap();
+                let start = range.start + whole.start();
+                let tag = m[1].to_ascii_lowercase();
+                let opening_end = range.start + whole.end();
+                let self_closing = VOID_TAGS.contains(&tag.as_str()) || whole.as_str().ends_with("/>");
+                let end = if self_closing {
+                    opening_end
+                } else {
+                    content[opening_end..]
+                        .to_ascii_lowercase()
+                        .find(&format!("</{}>", tag))
+                        .map(|i| opening_end + i + tag.len() + 3)
+                        .unwrap_or(opening_end)
+                };
+
+                found.push(RawHtml {
+                    dangerous: DANGEROUS_TAGS.contains(&tag.as_str()),
+                    tag,
+                    content: content[start..end].to_string(),
+                    line: line_of(content, start),
+                });
+            }
+        }
+
+        found
+    }
+
+    /// The raw HTML blocks -- as CommonMark defines them -- in the prose;
+    /// inline HTML (e.g., `<b>` within a paragraph) along with HTML in code
+    /// blocks and code spans is not considered.
+    pub fn html_blocks(&self) -> Vec<HtmlBlock> {
+        self.html_events()
+            .into_iter()
+            .filter_map(|e| e.block_tag.map(|tag| HtmlBlock { tag, line: line_of(&self.content, e.range.start) }))
+            .collect()
+    }
+}
+
+/// escapes text for use within a double quoted HTML attribute
+fn escape_attr(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl MarkdownDoc {
+    /// The HTML `<meta>` tags -- one per line -- for the frontmatter's
+    /// `title`, `description`, `author` and `tags` (as `keywords`) along with
+    /// the Open Graph `og:title`, `og:description` and `og:image` tags;
+    /// properties which aren't set produce no tag.
+    pub fn to_html_meta_tags(&self) -> String {
+        let Some(fm) = &self.fm else {
+            return String::new();
+        };
+        let author = fm.other.get("author").and_then(|a| a.as_str()).map(|a| a.to_string());
+        let keywords = fm.tags.as_ref().filter(|t| !t.is_empty()).map(|t| t.join(", "));
+
+        [
+            ("name", "title", fm.title.clone()),
+            ("name", "description", fm.description.clone()),
+            ("name", "author", author),
+            ("name", "keywords", keywords),
+            ("property", "og:title", fm.title.clone()),
+            ("property", "og:description", fm.description.clone()),
+            ("property", "og:image", fm.image.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(attr, name, content)| {
+            content.map(|c| format!("<meta {}=\"{}\" content=\"{}\">", attr, name, escape_attr(&c)))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontmatter_becomes_meta_tags() {
+        let doc = MarkdownDoc::try_from(
+            "---\ntitle: Tools & \"Tips\"\ndescription: Felling trees\nauthor: Ken\ntags: [axe, saw]\nimage: /card.png\n---\n# Body\n"
+        ).unwrap();
+
+        assert_eq!(
+            doc.to_html_meta_tags(),
+            [
+                "<meta name=\"title\" content=\"Tools &amp; &quot;Tips&quot;\">",
+                "<meta name=\"description\" content=\"Felling trees\">",
+                "<meta name=\"author\" content=\"Ken\">",
+                "<meta name=\"keywords\" content=\"axe, saw\">",
+                "<meta property=\"og:title\" content=\"Tools &amp; &quot;Tips&quot;\">",
+                "<meta property=\"og:description\" content=\"Felling trees\">",
+                "<meta property=\"og:image\" content=\"/card.png\">",
+            ].join("\n")
+        );
+        assert_eq!(MarkdownDoc::try_from("# No frontmatter\n").unwrap().to_html_meta_tags(), "");
+    }
+
+    #[test]
+    fn script_is_flagged_while_div_is_benign() {
+        let prose = Prose::from("# Page\n\n<div class=\"note\">\nHello\n</div>\n\n<script>\nalert(1)\n</script>\n\nSome `<iframe>` in code and <b>bold</b> text.\n\n```html\n<script>ignored()</script>\n```\n");
+        let html = prose.raw_html_blocks();
+        let tags: Vec<&str> = html.iter().map(|h| h.tag.as_str()).collect();
+
+        assert_eq!(tags, vec!["div", "script", "b"]);
+        assert!(!html[0].dangerous);
+        assert_eq!(html[0].line, 3);
+        assert_eq!(html[1].content, "<script>\nalert(1)\n</script>");
+        assert!(html[1].dangerous);
+        assert_eq!(html[2].content, "<b>bold</b>");
+    }
+
+    #[test]
+    fn html_blocks_follow_commonmark_start_conditions() {
+        let prose = Prose::from(
+            "# Page\n\n<details>\n<summary>More</summary>\n\nHidden\n</details>\n\n<!-- note -->\n\nSome <span>inline</span> html.\n\n<custom-card id=\"a\">\n\n- <b>bold</b> item\n\n```html\n<div>ignored</div>\n```\n\n> <div>quoted</div>\n"
+        );
+        let blocks = prose.html_blocks();
+        let blocks: Vec<(&str, usize)> = blocks.iter().map(|b| (b.tag.as_str(), b.line)).collect();
+
+        assert_eq!(blocks, vec![("details", 3), ("!--", 9), ("custom-card", 13), ("div", 21)]);
+        assert_eq!(html_block_tag("</details>"), None);
+        assert_eq!(html_block_tag("<span>text</span>"), None);
+        assert_eq!(html_block_tag("<!DOCTYPE html>").as_deref(), Some("!doctype"));
+    }
+}
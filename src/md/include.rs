@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::{
+    errors::{Result, io::IoError},
+    file::{FileMeta, FileWithMeta},
+};
+
+use super::{
+    frontmatter::FmFence,
+    markdown::split_fm_from_prose_with,
+    prose::FenceTracker,
+};
+
+/// the deepest that includes may be nested within one another
+pub const MAX_INCLUDE_DEPTH: usize = 10;
+
+lazy_static! {
+    /// an include directive, `{{ include: other.md }}`, or an Obsidian style
+    /// embed, `![[other.md]]` (an embed of a note may omit the extension)
+    static ref INCLUDE: Regex = Regex::new(
+        r"\{\{\s*include:\s*([^}]+?)\s*\}\}|!\[\[([^\[\]|#]+)(?:[#|][^\[\]]*)?\]\]"
+    ).unwrap();
+}
+
+/// The path of the markdown file an include refers to relative to `dir`;
+/// embeds of anything other than markdown (e.g., images) are `None`.
+fn include_path(dir: &Path, caps: &Captures) -> Option<PathBuf> {
+    match (caps.get(1), caps.get(2)) {
+        (Some(include), _) => Some(dir.join(include.as_str().trim())),
+        (None, Some(embed)) => {
+            let embed = Path::new(embed.as_str().trim());
+            match embed.extension().and_then(|e| e.to_str()) {
+                Some("md") => Some(dir.join(embed)),
+                Some(_) => None,
+                None => Some(dir.join(embed.with_extension("md"))),
+            }
+        }
+        (None, None) => None,
+    }
+}
+
+/// Replaces the includes in `content` -- outside of fenced code blocks --
+/// with the prose of the files they refer to; `stack` holds the files
+/// currently being included so that cycles can be detected.
+fn inline(content: &str, dir: &Path, fences: &[FmFence], stack: &mut Vec<PathBuf>) -> Result<String> {
+    let mut code = FenceTracker::default();
+    let mut lines: Vec<String> = vec![];
+
+    for line in content.split_inclusive('\n') {
+        if code.update(line) || code.in_fence() || !INCLUDE.is_match(line) {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let mut inlined = String::new();
+        let mut last = 0;
+        for caps in INCLUDE.captures_iter(line) {
+            let Some(path) = include_path(dir, &caps) else {
+                continue;
+            };
+            let whole = caps.get(0).expect("a match always has a whole capture");
+            inlined.push_str(&line[last..whole.start()]);
+            inlined.push_str(resolve_file(&path, fences, stack)?.trim_end_matches('\n'));
+            last = whole.end();
+        }
+        inlined.push_str(&line[last..]);
+        lines.push(inlined);
+    }
+
+    Ok(lines.concat())
+}
+
+/// the prose of the markdown file at `path` with its own includes resolved
+fn resolve_file(path: &Path, fences: &[FmFence], stack: &mut Vec<PathBuf>) -> Result<String> {
+    let name = path.display().to_string();
+    let canonical = path.canonicalize().map_err(|_| IoError::FileDoesNotExist(name.clone()))?;
+    if stack.contains(&canonical) {
+        return Err(IoError::IncludeCycle(name).into());
+    }
+    if stack.len() > MAX_INCLUDE_DEPTH {
+        return Err(IoError::IncludeTooDeep(name, MAX_INCLUDE_DEPTH).into());
+    }
+
+    let file = FileWithMeta::try_from(FileMeta::try_from(name.as_str())?)?;
+    let (prose, _) = split_fm_from_prose_with(&file.content, fences, false)?;
+    stack.push(canonical);
+    let resolved = inline(&prose.content, path.parent().unwrap_or(Path::new(".")), fences, stack);
+    stack.pop();
+
+    resolved
+}
+
+/// Inlines the prose of the markdown files which the `content` of the file
+/// at `path` includes with `{{ include: other.md }}` or `![[other.md]]`;
+/// paths are relative to the including file and includes are resolved
+/// recursively. A cycle of includes -- or includes nested deeper than
+/// `MAX_INCLUDE_DEPTH` -- is an error.
+pub fn resolve_includes(content: &str, path: &str, fences: &[FmFence]) -> Result<String> {
+    let path = Path::new(path);
+    let mut stack: Vec<PathBuf> = path.canonicalize().into_iter().collect();
+
+    inline(content, path.parent().unwrap_or(Path::new(".")), fences, &mut stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::md::frontmatter::default_fences;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ctx-include-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn includes_are_inlined_recursively() {
+        let dir = scratch_dir("inline");
+        fs::write(dir.join("shared/snippet.md"), "---\ntitle: Snippet\n---\nShared text.\n\n{{ include: nested.md }}\n").unwrap();
+        fs::write(dir.join("shared/nested.md"), "Nested text.\n").unwrap();
+        let host = "# Host\n\n{{ include: shared/snippet.md }}\n\n![[shared/nested]] and ![[logo.png]]\n\n```\n{{ include: missing.md }}\n```\n";
+        let resolved = resolve_includes(host, dir.join("host.md").to_str().unwrap(), &default_fences());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            resolved.unwrap(),
+            "# Host\n\nShared text.\n\nNested text.\n\nNested text. and ![[logo.png]]\n\n```\n{{ include: missing.md }}\n```\n"
+        );
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let dir = scratch_dir("cycle");
+        fs::write(dir.join("a.md"), "A {{ include: b.md }}\n").unwrap();
+        fs::write(dir.join("b.md"), "B ![[a.md]]\n").unwrap();
+        let a = dir.join("a.md");
+        let resolved = resolve_includes("A {{ include: b.md }}\n", a.to_str().unwrap(), &default_fences());
+        let missing = resolve_includes("{{ include: nope.md }}", a.to_str().unwrap(), &default_fences());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(resolved.unwrap_err().to_string().contains("cycle"));
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+
+use super::{frontmatter::Frontmatter, markdown::MarkdownDoc};
+
+/// The frontmatter of a single document in a `FrontmatterIndex`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexEntry {
+    pub file: String,
+    pub fm: Frontmatter,
+}
+
+/// An index of the frontmatter of a set of documents; documents without
+/// frontmatter are not indexed.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct FrontmatterIndex {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl FrontmatterIndex {
+    pub fn from_docs(docs: &[MarkdownDoc]) -> Self {
+        FrontmatterIndex {
+            entries: docs
+                .iter()
+                .filter_map(|doc| Some(IndexEntry {
+                    file: doc.file.as_ref().map(|f| f.filename()).unwrap_or_default().to_string(),
+                    fm: doc.fm.clone()?,
+                }))
+                .collect(),
+        }
+    }
+
+    /// the indexed documents grouped by their `category`; documents without
+    /// a category are left out
+    pub fn category_tree(&self) -> BTreeMap<String, Vec<&str>> {
+        let mut tree: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for entry in &self.entries {
+            if let Some(category) = &entry.fm.category {
+                tree.entry(category.clone()).or_default().push(&entry.file);
+            }
+        }
+
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn docs_are_grouped_by_category() {
+        let docs = vec![
+            MarkdownDoc::try_from("---\ncategory: guides\nsubcategory: felling\ntitle: Notching\n---\n").unwrap(),
+            MarkdownDoc::try_from("---\ncategory: guides\n---\n").unwrap(),
+            MarkdownDoc::try_from("---\ntitle: Uncategorized\n---\n").unwrap(),
+            MarkdownDoc::try_from("# No frontmatter\n").unwrap(),
+        ];
+        let index = FrontmatterIndex::from_docs(&docs);
+        let tree = index.category_tree();
+
+        assert_eq!(index.entries.len(), 3);
+        assert_eq!(tree.keys().collect::<Vec<_>>(), vec!["guides"]);
+        assert_eq!(tree["guides"].len(), 2);
+        assert_eq!(docs[0].breadcrumb(), vec!["guides", "felling", "Notching"]);
+        assert_eq!(docs[1].breadcrumb(), vec!["guides"]);
+    }
+}
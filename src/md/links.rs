@@ -0,0 +1,320 @@
+use std::path::{Component, Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Serialize, Deserialize};
+
+use super::{markdown::MarkdownDoc, prose::Prose};
+
+lazy_static! {
+    static ref MD_LINK: Regex = Regex::new(r#"(!?\[[^\]]*\]\()([^)\s]+)((?:\s+"[^"]*")?\))"#).unwrap();
+    static ref HTML_LINK: Regex = Regex::new(r#"((?:href|src)=["'])([^"']+)(["'])"#).unwrap();
+    static ref MD_LINK_PARTS: Regex = Regex::new(r#"(!?)\[([^\]]*)\]\(([^)\s]+)(?:\s+"[^"]*")?\)"#).unwrap();
+    /// Obsidian style wikilinks: `[[Target]]` or `[[Target|Display]]`
+    static ref WIKI_LINK: Regex = Regex::new(r"(!?)\[\[([^\[\]|]+)(?:\|([^\[\]]*))?\]\]").unwrap();
+    static ref SCHEME: Regex = Regex::new(r"^[A-Za-z][A-Za-z0-9+.\-]*:").unwrap();
+}
+
+/// the link text which -- by default -- is considered non-descriptive
+pub const DEFAULT_POOR_LINK_TEXT: [&str; 4] = ["here", "click here", "link", "read more"];
+
+/// A markdown link (or image) found in the prose
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MdLink {
+    /// the visible text of a link or the alt text of an image
+    pub text: String,
+    pub url: String,
+    pub is_image: bool,
+    /// whether this is an Obsidian style `[[wikilink]]`
+    #[serde(default)]
+    pub is_wiki: bool,
+    /// the 1-based line number of the link within the prose
+    pub line: usize,
+}
+
+/// whether a link is relative to the document it is found in (rather than
+/// being absolute, a URL with a scheme or a fragment on the same page)
+pub fn is_relative_link(link: &str) -> bool {
+    !(link.starts_with('/') || link.starts_with('#') || SCHEME.is_match(link))
+}
+
+/// lexically resolves `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut parts: Vec<Component> = vec![];
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => match parts.last() {
+                Some(Component::Normal(_)) => { parts.pop(); }
+                _ => parts.push(c),
+            },
+            _ => parts.push(c),
+        }
+    }
+
+    parts.iter().collect()
+}
+
+/// the path to `target` when starting from the directory `base`
+fn relative_to(target: &Path, base: &Path) -> PathBuf {
+    let target: Vec<Component> = target.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = target.iter().zip(base.iter()).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    base[common..].iter().for_each(|_| relative.push(".."));
+    target[common..].iter().for_each(|c| relative.push(c));
+
+    relative
+}
+
+/// Rewrites a relative link found in a document in the directory `old_base`
+/// so that it resolves to the same location from the directory `new_base`;
+/// any `#fragment` or `?query` suffix is preserved.
+pub fn rebase_link(link: &str, old_base: &str, new_base: &str) -> String {
+    if !is_relative_link(link) {
+        return link.to_string();
+    }
+    let split = link.find(['#', '?']).unwrap_or(link.len());
+    let (path, suffix) = link.split_at(split);
+    if path.is_empty() {
+        return link.to_string();
+    }
+
+    let resolved = normalize(&Path::new(old_base).join(path));
+    let rebased = relative_to(&resolved, &normalize(Path::new(new_base)));
+
+    format!("{}{}", rebased.to_string_lossy().replace('\\', "/"), suffix)
+}
+
+/// The canonical URL of the document at `path` when the site's content lives
+/// in `content_root` and is served from `url_base`: the `.md` extension is
+/// dropped and `index.md` / `README.md` pages map to their directory's URL.
+/// Documents outside of `content_root` have no canonical URL.
+pub fn canonical_url(path: &str, content_root: &str, url_base: &str) -> Option<String> {
+    let path = normalize(Path::new(path));
+    let root = normalize(Path::new(content_root));
+    let relative = path.strip_prefix(&root).ok()?;
+
+    let stem = relative.file_stem()?.to_string_lossy();
+    let dir = relative.parent().map(|p| p.to_string_lossy().replace('\\', "/")).unwrap_or_default();
+    let page = if stem == "index" || stem.eq_ignore_ascii_case("readme") {
+        if dir.is_empty() { String::new() } else { format!("{}/", dir) }
+    } else if dir.is_empty() {
+        stem.to_string()
+    } else {
+        format!("{}/{}", dir, stem)
+    };
+
+    Some(format!("{}/{}", url_base.trim_end_matches('/'), page))
+}
+
+impl Prose {
+    /// all markdown links and images outside of fenced code blocks
+    pub fn links(&self) -> Vec<MdLink> {
+        self.lines_outside_fences()
+            .into_iter()
+            .flat_map(|(line, text)| {
+                MD_LINK_PARTS.captures_iter(text).map(move |c| MdLink {
+                    text: c[2].to_string(),
+                    url: c[3].to_string(),
+                    is_image: !c[1].is_empty(),
+                    is_wiki: false,
+                    line,
+                })
+            })
+            .collect()
+    }
+
+    /// All Obsidian style `[[Target]]` and `[[Target|Display]]` links (and
+    /// `![[Target]]` embeds) outside of fenced code blocks; the display text
+    /// is used as the link's text when given and the target otherwise.
+    pub fn wiki_links(&self) -> Vec<MdLink> {
+        self.lines_outside_fences()
+            .into_iter()
+            .flat_map(|(line, text)| {
+                WIKI_LINK.captures_iter(text).map(move |c| {
+                    let target = c[2].trim().to_string();
+                    MdLink {
+                        text: c.get(3).map(|d| d.as_str().trim().to_string()).unwrap_or(target.clone()),
+                        url: target,
+                        is_image: !c[1].is_empty(),
+                        is_wiki: true,
+                        line,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// the targets of all Obsidian style `[[wikilinks]]` in the prose
+    pub fn extract_wiki_links(&self) -> Vec<String> {
+        self.wiki_links().into_iter().map(|l| l.url).collect()
+    }
+
+    /// links whose visible text is one of the `DEFAULT_POOR_LINK_TEXT`'s
+    pub fn poor_link_text(&self) -> Vec<MdLink> {
+        let blacklist: Vec<String> = DEFAULT_POOR_LINK_TEXT.iter().map(|t| t.to_string()).collect();
+        self.poor_link_text_with(&blacklist)
+    }
+
+    /// Links whose visible text -- as a whole and ignoring case -- matches
+    /// an entry in `blacklist`; images are not considered.
+    pub fn poor_link_text_with(&self, blacklist: &[String]) -> Vec<MdLink> {
+        self.links()
+            .into_iter()
+            .filter(|l| !l.is_image)
+            .filter(|l| blacklist.iter().any(|b| b.trim().eq_ignore_ascii_case(l.text.trim())))
+            .collect()
+    }
+}
+
+/// A link from one document to a local target, resolved to a path
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResolvedLink {
+    /// the document the link is found in
+    pub source: String,
+    /// the path the link resolves to
+    pub target: String,
+    /// whether the target exists on disk
+    pub exists: bool,
+}
+
+/// The links between a set of documents; targets which do not exist
+/// identify dead pages.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LinkGraph {
+    pub edges: Vec<ResolvedLink>,
+}
+
+/// Builds the graph of internal links between documents; each document's
+/// links are resolved relative to the directory it was loaded from.
+pub fn link_graph(docs: &[MarkdownDoc]) -> LinkGraph {
+    LinkGraph {
+        edges: docs
+            .iter()
+            .flat_map(|doc| {
+                let base = doc.file
+                    .as_ref()
+                    .and_then(|f| Path::new(f.filename()).parent().map(|p| p.to_string_lossy().to_string()))
+                    .unwrap_or_default();
+                doc.internal_links(&base)
+            })
+            .collect(),
+    }
+}
+
+impl MarkdownDoc {
+    /// Resolves the relative markdown links and `[[wikilinks]]` of a
+    /// document in the directory `base_dir` to the paths they point at; a
+    /// wikilink without an extension refers to a markdown file. Images and
+    /// links to a fragment of the same page are not included.
+    pub fn internal_links(&self, base_dir: &str) -> Vec<ResolvedLink> {
+        let source = self.file.as_ref().map(|f| f.filename()).unwrap_or_default();
+        let markdown = self.prose.links().into_iter().filter(|l| is_relative_link(&l.url));
+        let wiki = self.prose.wiki_links().into_iter();
+
+        markdown
+            .chain(wiki)
+            .filter(|l| !l.is_image)
+            .filter_map(|l| {
+                let path = &l.url[..l.url.find(['#', '?']).unwrap_or(l.url.len())];
+                if path.is_empty() {
+                    return None;
+                }
+                let mut target = normalize(&Path::new(base_dir).join(path));
+                if l.is_wiki && target.extension().is_none() {
+                    target.set_extension("md");
+                }
+
+                Some(ResolvedLink {
+                    source: source.to_string(),
+                    exists: target.exists(),
+                    target: target.to_string_lossy().replace('\\', "/"),
+                })
+            })
+            .collect()
+    }
+
+    /// Rewrites all relative markdown links and images -- as well as the
+    /// `href` and `src` attributes of HTML tags -- in a document which lived
+    /// in the directory `old_base` so that they are correct from `new_base`.
+    pub fn normalize_links(&self, old_base: &str, new_base: &str) -> Prose {
+        let rebase = |c: &Captures| format!("{}{}{}", &c[1], rebase_link(&c[2], old_base, new_base), &c[3]);
+
+        self.prose.map_outside_fences(|line| {
+            let line = MD_LINK.replace_all(line, rebase);
+            HTML_LINK.replace_all(&line, rebase).to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINKS: &str = "See [sibling](../sibling/doc.md#intro) and ![img](./img/a.png).\n\n<a href=\"local.md\">local</a> [web](https://example.com) [top](#top)\n";
+
+    #[test]
+    fn relative_links_are_rebased() {
+        let doc = MarkdownDoc::try_from(LINKS).unwrap();
+        let prose = doc.normalize_links("docs/a", "docs/b/c");
+
+        assert!(prose.content.contains("[sibling](../../sibling/doc.md#intro)"));
+        assert!(prose.content.contains("![img](../../a/img/a.png)"));
+        assert!(prose.content.contains("href=\"../../a/local.md\""));
+        assert!(prose.content.contains("[web](https://example.com)"));
+        assert!(prose.content.contains("[top](#top)"));
+    }
+
+    #[test]
+    fn poor_link_text_is_flagged() {
+        let prose = Prose::from("To install, [Click Here](./install.md).\n\nSee the [installation guide](./install.md) or ![here](a.png).\n");
+        let poor = prose.poor_link_text();
+
+        assert_eq!(poor.len(), 1);
+        assert_eq!(poor[0].text, "Click Here");
+        assert_eq!(poor[0].line, 1);
+        assert!(prose.poor_link_text_with(&["installation guide".to_string()])[0].line == 3);
+    }
+
+    #[test]
+    fn wiki_links_are_extracted() {
+        let prose = Prose::from("See [[Getting Started]] and [[setup#Install|the install guide]].\n\n```\n[[Not A Link]]\n```\n![[diagram.png]]\n");
+        let links = prose.wiki_links();
+
+        assert_eq!(prose.extract_wiki_links(), vec!["Getting Started", "setup#Install", "diagram.png"]);
+        assert_eq!(links[1].text, "the install guide");
+        assert!(links.iter().all(|l| l.is_wiki));
+        assert!(links[2].is_image);
+    }
+
+    #[test]
+    fn canonical_urls_are_derived_from_paths() {
+        let base = "https://example.com/docs/";
+
+        assert_eq!(canonical_url("content/guide/index.md", "content", base), Some("https://example.com/docs/guide/".to_string()));
+        assert_eq!(canonical_url("./content/guide/setup.md", "content/", base), Some("https://example.com/docs/guide/setup".to_string()));
+        assert_eq!(canonical_url("content/README.md", "content", base), Some("https://example.com/docs/".to_string()));
+        assert_eq!(canonical_url("other/page.md", "content", base), None);
+    }
+
+    #[test]
+    fn internal_links_are_resolved() {
+        let doc = MarkdownDoc::try_from("[song](./lumberjack.md#chorus) [[sections]] [[Missing Page|gone]] [web](https://example.com) [top](#top) ![img](pic.png)\n").unwrap();
+        let links = doc.internal_links("test/data");
+        let resolved: Vec<(&str, bool)> = links.iter().map(|l| (l.target.as_str(), l.exists)).collect();
+
+        assert_eq!(resolved, vec![
+            ("test/data/lumberjack.md", true),
+            ("test/data/sections.md", true),
+            ("test/data/Missing Page.md", false),
+        ]);
+    }
+
+    #[test]
+    fn rebase_to_same_dir_is_unchanged() {
+        assert_eq!(rebase_link("../sibling/doc.md", "docs/a", "docs/a"), "../sibling/doc.md");
+        assert_eq!(rebase_link("/abs/path.md", "docs/a", "docs/b"), "/abs/path.md");
+    }
+}
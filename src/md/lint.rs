@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde::{Serialize, Deserialize};
+
+use super::markdown::MarkdownDoc;
+
+/// The checks run by the `lint` subcommand
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintCheck {
+    /// footnotes which are referenced but never defined
+    UndefinedRefs,
+    /// headings which skip a level (e.g., an `h1` followed by an `h3`)
+    HeadingHierarchy,
+    /// headings whose section has no words at all
+    EmptySections,
+    /// links with non-descriptive text such as "click here"
+    PoorLinkText,
+    /// links to a missing heading of the document or a missing local file
+    BrokenLinks,
+}
+
+impl LintCheck {
+    /// every lint check, in the order they are run
+    pub const ALL: [LintCheck; 5] = [
+        LintCheck::UndefinedRefs,
+        LintCheck::HeadingHierarchy,
+        LintCheck::EmptySections,
+        LintCheck::PoorLinkText,
+        LintCheck::BrokenLinks,
+    ];
+}
+
+/// A single problem found by a lint check
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LintIssue {
+    pub check: LintCheck,
+    /// the file the issue was found in, if the document was loaded from one
+    pub file: Option<String>,
+    pub message: String,
+}
+
+/// The issues found across a set of documents grouped by the check which
+/// found them
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LintSummary {
+    pub documents: usize,
+    /// the total number of issues found
+    pub issues: usize,
+    /// the number of issues found by each check which was run
+    pub counts: BTreeMap<LintCheck, usize>,
+    pub by_check: BTreeMap<LintCheck, Vec<LintIssue>>,
+}
+
+impl MarkdownDoc {
+    /// Runs the given lint `checks` against the document; `poor_link_text`
+    /// is the link text which the `PoorLinkText` check flags.
+    pub fn lint(&self, checks: &[LintCheck], poor_link_text: &[String]) -> Vec<LintIssue> {
+        let file = self.file.as_ref().map(|f| f.filename().to_string());
+        let issue = |check: LintCheck, message: String| LintIssue { check, file: file.clone(), message };
+        let mut issues: Vec<LintIssue> = vec![];
+
+        for check in LintCheck::ALL.iter().filter(|c| checks.contains(c)) {
+            match check {
+                LintCheck::UndefinedRefs => issues.extend(
+                    self.prose.footnotes().into_iter().filter(|f| f.is_undefined()).map(|f| {
+                        issue(*check, format!("the footnote [^{}] is referenced but never defined", f.label))
+                    })
+                ),
+                LintCheck::HeadingHierarchy => issues.extend(self.prose.heading_jumps().into_iter().map(|j| {
+                    issue(*check, format!("the heading on line {} jumps from an h{} to an h{}", j.line, j.from, j.to))
+                })),
+                LintCheck::EmptySections => issues.extend(
+                    self.prose.section_words().into_iter().filter(|(_, words)| *words == 0).map(|(heading, _)| {
+                        issue(*check, format!("the section '{}' is empty", heading))
+                    })
+                ),
+                LintCheck::PoorLinkText => issues.extend(
+                    self.prose.poor_link_text_with(poor_link_text).into_iter().map(|l| {
+                        issue(*check, format!("the link on line {} has the non-descriptive text '{}'", l.line, l.text))
+                    })
+                ),
+                LintCheck::BrokenLinks => issues.extend(
+                    self.broken_links().into_iter().map(|url| issue(*check, format!("the link to '{}' is broken", url)))
+                ),
+            }
+        }
+
+        issues
+    }
+}
+
+/// Lints every document and groups the issues found by check
+pub fn lint_docs(docs: &[MarkdownDoc], checks: &[LintCheck], poor_link_text: &[String]) -> LintSummary {
+    let mut summary = LintSummary { documents: docs.len(), ..LintSummary::default() };
+    for check in checks {
+        summary.counts.insert(*check, 0);
+    }
+    for issue in docs.iter().flat_map(|d| d.lint(checks, poor_link_text)) {
+        summary.issues += 1;
+        *summary.counts.entry(issue.check).or_default() += 1;
+        summary.by_check.entry(issue.check).or_default().push(issue);
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    const DOC: &str = "# Guide\n\nSee [here](#nowhere) and note[^1].\n\n### Deep\n\nText.\n\n## Empty\n";
+
+    #[test]
+    fn every_check_reports_its_issues() {
+        let doc = MarkdownDoc::try_from(DOC).unwrap();
+        let config = Config::default();
+        let summary = lint_docs(&[doc], &LintCheck::ALL, &config.poor_link_text);
+
+        assert_eq!(summary.documents, 1);
+        assert_eq!(summary.issues, 5);
+        assert!(summary.counts.values().all(|count| *count == 1));
+        assert!(summary.by_check[&LintCheck::EmptySections][0].message.contains("'Empty'"));
+    }
+
+    #[test]
+    fn only_the_selected_checks_run() {
+        let doc = MarkdownDoc::try_from(DOC).unwrap();
+        let issues = doc.lint(&[LintCheck::HeadingHierarchy], &[]);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].check, LintCheck::HeadingHierarchy);
+    }
+}
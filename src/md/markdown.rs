@@ -1,42 +1,55 @@
 use serde::{Serialize, Deserialize};
+use serde_json::json;
 use tracing::{instrument, info};
 
+use std::env;
+
+use crate::config::Config;
 use crate::file::{FileMeta, FileWithMeta};
+use crate::hasher::hash;
 use crate::errors::md::MarkdownError;
 use lazy_static::lazy_static;
-use regex::{Regex, RegexBuilder};
+use regex::Regex;
 use super::{
+    callout::Callout,
+    heading::number_headings,
     prose::Prose, 
-    frontmatter::Frontmatter,
+    frontmatter::{Frontmatter, FmFence, default_fences},
 };
 
 // [Docs](https://docs.rs/regex/latest/regex/struct.Regex.html)
 lazy_static! {
-    static ref HAS_FM: Regex = RegexBuilder::new(r"^---\n.?*\n---")
-        .multi_line(false)
-        .build()
-        .unwrap();
-    static ref MD_PARTS: Regex = Regex::new(r"^---.*---(.*)").unwrap();
+    static ref HTML_LANG: Regex = Regex::new(r#"<html[^>]*\slang=["']([^"']+)["']"#).unwrap();
+    static ref PROSE_TAG: Regex = Regex::new(r"(?:^|\s)#([A-Za-z][\w/\-]*)").unwrap();
 }
 
+/// the default prefix of the environment variables which override
+/// frontmatter values (see `MarkdownDoc::apply_env_overrides`)
+pub const FM_ENV_PREFIX: &str = "CTX_FM_";
+
 /// determines whether content representing the raw
 /// text from a Markdown file, contains a frontmatter
 /// section or not.
 #[instrument]
 pub fn has_frontmatter(content: &str) -> bool {
-    HAS_FM.is_match(content)
+    has_frontmatter_with(content, &default_fences())
+}
+
+/// determines whether the raw content starts with a frontmatter section
+/// delimited by any of the given fences
+pub fn has_frontmatter_with(content: &str, fences: &[FmFence]) -> bool {
+    fences.iter().any(|f| f.split(content).is_some())
 }
 
 /// given a raw content string, this will return a string
 /// which ensures that there is NOT any frontmatter content
 #[instrument]
 pub fn exclude_frontmatter(content: &str) -> String {
-    if has_frontmatter(content) {
-        let replacement = MD_PARTS.replace(content, "$1").to_string();
-        replacement
-    } else {
-        content.to_string()
-    }
+    default_fences()
+        .iter()
+        .find_map(|f| f.split(content))
+        .map(|(_, rest)| rest.to_string())
+        .unwrap_or(content.to_string())
 }
 
 
@@ -46,17 +59,44 @@ pub fn exclude_frontmatter(content: &str) -> String {
 pub fn split_fm_from_prose(
     raw_content: &str
 ) -> Result<(Prose, Option<Frontmatter>), MarkdownError> {
+    split_fm_from_prose_with(raw_content, &default_fences(), false)
+}
 
+/// Splits the `Prose` and `Frontmatter` sections of the raw content where
+/// the frontmatter may be delimited by any of the given fences; the first
+/// fence to match determines the engine used to parse the frontmatter.
+/// When `lenient` is set, boolean-like strings in the typed boolean
+/// properties are coerced (see `Frontmatter::parse_lenient`).
+#[instrument]
+pub fn split_fm_from_prose_with(
+    raw_content: &str,
+    fences: &[FmFence],
+    lenient: bool
+) -> Result<(Prose, Option<Frontmatter>), MarkdownError> {
+    split_fm_from_prose_hashed(raw_content, fences, lenient, true)
+}
+
+/// splits the content like `split_fm_from_prose_with` but only hashes the
+/// prose when `hashed` is set
+fn split_fm_from_prose_hashed(
+    raw_content: &str,
+    fences: &[FmFence],
+    lenient: bool,
+    hashed: bool
+) -> Result<(Prose, Option<Frontmatter>), MarkdownError> {
+    let to_prose = |content: &str| if hashed { Prose::from(content) } else { Prose::unhashed(content) };
     let frontmatter: Option<Frontmatter>;
     let prose: Prose;
 
-    if has_frontmatter(raw_content) {
-        let fm = Frontmatter::try_from(raw_content)?;
-        frontmatter = Some(fm);
-        prose = Prose::from(exclude_frontmatter(raw_content));
+    if let Some((fence, (fm, rest))) = fences.iter().find_map(|f| f.split(raw_content).map(|s| (f, s))) {
+        frontmatter = Some(match lenient {
+            true => Frontmatter::parse_lenient(fm, fence.engine())?.0,
+            false => Frontmatter::parse(fm, fence.engine())?,
+        });
+        prose = to_prose(rest);
     } else {
         frontmatter = None;
-        prose = Prose::from(exclude_frontmatter(raw_content));        
+        prose = to_prose(raw_content);
     }
 
     info!(
@@ -83,10 +123,308 @@ pub struct MarkdownStructure {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarkdownDoc {
     pub has_frontmatter: bool,
+    /// whether the frontmatter marks this document as the index page of a
+    /// multi-page documentation site
+    pub is_index: bool,
+    /// the language of the document, if it could be inferred
+    pub language: Option<String>,
+    /// the tags from both the frontmatter and the prose
+    pub all_tags: Vec<String>,
+    /// the callout (admonition) blocks found in the prose
+    pub callouts: Vec<Callout>,
+    /// the number of words in the prose
+    pub word_count: usize,
     pub fm: Option<Frontmatter>,
     pub prose: Prose,
     pub structure: Option<MarkdownStructure>,
-    pub file: Option<FileMeta>
+    pub file: Option<FileMeta>,
+    /// whether invalid UTF-8 in the file was replaced when it was read
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy: bool,
+}
+
+impl MarkdownDoc {
+    /// Provides the reason -- if any -- that the configuration says this
+    /// document should be skipped rather than processed.
+    /// 
+    /// - documents whose `config.draft_field` property is `true` are skipped
+    ///   unless `config.include_drafts` is set
+    /// - documents with `requires_auth: true` are skipped when
+    ///   `config.skip_requires_auth` is set
+    pub fn skip_reason(&self, config: &Config) -> Option<String> {
+        let fm = self.fm.as_ref()?;
+        let is_draft = fm.other
+            .get(&config.draft_field)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if is_draft && !config.include_drafts {
+            Some(config.draft_field.clone())
+        } else if config.skip_requires_auth && fm.requires_auth == Some(true) {
+            Some("requires_auth".to_string())
+        } else if config.skip_expired && fm.is_expired() {
+            Some("expired".to_string())
+        } else {
+            None
+        }
+    }
+
+    /// the language of the document as set in the frontmatter or -- failing
+    /// that -- an HTML `lang` attribute in the prose
+    pub fn inferred_language(&self) -> Option<String> {
+        let from_fm = self.fm.as_ref().and_then(|fm| {
+            fm.other
+                .get("lang")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+                .or(fm.language.clone())
+        });
+
+        from_fm.or_else(|| {
+            HTML_LANG
+                .captures(&self.prose.content)
+                .map(|c| c[1].to_string())
+        })
+    }
+
+    /// In multi-page documentation sites, the index page is often indicated
+    /// by `index: true` or `slug: /` in the frontmatter.
+    pub fn is_index_page(&self) -> bool {
+        match &self.fm {
+            Some(fm) => {
+                fm.other.get("index").and_then(|v| v.as_bool()).unwrap_or(false)
+                    || fm.other.get("slug") == Some(&json!("/"))
+            }
+            None => false,
+        }
+    }
+
+    /// The position of the document in a documentation site's hierarchy as
+    /// `[category, subcategory, title]` from the frontmatter; properties
+    /// which are not set are skipped.
+    pub fn breadcrumb(&self) -> Vec<String> {
+        let Some(fm) = self.fm.as_ref() else { return vec![] };
+        let subcategory = fm.other.get("subcategory").and_then(|v| v.as_str()).map(|v| v.to_string());
+
+        [fm.category.clone(), subcategory, fm.title.clone()].into_iter().flatten().collect()
+    }
+
+    /// Tags expressed as `#tag` tokens in the prose (Obsidian-style);
+    /// markdown headings, URL fragments and fenced code are not considered.
+    pub fn tags_from_prose(&self) -> Vec<String> {
+        let mut tags: Vec<String> = vec![];
+        for (_, line) in self.prose.lines_outside_fences() {
+            for cap in PROSE_TAG.captures_iter(line) {
+                let tag = cap[1].to_string();
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        tags
+    }
+
+    /// The de-duplicated union of the frontmatter `tags` and the tags found
+    /// in the prose.
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.fm
+            .as_ref()
+            .and_then(|fm| fm.tags.clone())
+            .unwrap_or_default();
+        for tag in self.tags_from_prose() {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        tags
+    }
+
+    /// A stable identifier for the document of the form
+    /// `<content>-<path>` where:
+    ///
+    /// - `<content>` is the xxh3 hash of the prose (`prose.content`) as 16
+    ///   lowercase hex digits, so it survives a rename while the content is
+    ///   unchanged
+    /// - `<path>` is the upper 32 bits of the xxh3 hash of the filename --
+    ///   as provided, with any leading `./` removed -- as 8 lowercase hex
+    ///   digits; a document without a file hashes the empty string
+    ///
+    /// Matching on the `<content>` part finds a renamed document while the
+    /// full ID tells apart identical content at different paths.
+    pub fn stable_id(&self) -> String {
+        let path = self.file.as_ref().map(|f| f.filename().trim_start_matches("./")).unwrap_or_default();
+
+        format!("{:016x}-{:08x}", hash(&self.prose.content), hash(path) >> 32)
+    }
+
+    /// The change in word count between an `old` and `new` version of a
+    /// document; a negative number indicates words were removed.
+    pub fn word_count_delta(old: &MarkdownDoc, new: &MarkdownDoc) -> i64 {
+        new.prose.word_count() as i64 - old.prose.word_count() as i64
+    }
+
+    /// `(number, heading_text)` pairs -- such as `("1.2.1", "Installation")`
+    /// -- for every heading, suitable for a printable, numbered outline
+    pub fn numbered_outline(&self) -> Vec<(String, String)> {
+        number_headings(&self.prose.headings())
+    }
+
+    /// The heading hierarchy as an indented markdown list -- two spaces per
+    /// level below the shallowest heading -- where each heading links to its
+    /// anchor; the heading's line number is appended when `line_numbers` is
+    /// set.
+    pub fn outline_markdown(&self, line_numbers: bool) -> String {
+        let headings = self.prose.headings();
+        let top = headings.iter().map(|h| h.level).min().unwrap_or(1);
+        let file = self.file.as_ref().map(|f| f.filename()).unwrap_or_default();
+
+        headings
+            .iter()
+            .map(|h| {
+                let indent = "  ".repeat((h.level - top) as usize);
+                let line = if line_numbers { format!(" (line {})", h.line) } else { String::new() };
+                format!("{}- [{}]({}#{}){}\n", indent, h.text, file, h.slug, line)
+            })
+            .collect()
+    }
+
+    /// Builds a `MarkdownDoc` from the raw content of a markdown file where
+    /// the frontmatter may be delimited by any of the given fences.
+    pub fn parse(raw: &str, fences: &[FmFence]) -> Result<Self, MarkdownError> {
+        MarkdownDoc::from_raw(raw, None, fences, false, true)
+    }
+
+    /// Builds a `MarkdownDoc` from a loaded file where the frontmatter may
+    /// be delimited by any of the given fences.
+    pub fn from_file(file: FileWithMeta, fences: &[FmFence]) -> Result<Self, MarkdownError> {
+        MarkdownDoc::from_raw(&file.content, Some(file.meta), fences, false, true)
+    }
+
+    /// Builds a `MarkdownDoc` from raw content using the frontmatter fences,
+    /// leniency and hashing of the configuration.
+    pub fn parse_with(raw: &str, config: &Config) -> Result<Self, MarkdownError> {
+        MarkdownDoc::from_raw(raw, None, &config.fm_fences, config.lenient_fm, !config.no_hash)
+    }
+
+    /// Builds a `MarkdownDoc` from a loaded file using the frontmatter
+    /// fences, leniency and hashing of the configuration.
+    pub fn from_file_with(file: FileWithMeta, config: &Config) -> Result<Self, MarkdownError> {
+        let mut doc = MarkdownDoc::from_raw(
+            &file.content,
+            Some(file.meta),
+            &config.fm_fences,
+            config.lenient_fm,
+            !config.no_hash
+        )?;
+        doc.lossy = file.lossy;
+
+        Ok(doc)
+    }
+
+    /// builds a `MarkdownDoc` from the raw content of a markdown file and
+    /// then populates all of the properties derived from that content; the
+    /// prose is only hashed when `hashed` is set
+    fn from_raw(
+        raw: &str,
+        file: Option<FileMeta>,
+        fences: &[FmFence],
+        lenient: bool,
+        hashed: bool
+    ) -> Result<Self, MarkdownError> {
+        let (prose, fm) = split_fm_from_prose_hashed(raw, fences, lenient, hashed)?;
+
+        Ok(MarkdownDoc::from_parts(prose, fm, file))
+    }
+
+    /// Sets the frontmatter `field` to the textual `value` (see
+    /// `typed_value`) -- adding frontmatter when the document has none --
+    /// and refreshes the properties derived from the frontmatter. Only the
+    /// document in memory is changed.
+    pub fn update_frontmatter(&mut self, field: &str, value: &str) -> Result<(), MarkdownError> {
+        self.fm.get_or_insert_with(Frontmatter::default).update(field, value)?;
+        self.frontmatter_changed();
+
+        Ok(())
+    }
+
+    /// refreshes the properties derived from the frontmatter once it has
+    /// been changed
+    pub(crate) fn frontmatter_changed(&mut self) {
+        self.has_frontmatter = true;
+        self.is_index = self.is_index_page();
+        self.language = self.inferred_language();
+        self.all_tags = self.all_tags();
+    }
+
+    /// Overrides frontmatter values with the environment variables named
+    /// with the given `prefix` (e.g., `CTX_FM_VERSION=1.2` sets `version`
+    /// with the `FM_ENV_PREFIX` prefix); the field is the rest of the variable's
+    /// name in lowercase.
+    pub fn apply_env_overrides(&mut self, prefix: &str) -> Result<(), MarkdownError> {
+        let mut overrides: Vec<(String, String)> = env::vars()
+            .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?.to_lowercase(), v)))
+            .filter(|(field, _)| !field.is_empty())
+            .collect();
+        overrides.sort();
+        for (field, value) in overrides {
+            self.update_frontmatter(&field, &value)?;
+        }
+
+        Ok(())
+    }
+
+    /// builds a `MarkdownDoc` from its prose and frontmatter and then
+    /// populates all of the properties derived from them
+    fn from_parts(prose: Prose, fm: Option<Frontmatter>, file: Option<FileMeta>) -> Self {
+        let mut doc = MarkdownDoc {
+            has_frontmatter: fm.is_some(),
+            is_index: false,
+            language: None,
+            all_tags: vec![],
+            callouts: vec![],
+            word_count: 0,
+            fm,
+            prose,
+            structure: None,
+            file,
+            lossy: false,
+        };
+        doc.is_index = doc.is_index_page();
+        doc.language = doc.inferred_language();
+        doc.all_tags = doc.all_tags();
+        doc.callouts = doc.prose.callouts();
+        doc.word_count = doc.prose.word_count();
+
+        doc
+    }
+
+    /// Splits the document on its `h2` headings into one document per
+    /// section; each section keeps its heading, has a copy of the original
+    /// frontmatter and takes its `title` from the heading. Content before
+    /// the first `h2` is not part of any section.
+    pub fn split_on_h2(&self) -> Vec<MarkdownDoc> {
+        let lines: Vec<&str> = self.prose.content.split_inclusive('\n').collect();
+        let starts: Vec<(usize, String)> = self.prose
+            .headings()
+            .into_iter()
+            .filter(|h| h.level == 2)
+            .map(|h| (h.line - 1, h.text))
+            .collect();
+
+        starts
+            .iter()
+            .enumerate()
+            .map(|(idx, (start, title))| {
+                let end = starts.get(idx + 1).map(|(next, _)| *next).unwrap_or(lines.len());
+                let mut fm = self.fm.clone().unwrap_or_default();
+                fm.title = Some(title.clone());
+
+                MarkdownDoc::from_parts(Prose::from(lines[*start..end].concat()), Some(fm), None)
+            })
+            .collect()
+    }
 }
 
 /// try to create a `MarkdownDoc` from a string slice which represents the content
@@ -95,15 +433,7 @@ impl TryFrom<&str> for MarkdownDoc {
     type Error = MarkdownError;
 
     fn try_from(raw_content: &str) -> Result<Self, Self::Error> {
-        let (prose, fm) = split_fm_from_prose(raw_content)?;
-
-        Ok(MarkdownDoc {
-            has_frontmatter: has_frontmatter(raw_content),
-            fm,
-            prose,
-            structure: None,
-            file: None
-        })
+        MarkdownDoc::parse(raw_content, &default_fences())
     }
 }
 
@@ -111,29 +441,16 @@ impl TryFrom<FileWithMeta> for MarkdownDoc {
     type Error = MarkdownError;
 
     fn try_from(value: FileWithMeta) -> Result<Self, Self::Error> {
-        let raw = value.content.as_str();
-        let (
-            prose, 
-            fm
-        ) = split_fm_from_prose(
-            raw
-        )?;
-        
-        Ok(Self {
-            has_frontmatter: has_frontmatter(raw),
-            fm,
-            prose,
-            file: Some(value.meta),
-            structure: None
-        })
+        MarkdownDoc::from_file(value, &default_fences())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tracing::{info, Level};
-    use tracing_subscriber;
+    use tracing::Level;
+    use crate::test_support::init_tracing_once;
+    use crate::md::reporting::load_md;
 
     const PROSE_ONLY: &str = r#"
 # Hello World\n
@@ -152,33 +469,142 @@ baz: "baz"
 
 Hello World
 "#;
+    #[allow(dead_code)]
     fn trace(lvl: Level) {
-        let collector = tracing_subscriber::fmt()
-            // filter spans/events with level TRACE or higher.
-            .with_max_level(lvl)
-            .pretty()
-            // build but do not install the subscriber.
-            .finish();
+        init_tracing_once(lvl);
+    }
 
-        tracing::subscriber::set_global_default(collector).unwrap();
 
-        info!("Tracing for tests enabled");
+    #[test]
+    fn index_page_detected_from_frontmatter() {
+        let index = MarkdownDoc::try_from("---\nindex: true\n---\n# Home\n").unwrap();
+        let slug = MarkdownDoc::try_from("---\nslug: /\n---\n# Home\n").unwrap();
+        let other = MarkdownDoc::try_from(FM_CONTENT).unwrap();
+        let no_fm = MarkdownDoc::try_from(PROSE_ONLY).unwrap();
+
+        assert!(index.is_index_page());
+        assert!(index.is_index);
+        assert!(slug.is_index_page());
+        assert!(!other.is_index_page());
+        assert!(!no_fm.is_index_page());
     }
 
+    #[test]
+    fn draft_skipped_by_default_and_included_with_flag() {
+        let draft = MarkdownDoc::try_from("---\ndraft: true\n---\n# WIP\n").unwrap();
+        let mut config = Config::default();
+        assert_eq!(draft.skip_reason(&config), Some("draft".to_string()));
+
+        config.include_drafts = true;
+        assert_eq!(draft.skip_reason(&config), None);
+    }
+
+    #[test]
+    fn requires_auth_skipped_when_configured() {
+        let private = MarkdownDoc::try_from("---\nrequires_auth: true\n---\n# Secret\n").unwrap();
+        let mut config = Config::default();
+        assert_eq!(private.skip_reason(&config), None);
+
+        config.skip_requires_auth = true;
+        assert_eq!(private.skip_reason(&config), Some("requires_auth".to_string()));
+    }
+
+    #[test]
+    fn frontmatter_is_overridden_from_the_environment() {
+        env::set_var("CTX_TEST_OVERRIDE_VERSION", "1.2.3");
+        env::set_var("CTX_TEST_OVERRIDE_TITLE", "From CI");
+        env::set_var("CTX_TEST_OVERRIDE_TAGS", "ci, build");
+        let mut doc = MarkdownDoc::try_from("---\ntitle: Original\n---\n# Hello\n").unwrap();
+        let mut no_fm = MarkdownDoc::try_from("# Hello\n").unwrap();
+        doc.apply_env_overrides("CTX_TEST_OVERRIDE_").unwrap();
+        no_fm.apply_env_overrides("CTX_TEST_OVERRIDE_").unwrap();
+
+        let fm = doc.fm.as_ref().unwrap();
+        assert_eq!(fm.title.as_deref(), Some("From CI"));
+        assert_eq!(fm.other.get("version"), Some(&json!("1.2.3")));
+        assert_eq!(doc.all_tags, vec!["ci".to_string(), "build".to_string()]);
+        assert!(no_fm.has_frontmatter);
+        assert_eq!(no_fm.fm.unwrap().title.as_deref(), Some("From CI"));
+    }
+
+    #[test]
+    fn expired_skipped_when_configured() {
+        let expired = MarkdownDoc::try_from("---\nexpires: 2001-01-01\n---\n# Old news\n").unwrap();
+        let current = MarkdownDoc::try_from("---\nexpires: 9999-12-31\n---\n# Future\n").unwrap();
+        let mut config = Config::default();
+        assert_eq!(expired.skip_reason(&config), None);
+
+        config.skip_expired = true;
+        assert_eq!(expired.skip_reason(&config), Some("expired".to_string()));
+        assert_eq!(current.skip_reason(&config), None);
+    }
+
+    #[test]
+    fn language_inferred_from_frontmatter_then_html() {
+        let fm = MarkdownDoc::try_from("---\nlang: fr\n---\n# Bonjour\n").unwrap();
+        let field = MarkdownDoc::try_from("---\nlanguage: de\n---\n# Hallo\n").unwrap();
+        let html = MarkdownDoc::try_from("<html lang=\"en\">\n\n# Hello\n").unwrap();
+
+        assert_eq!(fm.inferred_language(), Some("fr".to_string()));
+        assert_eq!(field.inferred_language(), Some("de".to_string()));
+        assert_eq!(html.language, Some("en".to_string()));
+        assert_eq!(MarkdownDoc::try_from(PROSE_ONLY).unwrap().inferred_language(), None);
+    }
+
+    #[test]
+    fn tags_from_prose_merge_with_frontmatter_tags() {
+        let doc = MarkdownDoc::try_from(
+            "---\ntags: [rust, docs]\n---\n# Heading\n\nSome #rust and #cli notes, see [x](http://a.com/#frag).\n"
+        ).unwrap();
+
+        assert_eq!(doc.tags_from_prose(), vec!["rust".to_string(), "cli".to_string()]);
+        assert_eq!(doc.all_tags, vec!["rust".to_string(), "docs".to_string(), "cli".to_string()]);
+    }
+
+    #[test]
+    fn custom_fence_is_used_for_detection() {
+        let raw = "<!--meta\ntitle: custom\n-->\n# Body\n";
+        let mut fences = default_fences();
+        assert!(!has_frontmatter_with(raw, &fences));
+
+        fences.insert(0, FmFence::new("<!--meta", "-->"));
+        let doc = MarkdownDoc::parse(raw, &fences).unwrap();
+        assert!(doc.has_frontmatter);
+        assert_eq!(doc.fm.unwrap().title, Some("custom".to_string()));
+        assert_eq!(doc.prose.content, "# Body\n");
+    }
+
+    #[test]
+    fn stable_id_is_derived_from_content_and_path() {
+        let doc = MarkdownDoc::try_from("# Stable\n\nUnchanged content.\n").unwrap();
+        let same = MarkdownDoc::try_from("---\ntitle: Other\n---\n# Stable\n\nUnchanged content.\n").unwrap();
+        let changed = MarkdownDoc::try_from("# Stable\n\nChanged content.\n").unwrap();
+        let id = doc.stable_id();
+
+        assert_eq!(id, format!("{:016x}-{:08x}", hash("# Stable\n\nUnchanged content.\n"), hash("") >> 32));
+        assert_eq!(id, "cc4af53850968353-2d068005");
+        assert_eq!(same.stable_id(), id);
+        assert_ne!(changed.stable_id(), id);
+
+        let file = load_md("test/data/lumberjack.md", &Config::default()).unwrap();
+        let again = load_md("./test/data/lumberjack.md", &Config::default()).unwrap();
+        assert_eq!(file.stable_id(), again.stable_id());
+        assert!(file.stable_id().ends_with(&format!("-{:08x}", hash("test/data/lumberjack.md") >> 32)));
+    }
 
     #[test]
     fn prose_only_returns_false() {
-        assert_eq!(has_frontmatter(PROSE_ONLY), false);
+        assert!(!has_frontmatter(PROSE_ONLY));
     }
 
     #[test]
     fn fm_content_returns_true() {
-        assert_eq!(has_frontmatter(FM_CONTENT), false);
+        assert!(has_frontmatter(FM_CONTENT));
     }
 
     #[test]
     fn fm_content_with_extraction_returns_false() {
-        assert_eq!(has_frontmatter(&exclude_frontmatter(FM_CONTENT)), false);
+        assert!(!has_frontmatter(&exclude_frontmatter(FM_CONTENT)));
     }
 
     #[test]
@@ -188,6 +614,9 @@ Hello World
             FM_CONTENT
         ).unwrap();
 
+        assert!(!prose.content.contains("---"));
+        assert!(prose.content.contains("# With Frontmatter"));
+
         if let Some(fm) = fm {
             let foo = fm.other.get("foo");
             let bar = fm.other.get("bar");
@@ -207,5 +636,27 @@ Hello World
 
     }
 
+    #[test]
+    fn split_on_h2_creates_a_doc_per_section() {
+        let doc = MarkdownDoc::try_from(include_str!("../../test/data/sections.md")).unwrap();
+        let sections = doc.split_on_h2();
+        let titles: Vec<String> = sections.iter().filter_map(|s| s.fm.as_ref()?.title.clone()).collect();
+
+        assert_eq!(titles, vec!["Felling", "Limbing", "Bucking"]);
+        assert!(sections.iter().all(|s| s.all_tags == vec!["guide".to_string()]));
+        assert!(sections[0].prose.content.starts_with("## Felling"));
+        assert!(!sections[0].prose.content.contains("introduction"));
+        assert!(sections[1].prose.content.contains("### Safety"));
+        assert!(sections[1].prose.content.contains("## Not A Section"));
+        assert!(!sections[1].prose.content.contains("Bucking"));
+        assert_eq!(sections[2].word_count, 6);
+    }
 
+    #[test]
+    fn outline_is_an_indented_markdown_list() {
+        let doc = MarkdownDoc::try_from("## Setup\n\n### Install {#install}\n\n## Usage\n").unwrap();
+
+        assert_eq!(doc.outline_markdown(false), "- [Setup](#setup)\n  - [Install](#install)\n- [Usage](#usage)\n");
+        assert!(doc.outline_markdown(true).starts_with("- [Setup](#setup) (line 1)\n"));
+    }
 }
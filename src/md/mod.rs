@@ -2,3 +2,21 @@ pub mod frontmatter;
 pub mod prose;
 pub mod markdown;
 pub mod reporting;
+pub mod heading;
+pub mod diff;
+pub mod emoji;
+pub mod links;
+pub mod callout;
+pub mod footnote;
+pub mod quality;
+pub mod typography;
+pub mod html;
+pub mod definition;
+pub mod index;
+pub mod doc_comments;
+pub mod readability;
+pub mod paragraphs;
+pub mod lint;
+pub mod include;
+pub mod stale;
+pub mod cascade;
@@ -0,0 +1,309 @@
+use lazy_static::lazy_static;
+use pulldown_cmark::{Event, Parser, Tag};
+use regex::Regex;
+
+use super::{markdown::MarkdownDoc, prose::{FenceTracker, Prose, fence_of}};
+
+lazy_static! {
+    /// the end of a sentence: terminal punctuation followed by whitespace
+    static ref SENTENCE_END: Regex = Regex::new(r"[.!?]+(?:\s+|$)").unwrap();
+    /// the marker at the start of an ordered or unordered list item
+    static ref LIST_MARKER: Regex = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+").unwrap();
+    /// an ATX heading: one to six `#` followed by a space (or nothing)
+    static ref HEADING: Regex = Regex::new(r"^\s*#{1,6}(?:[ \t]|$)").unwrap();
+}
+
+/// abbreviations -- lowercased and without their final `.` -- which do not
+/// end a sentence
+const ABBREVIATIONS: [&str; 14] = [
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "e.g", "i.e", "cf", "al", "fig",
+];
+
+/// Whether the terminal punctuation which ends at `end` is a sentence
+/// boundary; a `.` after an abbreviation or an initial (e.g., `Dr.` or
+/// `J.`) or which is followed by a lowercase word is not.
+fn is_sentence_boundary(text: &str, start: usize, end: usize) -> bool {
+    if text[start..end].trim_end() != "." {
+        return true;
+    }
+    let word = text[..start].rsplit(char::is_whitespace).next().unwrap_or_default();
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+    let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+    let continues_lowercase = text[end..].chars().next().is_some_and(char::is_lowercase);
+
+    !(is_initial || continues_lowercase || ABBREVIATIONS.contains(&word.as_str()))
+}
+
+/// whether a paragraph is a fenced code block or a heading, neither of
+/// which holds sentences
+fn is_code_or_heading(paragraph: &str) -> bool {
+    let first_line = paragraph.lines().next().unwrap_or_default();
+    fence_of(first_line).is_some() || HEADING.is_match(first_line)
+}
+
+/// Splits text on sentence boundaries (see `is_sentence_boundary`) so that
+/// abbreviations, initials and decimals (e.g., `3.14`) don't end a
+/// sentence; the terminal punctuation stays with its sentence and runs of
+/// whitespace (including line breaks) are collapsed to a single space.
+fn sentences_in(text: &str) -> Vec<String> {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let mut sentences: Vec<String> = vec![];
+    let mut start = 0;
+    for end in SENTENCE_END.find_iter(&text) {
+        if !is_sentence_boundary(&text, end.start(), end.end()) {
+            continue;
+        }
+        sentences.push(text[start..end.end()].trim().to_string());
+        start = end.end();
+    }
+    sentences.push(text[start..].trim().to_string());
+
+    sentences.into_iter().filter(|s| s.chars().any(|c| c.is_alphanumeric())).collect()
+}
+
+/// the text of some markdown with all of its markup removed; line breaks
+/// become spaces
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+impl Prose {
+    /// The text of the prose with its markdown syntax removed; each block
+    /// (paragraph, heading, list item, code block, etc.) ends with a line
+    /// break.
+    pub fn strip_markdown_syntax(&self) -> String {
+        let mut text = String::new();
+        for event in Parser::new(&self.content) {
+            match event {
+                Event::Text(t) | Event::Code(t) => text.push_str(&t),
+                Event::SoftBreak | Event::HardBreak => text.push('\n'),
+                Event::End(
+                    Tag::Paragraph | Tag::Heading(..) | Tag::Item | Tag::CodeBlock(_) | Tag::TableRow | Tag::TableHead
+                ) if !text.is_empty() && !text.ends_with('\n') => text.push('\n'),
+                _ => {}
+            }
+        }
+
+        text
+    }
+
+    /// Splits the prose into paragraphs on blank lines; a fenced code block
+    /// -- blank lines and all -- is always a single paragraph and a heading
+    /// is always a paragraph of its own.
+    pub fn split_paragraphs(&self) -> Vec<String> {
+        let mut paragraphs: Vec<String> = vec![];
+        let mut current: Vec<&str> = vec![];
+        let mut fences = FenceTracker::default();
+        let flush = |current: &mut Vec<&str>, paragraphs: &mut Vec<String>| {
+            if !current.is_empty() {
+                paragraphs.push(current.join("\n"));
+                current.clear();
+            }
+        };
+
+        for line in self.content.lines() {
+            let was_in_fence = fences.in_fence();
+            if fences.update(line) {
+                if !was_in_fence {
+                    flush(&mut current, &mut paragraphs);
+                }
+                current.push(line);
+                if was_in_fence {
+                    flush(&mut current, &mut paragraphs);
+                }
+            } else if fences.in_fence() {
+                current.push(line);
+            } else if line.trim().is_empty() {
+                flush(&mut current, &mut paragraphs);
+            } else if HEADING.is_match(line) {
+                flush(&mut current, &mut paragraphs);
+                paragraphs.push(line.trim().to_string());
+            } else {
+                current.push(line);
+            }
+        }
+        flush(&mut current, &mut paragraphs);
+
+        paragraphs
+    }
+
+    /// the text which sentences are found in: each paragraph which is not a
+    /// heading or code block, with each list item (less its marker) apart
+    fn sentence_sources(&self) -> Vec<String> {
+        self.split_paragraphs()
+            .iter()
+            .filter(|p| !is_code_or_heading(p))
+            .flat_map(|paragraph| {
+                let mut items: Vec<String> = vec![];
+                for line in paragraph.lines() {
+                    match LIST_MARKER.find(line) {
+                        Some(marker) => items.push(line[marker.end()..].to_string()),
+                        None => match items.last_mut() {
+                            Some(item) => {
+                                item.push('\n');
+                                item.push_str(line);
+                            }
+                            None => items.push(line.to_string()),
+                        },
+                    }
+                }
+                items
+            })
+            .collect()
+    }
+
+    /// Splits the prose into sentences on terminal punctuation (`.`, `!` and
+    /// `?`); headings and fenced code blocks hold no sentences while each
+    /// list item -- with its marker removed -- is split on its own.
+    pub fn split_sentences(&self) -> Vec<String> {
+        self.sentence_sources().iter().flat_map(|text| sentences_in(text)).collect()
+    }
+
+    /// The sentences of the prose -- split like `split_sentences` -- in
+    /// their plain text form with all markdown removed.
+    pub fn sentences(&self) -> Vec<String> {
+        self.sentence_sources().iter().flat_map(|text| sentences_in(&plain_text(text))).collect()
+    }
+}
+
+/// the longest description backfilled from the first paragraph, in characters
+pub const MAX_DESCRIPTION_LENGTH: usize = 160;
+
+impl MarkdownDoc {
+    /// A plain text preview of the document: the frontmatter `excerpt` when
+    /// one is set, otherwise the first paragraph of the prose which is not
+    /// a heading or code block with its markdown removed.
+    pub fn first_paragraph(&self) -> Option<String> {
+        if let Some(excerpt) = self.fm.as_ref().and_then(|fm| fm.excerpt.as_ref()).filter(|e| !e.trim().is_empty()) {
+            return Some(excerpt.trim().to_string());
+        }
+
+        self.prose
+            .split_paragraphs()
+            .iter()
+            .filter(|p| !is_code_or_heading(p))
+            .map(|p| plain_text(p))
+            .find(|p| !p.is_empty())
+    }
+
+    /// The description of the document: the frontmatter `description` when
+    /// one is set, otherwise the first paragraph truncated -- with an
+    /// ellipsis -- to `MAX_DESCRIPTION_LENGTH` characters.
+    pub fn effective_description(&self) -> Option<String> {
+        if let Some(description) = self.fm.as_ref().and_then(|fm| fm.description.clone()) {
+            return Some(description);
+        }
+        let paragraph = self.first_paragraph()?;
+        if paragraph.chars().count() <= MAX_DESCRIPTION_LENGTH {
+            return Some(paragraph);
+        }
+        let truncated: String = paragraph.chars().take(MAX_DESCRIPTION_LENGTH - 1).collect();
+
+        Some(format!("{}…", truncated.trim_end()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "# Title\n\nFirst line\ncontinues here. Second sentence!\n\n\n- one item\n- two. Still two\n\n```rust\nlet a = 1;\n\nlet b = 2;\n```\n## Next\nIs this the end?";
+
+    #[test]
+    fn paragraphs_are_split_on_blank_lines() {
+        let paragraphs = Prose::from(CONTENT).split_paragraphs();
+
+        assert_eq!(
+            paragraphs,
+            vec![
+                "# Title",
+                "First line\ncontinues here. Second sentence!",
+                "- one item\n- two. Still two",
+                "```rust\nlet a = 1;\n\nlet b = 2;\n```",
+                "## Next",
+                "Is this the end?",
+            ]
+        );
+        assert!(Prose::from("\n\n").split_paragraphs().is_empty());
+        assert_eq!(
+            Prose::from("#hashtag starts this paragraph.\n####### Not a heading either.\n").split_sentences(),
+            vec!["#hashtag starts this paragraph.", "####### Not a heading either."]
+        );
+        assert_eq!(
+            Prose::from("````\n```\n\nstill code\n````\n").split_paragraphs(),
+            vec!["````\n```\n\nstill code\n````"]
+        );
+    }
+
+    #[test]
+    fn first_paragraph_is_a_plain_text_preview() {
+        let doc = MarkdownDoc::try_from("# Title\n\n```\ncode\n```\n\nSome **bold** and [a link](/x)\nwith `code`.\n\nLater.\n").unwrap();
+        let excerpt = MarkdownDoc::try_from("---\nexcerpt: Hand written\n---\n# Title\n\nFirst.\n").unwrap();
+
+        assert_eq!(doc.first_paragraph().as_deref(), Some("Some bold and a link with code."));
+        assert_eq!(excerpt.first_paragraph().as_deref(), Some("Hand written"));
+        assert_eq!(MarkdownDoc::try_from("# Only a heading\n").unwrap().first_paragraph(), None);
+    }
+
+    #[test]
+    fn sentences_are_plain_text_and_skip_abbreviations() {
+        let prose = Prose::from("# Title\n\nSee [Dr. Smith](/dr) at 3.14 p.m. today. Tools (e.g. an **axe**) help!\n\nJ. R. R. Tolkien wrote it. Next?\n");
+
+        assert_eq!(
+            prose.sentences(),
+            vec![
+                "See Dr. Smith at 3.14 p.m. today.",
+                "Tools (e.g. an axe) help!",
+                "J. R. R. Tolkien wrote it.",
+                "Next?",
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_syntax_is_stripped() {
+        let prose = Prose::from("# Title\n\nSome **bold** [link](/x).\n\n- one\n- `two`\n\n```\ncode\n```\n");
+
+        assert_eq!(prose.strip_markdown_syntax(), "Title\nSome bold link.\none\ntwo\ncode\n");
+    }
+
+    #[test]
+    fn description_is_backfilled_from_the_first_paragraph() {
+        let described = MarkdownDoc::try_from("---\ndescription: Set by hand\n---\nFirst.\n").unwrap();
+        let short = MarkdownDoc::try_from("# Title\n\nA short *intro*.\n").unwrap();
+        let long = MarkdownDoc::try_from(format!("{}\n", "word ".repeat(50)).as_str()).unwrap();
+        let backfilled = long.effective_description().unwrap();
+
+        assert_eq!(described.effective_description().as_deref(), Some("Set by hand"));
+        assert_eq!(short.effective_description().as_deref(), Some("A short intro."));
+        assert!(backfilled.ends_with("word…"));
+        assert!(backfilled.chars().count() <= MAX_DESCRIPTION_LENGTH);
+        assert_eq!(MarkdownDoc::try_from("# Only a heading\n").unwrap().effective_description(), None);
+    }
+
+    #[test]
+    fn sentences_skip_headings_and_code() {
+        let sentences = Prose::from(CONTENT).split_sentences();
+
+        assert_eq!(
+            sentences,
+            vec![
+                "First line continues here.",
+                "Second sentence!",
+                "one item",
+                "two.",
+                "Still two",
+                "Is this the end?",
+            ]
+        );
+    }
+}
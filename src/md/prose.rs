@@ -1,10 +1,77 @@
+use regex::Regex;
 use serde::{Serialize, Deserialize};
 
-use crate::hasher::hash;
+use crate::hasher::{UNHASHED, hash, hash_with_seed, is_unhashed, normalized_hash};
+
+use super::heading::{Heading, HeadingJump, extract_headings, heading_jumps};
+
+/// the number of words on a line; tokens made up solely of markup (e.g.,
+/// `#`, `-`, `>`) are not counted
+fn words_in(line: &str) -> usize {
+    line.split_whitespace()
+        .filter(|token| token.chars().any(|c| c.is_alphanumeric()))
+        .count()
+}
+
+/// The run of backticks or tildes which opens a fenced code block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Fence {
+    marker: char,
+    len: usize,
+}
+
+/// The fence the line starts with -- along with the rest of the line (the
+/// info string of an opening fence) -- if it starts with three or more
+/// backticks or tildes; a backtick fence's info string can't hold a backtick.
+pub(crate) fn fence_of(line: &str) -> Option<(Fence, &str)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == marker).count();
+    let rest = &trimmed[len..];
+    (len >= 3 && !(marker == '`' && rest.contains('`'))).then_some((Fence { marker, len }, rest))
+}
+
+/// **FenceTracker**
+///
+/// Follows the lines of some markdown to tell which are within a fenced
+/// code block. A block is only closed by a fence of the same character
+/// which is at least as long as the one which opened it and has no info
+/// string; any other fence-like line within the block is part of its code.
+#[derive(Debug, Default)]
+pub(crate) struct FenceTracker {
+    open: Option<Fence>,
+}
+
+impl FenceTracker {
+    /// Moves on to the next line; returns whether the line is a fence which
+    /// opens or closes a code block.
+    pub(crate) fn update(&mut self, line: &str) -> bool {
+        match (self.open, fence_of(line)) {
+            (None, Some((fence, _))) => {
+                self.open = Some(fence);
+                true
+            }
+            (Some(open), Some((fence, rest)))
+                if fence.marker == open.marker && fence.len >= open.len && rest.trim().is_empty() =>
+            {
+                self.open = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// whether the last line was within a code block (or opened one)
+    pub(crate) fn in_fence(&self) -> bool {
+        self.open.is_some()
+    }
+}
 
 #[derive(Debug,Serialize,Deserialize)]
 pub struct Prose {
     pub content: String,
+    /// the hash of the content; `UNHASHED` when it was not hashed
+    #[serde(default, skip_serializing_if = "is_unhashed")]
     pub hash: u64
 }
 
@@ -15,6 +82,181 @@ impl Prose {
             content: content.to_string(),
         }
     }
+
+    /// creates `Prose` without hashing its content; the hash is `UNHASHED`
+    pub fn unhashed(content: &str) -> Prose {
+        Prose {
+            hash: UNHASHED,
+            content: content.to_string(),
+        }
+    }
+
+    /// creates `Prose` whose hash is computed with the given seed so that
+    /// tests and snapshots can rely on a specific hash
+    pub fn new_with_seed(content: &str, seed: u64) -> Prose {
+        Prose {
+            hash: hash_with_seed(content, seed),
+            content: content.to_string(),
+        }
+    }
+
+    /// Applies `transform` to every line of the content which is not part
+    /// of a fenced code block (fence lines themselves are left untouched);
+    /// the result is only hashed when this prose was.
+    pub(crate) fn map_outside_fences<F: Fn(&str) -> String>(&self, transform: F) -> Prose {
+        let mut fences = FenceTracker::default();
+        let mut content = String::with_capacity(self.content.len());
+
+        for line in self.content.split_inclusive('\n') {
+            if fences.update(line) || fences.in_fence() {
+                content.push_str(line);
+            } else {
+                content.push_str(&transform(line));
+            }
+        }
+
+        match is_unhashed(&self.hash) {
+            true => Prose::unhashed(&content),
+            false => Prose::from(content),
+        }
+    }
+
+    /// Returns a new `Prose` where all occurrences of `from` have been
+    /// replaced with `to`; content inside fenced code blocks is left as is.
+    pub fn replace_all(&self, from: &str, to: &str) -> Prose {
+        self.map_outside_fences(|line| line.replace(from, to))
+    }
+
+    /// Returns a new `Prose` where all matches of the regular expression
+    /// `pattern` are replaced with `replacement` (which may refer to capture
+    /// groups with `$1`, `$name`, etc.). Matching is done line by line and
+    /// content inside fenced code blocks is left as is.
+    pub fn replace_regex(&self, pattern: &str, replacement: &str) -> Result<Prose, regex::Error> {
+        let re = Regex::new(pattern)?;
+        Ok(self.map_outside_fences(|line| re.replace_all(line, replacement).to_string()))
+    }
+
+    /// a hash of the content which ignores insignificant whitespace
+    pub fn normalized_hash(&self) -> u64 {
+        normalized_hash(&self.content)
+    }
+
+    /// Scans the prose line by line and returns each line -- along with its
+    /// 1-based line number -- which is _not_ part of a fenced code block.
+    pub fn lines_outside_fences(&self) -> Vec<(usize, &str)> {
+        let mut fences = FenceTracker::default();
+        let mut lines: Vec<(usize, &str)> = vec![];
+
+        for (idx, line) in self.content.lines().enumerate() {
+            if !fences.update(line) && !fences.in_fence() {
+                lines.push((idx + 1, line));
+            }
+        }
+
+        lines
+    }
+
+    /// The fraction -- from `0.0` to `1.0` -- of the prose's characters which
+    /// are part of a fenced code block (including the fences themselves).
+    pub fn code_ratio(&self) -> f64 {
+        let total = self.content.chars().count();
+        if total == 0 {
+            return 0.0;
+        }
+        let mut fences = FenceTracker::default();
+        let mut code = 0;
+
+        for line in self.content.split_inclusive('\n') {
+            if fences.update(line) || fences.in_fence() {
+                code += line.chars().count();
+            }
+        }
+
+        code as f64 / total as f64
+    }
+
+    /// The number of words in the prose; content within fenced code blocks
+    /// and tokens made up solely of markup (e.g., `#`, `-`, `>`) are not
+    /// counted.
+    pub fn word_count(&self) -> usize {
+        self.lines_outside_fences()
+            .iter()
+            .map(|(_, line)| words_in(line))
+            .sum()
+    }
+
+    /// The number of characters (Unicode scalar values) in the prose,
+    /// whitespace included.
+    pub fn char_count_with_whitespace(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    /// The number of characters (Unicode scalar values) in the prose which
+    /// are not whitespace.
+    pub fn char_count_no_whitespace(&self) -> usize {
+        self.content.chars().filter(|c| !c.is_whitespace()).count()
+    }
+
+    /// The number of words in each heading's section -- the prose between
+    /// the heading and the next heading of the same or a higher level -- as
+    /// `(heading_text, words)` pairs in document order; words in nested
+    /// sections count towards their parent while heading text does not.
+    pub fn section_words(&self) -> Vec<(String, usize)> {
+        let headings = self.headings();
+        let heading_lines: Vec<usize> = headings.iter().map(|h| h.line).collect();
+        let lines: Vec<(usize, usize)> = self.lines_outside_fences()
+            .into_iter()
+            .filter(|(line, _)| !heading_lines.contains(line))
+            .map(|(line, text)| (line, words_in(text)))
+            .collect();
+
+        headings
+            .iter()
+            .enumerate()
+            .map(|(idx, h)| {
+                let end = headings[idx + 1..]
+                    .iter()
+                    .find(|next| next.level <= h.level)
+                    .map(|next| next.line)
+                    .unwrap_or(usize::MAX);
+                let words = lines
+                    .iter()
+                    .filter(|(line, _)| *line > h.line && *line < end)
+                    .map(|(_, words)| words)
+                    .sum();
+                (h.text.clone(), words)
+            })
+            .collect()
+    }
+
+    /// all of the headings found in the prose, in document order
+    pub fn headings(&self) -> Vec<Heading> {
+        extract_headings(&self.content)
+    }
+
+    /// the headings which skip a level (e.g., an `h1` followed by an `h3`)
+    pub fn heading_jumps(&self) -> Vec<HeadingJump> {
+        heading_jumps(&self.headings())
+    }
+
+    /// the heading slugs which are used by more than one heading and would
+    /// therefore produce ambiguous anchors
+    pub fn slug_collisions(&self) -> Vec<String> {
+        let mut seen: Vec<String> = vec![];
+        let mut collisions: Vec<String> = vec![];
+
+        for h in self.headings() {
+            if seen.contains(&h.slug) {
+                if !collisions.contains(&h.slug) {
+                    collisions.push(h.slug);
+                }
+            } else {
+                seen.push(h.slug);
+            }
+        }
+
+        collisions
+    }
 }
 
 impl From<String> for Prose {
@@ -31,3 +273,84 @@ impl From<&str> for Prose {
         Prose::from(content.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_ids_resolve_slug_collisions() {
+        let colliding = Prose::from("## Setup\n\n## Setup\n");
+        let resolved = Prose::from("## Setup\n\n## Setup {#setup-again}\n");
+
+        assert_eq!(colliding.slug_collisions(), vec!["setup".to_string()]);
+        assert!(resolved.slug_collisions().is_empty());
+    }
+
+    const WITH_CODE: &str = "Use foo here.\n\n```rust\nlet foo = 1;\n```\n\nAnd foo again.\n";
+
+    #[test]
+    fn replace_all_skips_fenced_code() {
+        let original = Prose::from(WITH_CODE);
+        let replaced = original.replace_all("foo", "bar");
+
+        assert_eq!(replaced.content, "Use bar here.\n\n```rust\nlet foo = 1;\n```\n\nAnd bar again.\n");
+        assert_ne!(replaced.hash, original.hash);
+    }
+
+    #[test]
+    fn replace_regex_uses_captures_and_skips_fenced_code() {
+        let replaced = Prose::from(WITH_CODE).replace_regex(r"(f)oo", "${1}izz").unwrap();
+
+        assert_eq!(replaced.content, "Use fizz here.\n\n```rust\nlet foo = 1;\n```\n\nAnd fizz again.\n");
+        assert!(Prose::from(WITH_CODE).replace_regex("(", "x").is_err());
+    }
+
+    #[test]
+    fn word_count_ignores_markup_and_code() {
+        let prose = Prose::from("# Hello World\n\n- one two\n\n```\nlet x = 1;\n```\n");
+        assert_eq!(prose.word_count(), 4);
+    }
+
+    #[test]
+    fn characters_are_counted_with_and_without_whitespace() {
+        let prose = Prose::from("# Café\n\nA  b\tc\n");
+
+        assert_eq!(prose.char_count_with_whitespace(), 15);
+        assert_eq!(prose.char_count_no_whitespace(), 8);
+        assert_eq!(Prose::from(prose.strip_markdown_syntax()).char_count_no_whitespace(), 7);
+    }
+
+    #[test]
+    fn section_words_include_nested_sections() {
+        let prose = Prose::from("# Guide\n\nOne two.\n\n## Setup\n\nThree four five.\n\n```\nnot counted\n```\n\n### Details\n\nSix.\n\n## Empty\n");
+
+        assert_eq!(
+            prose.section_words(),
+            vec![
+                ("Guide".to_string(), 6),
+                ("Setup".to_string(), 4),
+                ("Details".to_string(), 1),
+                ("Empty".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_ratio_measures_fenced_code() {
+        assert_eq!(Prose::from("```\nab\n```\n0123456789\n").code_ratio(), 0.5);
+        assert_eq!(Prose::from("no code at all\n").code_ratio(), 0.0);
+        assert_eq!(Prose::from("").code_ratio(), 0.0);
+    }
+
+    #[test]
+    fn fences_close_only_on_a_matching_fence() {
+        let prose = Prose::from("````md\n```rust\ncode\n```\n~~~\n````\nafter\n~~~\n```\ninside\n~~~\nend\n");
+        let lines: Vec<&str> = prose.lines_outside_fences().iter().map(|(_, l)| *l).collect();
+
+        assert_eq!(lines, vec!["after", "end"]);
+        assert_eq!(fence_of("``` rust"), Some((Fence { marker: '`', len: 3 }, " rust")));
+        assert_eq!(fence_of("``"), None);
+        assert_eq!(fence_of("``` `inline` code"), None);
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use super::{links::is_relative_link, markdown::MarkdownDoc};
+
+/// the range of word counts considered reasonable for a single document
+pub const REASONABLE_WORD_COUNT: (usize, usize) = (100, 5000);
+
+/// The relative weight each check contributes to a document's quality
+/// score; a weight of zero disables the check.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct QualityWeights {
+    pub has_h1: u32,
+    pub has_description: u32,
+    pub reasonable_word_count: u32,
+    pub no_broken_links: u32,
+    pub image_alt_text: u32,
+}
+
+impl Default for QualityWeights {
+    fn default() -> Self {
+        QualityWeights {
+            has_h1: 20,
+            has_description: 20,
+            reasonable_word_count: 20,
+            no_broken_links: 20,
+            image_alt_text: 20,
+        }
+    }
+}
+
+/// The outcome of a single quality check
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QualityCheck {
+    pub name: String,
+    pub passed: bool,
+    pub weight: u32,
+    /// why the check failed
+    pub detail: Option<String>,
+}
+
+impl QualityCheck {
+    fn new(name: &str, weight: u32, failure: Option<String>) -> Self {
+        QualityCheck {
+            name: name.to_string(),
+            passed: failure.is_none(),
+            weight,
+            detail: failure,
+        }
+    }
+}
+
+/// The quality score -- from 0 to 100 -- of a document along with the
+/// breakdown of the checks which produced it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QualityReport {
+    pub file: Option<String>,
+    pub score: u32,
+    pub checks: Vec<QualityCheck>,
+}
+
+/// The quality of a set of documents taken as a whole
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QualitySummary {
+    pub documents: usize,
+    /// the mean score of all documents
+    pub average_score: f64,
+    pub lowest_score: Option<u32>,
+    /// the number of documents which passed every check
+    pub perfect: usize,
+}
+
+impl MarkdownDoc {
+    /// Links which point at a missing heading of this document or -- when
+    /// the document was loaded from a file -- at a missing local file.
+    pub fn broken_links(&self) -> Vec<String> {
+        let slugs: Vec<String> = self.prose.headings().into_iter().map(|h| h.slug).collect();
+        let dir = self.file
+            .as_ref()
+            .and_then(|f| Path::new(f.filename()).parent().map(|p| p.to_path_buf()));
+
+        self.prose
+            .links()
+            .into_iter()
+            .map(|l| l.url)
+            .filter(|url| {
+                if let Some(fragment) = url.strip_prefix('#') {
+                    !slugs.iter().any(|s| s == fragment)
+                } else if is_relative_link(url) {
+                    let path = &url[..url.find(['#', '?']).unwrap_or(url.len())];
+                    dir.as_ref().is_some_and(|d| !path.is_empty() && !d.join(path).exists())
+                } else {
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// Scores the document against a set of weighted quality checks.
+    pub fn quality(&self, weights: &QualityWeights) -> QualityReport {
+        let (min, max) = REASONABLE_WORD_COUNT;
+        let has_description = self.fm
+            .as_ref()
+            .and_then(|fm| fm.description.as_ref())
+            .is_some_and(|d| !d.trim().is_empty());
+        let missing_alt = self.prose
+            .links()
+            .into_iter()
+            .filter(|l| l.is_image && l.text.trim().is_empty())
+            .count();
+        let broken = self.broken_links();
+
+        let checks = vec![
+            QualityCheck::new(
+                "has_h1",
+                weights.has_h1,
+                (!self.prose.headings().iter().any(|h| h.level == 1)).then(|| "no h1 heading".to_string()),
+            ),
+            QualityCheck::new(
+                "has_description",
+                weights.has_description,
+                (!has_description).then(|| "no description in the frontmatter".to_string()),
+            ),
+            QualityCheck::new(
+                "reasonable_word_count",
+                weights.reasonable_word_count,
+                (!(min..=max).contains(&self.word_count))
+                    .then(|| format!("{} words is outside of {}-{}", self.word_count, min, max)),
+            ),
+            QualityCheck::new(
+                "no_broken_links",
+                weights.no_broken_links,
+                (!broken.is_empty()).then(|| format!("broken links: {}", broken.join(", "))),
+            ),
+            QualityCheck::new(
+                "image_alt_text",
+                weights.image_alt_text,
+                (missing_alt > 0).then(|| format!("{} image(s) without alt text", missing_alt)),
+            ),
+        ];
+
+        let total: u32 = checks.iter().map(|c| c.weight).sum();
+        let passed: u32 = checks.iter().filter(|c| c.passed).map(|c| c.weight).sum();
+        let score = if total == 0 { 100 } else { (passed as f64 * 100.0 / total as f64).round() as u32 };
+
+        QualityReport {
+            file: self.file.as_ref().map(|f| f.filename().to_string()),
+            score,
+            checks,
+        }
+    }
+}
+
+/// aggregates the quality reports of many documents
+pub fn quality_summary(reports: &[QualityReport]) -> QualitySummary {
+    let total: u32 = reports.iter().map(|r| r.score).sum();
+
+    QualitySummary {
+        documents: reports.len(),
+        average_score: if reports.is_empty() { 0.0 } else { total as f64 / reports.len() as f64 },
+        lowest_score: reports.iter().map(|r| r.score).min(),
+        perfect: reports.iter().filter(|r| r.checks.iter().all(|c| c.passed)).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality_score_reflects_weighted_checks() {
+        let doc = MarkdownDoc::try_from("---\ndescription: about\n---\n# Title\n\n![](a.png) [gone](#missing)\n").unwrap();
+        let report = doc.quality(&QualityWeights::default());
+        let failed: Vec<&str> = report.checks.iter().filter(|c| !c.passed).map(|c| c.name.as_str()).collect();
+
+        assert_eq!(failed, vec!["reasonable_word_count", "no_broken_links", "image_alt_text"]);
+        assert_eq!(report.score, 40);
+
+        let weights = QualityWeights {
+            has_h1: 1,
+            has_description: 1,
+            reasonable_word_count: 0,
+            no_broken_links: 0,
+            image_alt_text: 0,
+        };
+        assert_eq!(doc.quality(&weights).score, 100);
+    }
+
+    #[test]
+    fn summary_aggregates_scores() {
+        let good = MarkdownDoc::try_from("# A\n\n[a](#a)\n").unwrap().quality(&QualityWeights::default());
+        let bad = MarkdownDoc::try_from("no heading\n").unwrap().quality(&QualityWeights::default());
+        let summary = quality_summary(&[good.clone(), bad.clone()]);
+
+        assert_eq!(summary.documents, 2);
+        assert_eq!(summary.lowest_score, Some(bad.score));
+        assert_eq!(summary.average_score, (good.score + bad.score) as f64 / 2.0);
+    }
+}
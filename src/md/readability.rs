@@ -0,0 +1,183 @@
+use serde::{Serialize, Deserialize};
+
+use super::{markdown::MarkdownDoc, prose::Prose};
+
+/// the fewest syllables a word has for the Gunning Fog Index to treat it as
+/// complex
+const FOG_COMPLEX_SYLLABLES: usize = 3;
+
+/// the alphabetic words of some text, lowercased; tokens without any letters
+/// (e.g., markup or numbers) are not words
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|token| token.chars().filter(|c| c.is_alphabetic()).collect::<String>().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Estimates the syllables in a word by counting its clusters of vowels; a
+/// silent trailing `e` is not counted and every word has at least one.
+fn syllables_in(word: &str) -> usize {
+    let mut clusters = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = "aeiouy".contains(c);
+        if vowel && !previous_vowel {
+            clusters += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if clusters > 1 && word.ends_with('e') && !word.ends_with("le") && !word.ends_with("ee") {
+        clusters -= 1;
+    }
+
+    clusters.max(1)
+}
+
+impl Prose {
+    /// The average number of words in each sentence of the prose; `0.0`
+    /// when there are no sentences.
+    pub fn avg_sentence_length_words(&self) -> f64 {
+        let sentences = self.sentences();
+        if sentences.is_empty() {
+            return 0.0;
+        }
+        let words: usize = sentences.iter().map(|s| words(s).len()).sum();
+
+        words as f64 / sentences.len() as f64
+    }
+
+    /// An estimate of the number of syllables in the sentences of the prose
+    /// based on the clusters of vowels in each word.
+    pub fn syllable_estimate(&self) -> usize {
+        self.sentences().iter().flat_map(|s| words(s)).map(|w| syllables_in(&w)).sum()
+    }
+
+    /// The distinct words -- lowercased and in order of first use -- which
+    /// have more than `syllable_threshold` syllables; words in code (fenced
+    /// blocks or inline spans) are not considered.
+    pub fn complex_words(&self, syllable_threshold: usize) -> Vec<String> {
+        let mut complex: Vec<String> = vec![];
+        for sentence in self.split_sentences() {
+            let without_code: Vec<&str> = sentence.split('`').step_by(2).collect();
+            for word in words(&without_code.join(" ")) {
+                if syllables_in(&word) > syllable_threshold && !complex.contains(&word) {
+                    complex.push(word);
+                }
+            }
+        }
+
+        complex
+    }
+
+    /// The Flesch-Kincaid reading ease score of the prose; higher scores are
+    /// easier to read (90-100 is very easy while below 30 is very difficult).
+    /// Prose without any sentences scores `0.0`.
+    pub fn flesch_kincaid_score(&self) -> f64 {
+        let words: usize = self.sentences().iter().map(|s| words(s).len()).sum();
+        if words == 0 {
+            return 0.0;
+        }
+        let syllables_per_word = self.syllable_estimate() as f64 / words as f64;
+
+        206.835 - 1.015 * self.avg_sentence_length_words() - 84.6 * syllables_per_word
+    }
+}
+
+/// The level of education a reader needs to understand a text on a first
+/// reading, as suggested by its Gunning Fog Index
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GradeLevel {
+    /// grades 1 through 6 (an index below 7)
+    Elementary,
+    /// grades 7 and 8 (an index below 9)
+    MiddleSchool,
+    /// grades 9 through 12 (an index below 13)
+    HighSchool,
+    /// an undergraduate degree (an index below 17)
+    College,
+    /// beyond an undergraduate degree
+    Graduate,
+}
+
+impl GradeLevel {
+    /// the grade level which a Gunning Fog Index corresponds to
+    pub fn from_fog_index(score: f64) -> GradeLevel {
+        match score {
+            s if s < 7.0 => GradeLevel::Elementary,
+            s if s < 9.0 => GradeLevel::MiddleSchool,
+            s if s < 13.0 => GradeLevel::HighSchool,
+            s if s < 17.0 => GradeLevel::College,
+            _ => GradeLevel::Graduate,
+        }
+    }
+}
+
+impl MarkdownDoc {
+    /// The Gunning Fog Index of the prose -- roughly the years of formal
+    /// education needed to understand it on a first reading -- calculated
+    /// as `0.4 * ((words / sentences) + 100 * (complex_words / words))`
+    /// where a complex word has three or more syllables. Prose without any
+    /// words scores `0.0`.
+    pub fn reading_grade(&self) -> f64 {
+        let sentences = self.prose.sentences();
+        let words: Vec<String> = sentences.iter().flat_map(|s| words(s)).collect();
+        if words.is_empty() {
+            return 0.0;
+        }
+        let complex = words.iter().filter(|w| syllables_in(w) >= FOG_COMPLEX_SYLLABLES).count();
+
+        0.4 * (words.len() as f64 / sentences.len() as f64 + 100.0 * complex as f64 / words.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syllables_are_estimated_from_vowel_clusters() {
+        assert_eq!(syllables_in("cat"), 1);
+        assert_eq!(syllables_in("make"), 1);
+        assert_eq!(syllables_in("table"), 2);
+        assert_eq!(syllables_in("readability"), 5);
+        assert_eq!(syllables_in("rhythm"), 1);
+    }
+
+    #[test]
+    fn complex_words_exceed_the_syllable_threshold() {
+        let prose = Prose::from("The cat sat. Institutional rules apply; institutional `configuration` too.\n\n```\nunnecessarily = 1\n```\n");
+
+        assert_eq!(prose.complex_words(3), vec!["institutional"]);
+        assert!(Prose::from("The cat sat on the mat.").complex_words(2).is_empty());
+    }
+
+    #[test]
+    fn reading_grade_is_the_gunning_fog_index() {
+        let simple = MarkdownDoc::try_from("# Title\n\nThe cat sat. The dog ran.\n").unwrap();
+        let complex = MarkdownDoc::try_from("Institutional considerations necessitate evaluation. It is very hard.\n").unwrap();
+
+        assert_eq!(simple.reading_grade(), 0.4 * 3.0);
+        // 8 words over 2 sentences of which 4 are complex
+        assert!((complex.reading_grade() - 0.4 * (4.0 + 50.0)).abs() < 1e-9);
+        assert_eq!(MarkdownDoc::try_from("```\ncode\n```\n").unwrap().reading_grade(), 0.0);
+        assert_eq!(GradeLevel::from_fog_index(simple.reading_grade()), GradeLevel::Elementary);
+        assert_eq!(GradeLevel::from_fog_index(8.0), GradeLevel::MiddleSchool);
+        assert_eq!(GradeLevel::from_fog_index(12.9), GradeLevel::HighSchool);
+        assert_eq!(GradeLevel::from_fog_index(16.0), GradeLevel::College);
+        assert_eq!(GradeLevel::from_fog_index(complex.reading_grade()), GradeLevel::Graduate);
+    }
+
+    #[test]
+    fn simple_prose_is_easier_to_read() {
+        let simple = Prose::from("# Title\n\nThe cat sat. The dog ran! Is it fun?\n\n```\nnot.a.sentence = 1;\n```\n");
+        let complex = Prose::from("Institutional considerations necessitate comprehensive organizational evaluation procedures.\n");
+
+        assert_eq!(simple.avg_sentence_length_words(), 3.0);
+        assert_eq!(simple.syllable_estimate(), 9);
+        assert!(simple.flesch_kincaid_score() > 100.0);
+        assert!(complex.flesch_kincaid_score() < 0.0);
+        assert_eq!(Prose::from("```\ncode\n```\n").flesch_kincaid_score(), 0.0);
+    }
+}
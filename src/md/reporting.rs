@@ -1,22 +1,662 @@
-use color_eyre::eyre::Result;
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
 use serde_json::{Value, json};
 
 use crate::{
-    Target, 
-    md::markdown::MarkdownDoc, 
-    file::{FileMeta, FileWithMeta}
+    Config,
+    Fingerprint,
+    Target,
+    Warning,
+    md::{
+        markdown::MarkdownDoc,
+        prose::Prose,
+        links::canonical_url,
+        frontmatter::TYPED_BOOLEAN_FIELDS,
+        doc_comments::{SourceLanguage, extract_doc_comments},
+        include::resolve_includes,
+        cascade::cascade_index_frontmatter,
+        readability::GradeLevel,
+    },
+    file::{FileMeta, FileWithMeta, UTF8_BOM, files_with_extension_to_depth},
+    hasher::{hash, normalized_hash},
+    errors::{Result, WithTarget, io::IoError},
 };
 
-pub fn md_file(target: &Target) -> Result<Value> {
+/// Loads the content of a markdown file; with `resolve_includes` set the
+/// files it includes are inlined (and the hash reflects the inlined content)
+/// while with `strip_bom` set a UTF-8 byte order mark is removed before the
+/// content is hashed; with `no_hash` set the content is not hashed at all.
+pub fn load_file(meta: FileMeta, config: &Config) -> Result<FileWithMeta> {
+    let mut file = read_file(meta, config)?;
+    if config.resolve_includes {
+        file.content = resolve_includes(&file.content, file.meta.filename(), &config.fm_fences)?;
+        file.rehash();
+    }
+
+    Ok(file)
+}
+
+/// reads a file's content as `load_file` does -- honouring `lossy`,
+/// `strip_bom` and `no_hash` -- but without resolving includes
+fn read_file(meta: FileMeta, config: &Config) -> Result<FileWithMeta> {
+    let mut file = meta.load_content_with(config.lossy, !config.no_hash)?;
+    if config.strip_bom {
+        file.strip_bom();
+    }
+
+    Ok(file)
+}
+
+/// loads the markdown file at `path` into a `MarkdownDoc`
+pub fn load_md(path: &str, config: &Config) -> Result<MarkdownDoc> {
+    let file = load_file(FileMeta::try_from(path)?, config)?;
+    let md = MarkdownDoc::from_file_with(file, config)?;
+
+    Ok(md)
+}
+
+/// reports on a markdown document; documents which the configuration
+/// says to skip are reported as _skipped_ rather than in full
+pub fn md_report(md: &MarkdownDoc, config: &Config, warnings: &mut Vec<Warning>) -> Value {
+    match md.skip_reason(config) {
+        Some(reason) => {
+            let file = md.file.as_ref().map(|f| f.filename());
+            eprintln!("- '{}' was skipped ({})", file.unwrap_or_default(), reason);
+            if let Some(fm) = md.fm.as_ref().filter(|_| reason == "expired") {
+                warnings.push(Warning::new(
+                    file.unwrap_or_default(),
+                    &format!(
+                        "'{}' expired on {}",
+                        fm.title.as_deref().unwrap_or("untitled"),
+                        fm.expires.as_deref().unwrap_or_default()
+                    )
+                ));
+            }
+            json!({ "file": file, "skipped": true, "reason": reason })
+        }
+        None => {
+            let mut report = json!(md);
+            let typeset = config.typography.map(|mode| md.prose.apply_typography(mode));
+            if let Some(typeset) = &typeset {
+                report["prose"] = match config.no_hash {
+                    true => json!(Prose::unhashed(typeset)),
+                    false => json!(Prose::from(typeset.as_str())),
+                };
+            }
+            if config.hash_normalize_whitespace && !config.no_hash {
+                // the hash is of the prose as it is output (i.e., typeset)
+                let content = typeset.as_deref().unwrap_or(&md.prose.content);
+                report["prose"]["hash"] = json!(normalized_hash(content));
+            }
+            if config.emoji {
+                let emoji = md.prose.emoji();
+                report["emoji_count"] = json!(emoji.len());
+                if let Some(threshold) = config.emoji_threshold {
+                    let exceeded = emoji.len() > threshold;
+                    if exceeded {
+                        warnings.push(Warning::new(
+                            md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                            &format!("uses {} emoji which exceeds the threshold of {}", emoji.len(), threshold)
+                        ));
+                    }
+                    report["emoji_threshold_exceeded"] = json!(exceeded);
+                }
+                report["emoji"] = json!(emoji);
+            }
+            if let (Some(base), Some(file)) = (&config.url_base, &md.file) {
+                let root = config.content_root.as_deref().unwrap_or(".");
+                report["canonical_url"] = json!(canonical_url(file.filename(), root, base));
+            }
+            report["stable_id"] = json!(md.stable_id());
+            report["sentence_count"] = json!(md.prose.sentences().len());
+            report["avg_words_per_sentence"] = json!(md.prose.avg_sentence_length_words());
+            let grade = md.reading_grade();
+            report["reading_grade"] = json!(grade);
+            report["grade_level"] = json!(GradeLevel::from_fog_index(grade));
+            report["preview"] = json!(md.first_paragraph());
+            let description = md.effective_description();
+            let has_fm_description = md.fm.as_ref().is_some_and(|fm| fm.description.is_some());
+            if config.backfill_fm && description.is_some() && !has_fm_description {
+                warnings.push(Warning::new(
+                    md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                    "has no frontmatter description so it was backfilled from the first paragraph"
+                ));
+            }
+            report["effective_description"] = json!(description);
+            let definitions = md.prose.extract_definitions();
+            if !definitions.is_empty() {
+                report["definitions"] = json!(definitions);
+            }
+            if config.section_words {
+                report["section_words"] = json!(md.prose.section_words());
+            }
+            if config.char_count {
+                let stripped;
+                let prose = match config.strip_syntax {
+                    true => {
+                        stripped = Prose::unhashed(&md.prose.strip_markdown_syntax());
+                        &stripped
+                    }
+                    false => &md.prose,
+                };
+                report["char_count"] = json!({
+                    "with_whitespace": prose.char_count_with_whitespace(),
+                    "no_whitespace": prose.char_count_no_whitespace(),
+                });
+            }
+            if config.fail_on_empty_prose {
+                let words = md.prose.word_count();
+                if words < config.min_words {
+                    warnings.push(Warning::new(
+                        md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                        &format!("has {} words of prose which is fewer than the minimum of {}", words, config.min_words)
+                    ));
+                    report["empty_prose"] = json!({ "words": words, "min_words": config.min_words });
+                }
+            }
+            if config.readability {
+                report["readability"] = json!({ "flesch_kincaid": md.prose.flesch_kincaid_score() });
+            }
+            let mut links = md.prose.links();
+            if config.obsidian {
+                links.extend(md.prose.wiki_links());
+            }
+            report["links"] = json!(links);
+            let html_blocks = md.prose.html_blocks();
+            if config.fail_on_html {
+                for block in &html_blocks {
+                    warnings.push(Warning::raw_html(
+                        md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                        &block.tag,
+                        block.line
+                    ));
+                }
+            }
+            report["html_blocks"] = json!(html_blocks);
+            let fm_other = md.fm.as_ref().map(|fm| &fm.other);
+            for (key, value) in TYPED_BOOLEAN_FIELDS.iter().filter_map(|k| fm_other?.get(*k).map(|v| (k, v))) {
+                warnings.push(Warning::new(
+                    md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                    &format!("has the non-boolean frontmatter value {} for '{}'", value, key)
+                ));
+            }
+            if config.lint {
+                for link in md.prose.poor_link_text_with(&config.poor_link_text) {
+                    warnings.push(Warning::new(
+                        md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                        &format!("has a link on line {} with the non-descriptive text '{}'", link.line, link.text)
+                    ));
+                }
+                let jumps = md.prose.heading_jumps();
+                for jump in &jumps {
+                    warnings.push(Warning::new(
+                        md.file.as_ref().map(|f| f.filename()).unwrap_or_default(),
+                        &format!("has a heading on line {} which jumps from an h{} to an h{}", jump.line, jump.from, jump.to)
+                    ));
+                }
+                report["heading_jumps"] = json!(jumps);
+            }
+
+            report
+        }
+    }
+}
+
+pub fn md_file(target: &Target, config: &Config, warnings: &mut Vec<Warning>) -> Result<Value> {
     eprintln!("- '{}' is being processed as a local Markdown file", &target.user_input);
-    let file = FileMeta::try_from(&target.user_input)?;
-    let file = FileWithMeta::try_from(file)?;
-    let md = MarkdownDoc::try_from(file)?;
+    let md = load_md(&target.user_input, config).with_target(&target.user_input)?;
+
+    Ok(md_report(&md, config, warnings))
+}
+
+/// reports on the markdown held in the doc comments of a source file as
+/// though it were a markdown document
+pub fn source_file(target: &Target, config: &Config, warnings: &mut Vec<Warning>) -> Result<Value> {
+    eprintln!("- '{}' is being processed as a source file", &target.user_input);
+    let language = SourceLanguage::from_path(&target.user_input)
+        .ok_or_else(|| IoError::UnsupportedSourceLanguage(target.user_input.clone()))?;
+    let mut file = FileMeta::try_from(target.user_input.as_str())
+        .map_err(|e| e.into())
+        .and_then(|meta| read_file(meta, config))
+        .with_target(&target.user_input)?;
+    file.content = extract_doc_comments(&file.content, language);
+    file.rehash();
+    let md = MarkdownDoc::from_file_with(file, config).with_target(&target.user_input)?;
+
+    let mut report = md_report(&md, config, warnings);
+    report["source_language"] = json!(language);
+
+    Ok(report)
+}
+
+/// loads all markdown files found -- recursively -- under the directory
+/// `dir` into `MarkdownDoc`'s; a file which fails to load is recorded as a
+/// warning rather than failing the whole directory
+pub fn md_docs_in_dir(dir: &str, config: &Config, warnings: &mut Vec<Warning>) -> Result<Vec<MarkdownDoc>> {
+    let mut docs: Vec<MarkdownDoc> = vec![];
+    for f in files_with_extension_to_depth(dir, "md", config.max_depth)? {
+        let filename = f.filename().to_string();
+        match load_file(f, config).and_then(|file| Ok(MarkdownDoc::from_file_with(file, config)?)) {
+            Ok(doc) => docs.push(doc),
+            Err(e) => warnings.push(Warning::new(&filename, &format!("could not be processed: {}", e))),
+        }
+    }
+    if config.cascade_fm {
+        cascade_index_frontmatter(&mut docs, dir)?;
+    }
+
+    Ok(docs)
+}
+
+pub fn md_dir(target: &Target, config: &Config, warnings: &mut Vec<Warning>) -> Result<Value> {
+    eprintln!("- '{}' is being processed as a directory of Markdown files", &target.user_input);
+    let docs: Vec<Value> = md_docs_in_dir(&target.user_input, config, warnings)
+        .with_target(&target.user_input)?
+        .iter()
+        .map(|md| md_report(md, config, warnings))
+        .collect();
+
+    Ok(json!(docs))
+}
+
+/// the file paths of all the index pages found in the directory `dir`
+pub fn index_pages_in_dir(dir: &str, config: &Config, warnings: &mut Vec<Warning>) -> Result<Vec<String>> {
+    Ok(
+        md_docs_in_dir(dir, config, warnings)?
+            .iter()
+            .filter(|d| d.is_index_page())
+            .filter_map(|d| d.file.as_ref().map(|f| f.filename().to_string()))
+            .collect()
+    )
+}
+
+/// all of the markdown documents a target refers to; a markdown file
+/// target provides a single document while a directory provides all the
+/// markdown documents found within it
+pub fn md_docs_for(target: &Target, config: &Config, warnings: &mut Vec<Warning>) -> Result<Vec<MarkdownDoc>> {
+    match target.kind {
+        Fingerprint::MarkdownFile => Ok(vec![load_md(&target.user_input, config)?]),
+        Fingerprint::Directory => md_docs_in_dir(&target.user_input, config, warnings),
+        _ => Ok(vec![]),
+    }
+}
+
+/// Groups documents by their `requires_auth` frontmatter setting so that
+/// access control can be audited before publishing.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuthReport {
+    /// documents with `requires_auth: true`
+    pub protected: Vec<String>,
+    /// documents with `requires_auth: false`
+    pub public: Vec<String>,
+    /// documents which do not set `requires_auth`
+    pub unspecified: Vec<String>,
+}
+
+pub fn auth_report(docs: &[MarkdownDoc]) -> AuthReport {
+    let mut report = AuthReport::default();
+
+    for doc in docs {
+        let name = doc.file.as_ref().map(|f| f.filename()).unwrap_or_default().to_string();
+        match doc.fm.as_ref().and_then(|fm| fm.requires_auth) {
+            Some(true) => report.protected.push(name),
+            Some(false) => report.public.push(name),
+            None => report.unspecified.push(name),
+        }
+    }
+
+    report
+}
+
+/// Groups documents by the `category`, `layout` and `subject` set in their
+/// frontmatter to give a structural view of a set of documents; documents
+/// which do not set a dimension are left out of that dimension.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Taxonomy {
+    pub category: BTreeMap<String, Vec<String>>,
+    pub layout: BTreeMap<String, Vec<String>>,
+    pub subject: BTreeMap<String, Vec<String>>,
+}
+
+pub fn taxonomy(docs: &[MarkdownDoc]) -> Taxonomy {
+    let mut report = Taxonomy::default();
+
+    for doc in docs {
+        let Some(fm) = doc.fm.as_ref() else { continue };
+        let name = doc.file.as_ref().map(|f| f.filename()).unwrap_or_default().to_string();
+        for (dimension, value) in [
+            (&mut report.category, &fm.category),
+            (&mut report.layout, &fm.layout),
+            (&mut report.subject, &fm.subject),
+        ] {
+            if let Some(value) = value {
+                dimension.entry(value.clone()).or_default().push(name.clone());
+            }
+        }
+    }
+
+    report
+}
+
+/// Replaces the prose of the file a document was loaded from with the
+/// result of `rewrite` while keeping its frontmatter as is; returns whether
+/// the file changed.
+///
+/// The prose is rewritten as it is on disk rather than as it was loaded --
+/// a byte order mark is kept and includes are not inlined. A file which
+/// isn't valid UTF-8 (even with `--lossy`) or which has been modified since
+/// the document was loaded is not rewritten.
+pub fn rewrite_prose(md: &MarkdownDoc, config: &Config, rewrite: impl Fn(&Prose) -> String) -> Result<bool> {
+    let filename = md.file.as_ref().map(|f| f.filename()).unwrap_or_default();
+    let mut file = FileMeta::try_from(filename)?.load_content_with(false, false)?;
+    if md.file.as_ref().map(|f| f.modified()) != Some(file.meta.modified()) {
+        return Err(IoError::FileChanged(filename.to_string()).into());
+    }
+    let raw = file.content.strip_prefix(UTF8_BOM).unwrap_or(&file.content);
+    let prose = config.fm_fences.iter().find_map(|f| f.split(raw)).map_or(raw, |(_, prose)| prose);
+    let header = &file.content[..file.content.len() - prose.len()];
+    let content = format!("{}{}", header, rewrite(&Prose::unhashed(prose)));
+    if content == file.content {
+        return Ok(false);
+    }
+
+    file.hash = hash(&content);
+    file.content = content;
+    file.write_back()?;
+
+    Ok(true)
+}
+
+/// The outcome of checking a document's word count against CI thresholds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WordCountCheck {
+    pub file: Option<String>,
+    pub words: usize,
+    /// the word count of the previous version, when one was available
+    pub previous_words: Option<usize>,
+    pub delta: Option<i64>,
+    /// the reasons the check failed; empty when it passed
+    pub failures: Vec<String>,
+}
+
+/// Checks the word count of a document against a minimum word count and
+/// the maximum percentage of words which may be dropped since the `old`
+/// version of the document.
+pub fn check_word_count(
+    new: &MarkdownDoc,
+    old: Option<&MarkdownDoc>,
+    min_words: Option<usize>,
+    max_drop_percent: Option<f64>,
+) -> WordCountCheck {
+    let words = new.prose.word_count();
+    let previous_words = old.map(|o| o.prose.word_count());
+    let delta = old.map(|o| MarkdownDoc::word_count_delta(o, new));
+    let mut failures: Vec<String> = vec![];
+
+    if let Some(min) = min_words.filter(|min| words < *min) {
+        failures.push(format!("has {} words which is fewer than the minimum of {}", words, min));
+    }
+    if let (Some(max), Some(prev), Some(delta)) = (max_drop_percent, previous_words, delta) {
+        let dropped = if prev > 0 { -delta as f64 / prev as f64 * 100.0 } else { 0.0 };
+        if dropped > max {
+            failures.push(format!("dropped {:.1}% of its words which exceeds the maximum of {}%", dropped, max));
+        }
+    }
+
+    WordCountCheck {
+        file: new.file.as_ref().map(|f| f.filename().to_string()),
+        words,
+        previous_words,
+        delta,
+        failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auth_report_groups_by_requires_auth() {
+        let docs = vec![
+            MarkdownDoc::try_from("---\nrequires_auth: true\n---\n# Private\n").unwrap(),
+            MarkdownDoc::try_from("---\nrequiresAuth: false\n---\n# Public\n").unwrap(),
+            MarkdownDoc::try_from("# Who knows\n").unwrap(),
+        ];
+        let report = auth_report(&docs);
+
+        assert_eq!(report.protected.len(), 1);
+        assert_eq!(report.public.len(), 1);
+        assert_eq!(report.unspecified.len(), 1);
+    }
+
+    #[test]
+    fn taxonomy_groups_by_frontmatter_dimensions() {
+        let docs = vec![
+            MarkdownDoc::try_from("---\ncategory: guides\nlayout: doc\n---\n# A\n").unwrap(),
+            MarkdownDoc::try_from("---\ncategory: guides\nsubject: felling\n---\n# B\n").unwrap(),
+            MarkdownDoc::try_from("---\ncategory: reference\n---\n# C\n").unwrap(),
+            MarkdownDoc::try_from("# No frontmatter\n").unwrap(),
+        ];
+        let report = taxonomy(&docs);
+
+        assert_eq!(report.category.get("guides").map(|d| d.len()), Some(2));
+        assert_eq!(report.category.get("reference").map(|d| d.len()), Some(1));
+        assert_eq!(report.layout.len(), 1);
+        assert_eq!(report.subject.keys().collect::<Vec<_>>(), vec!["felling"]);
+    }
+
+    #[test]
+    fn lenient_frontmatter_reports_non_boolean_values() {
+        let config = Config { lenient_fm: true, ..Config::default() };
+        let coerced = MarkdownDoc::parse_with("---\nrequires_auth: \"yes\"\n---\n# A\n", &config).unwrap();
+        let untouched = MarkdownDoc::parse_with("---\nrequires_auth: sometimes\n---\n# B\n", &config).unwrap();
+        let mut warnings = vec![];
+        md_report(&coerced, &config, &mut warnings);
+        md_report(&untouched, &config, &mut warnings);
+
+        assert_eq!(coerced.fm.as_ref().and_then(|fm| fm.requires_auth), Some(true));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("\"sometimes\""));
+
+        // without --lenient-fm the value is kept (in `other`) and reported
+        let strict = MarkdownDoc::parse_with("---\nrequires_auth: \"yes\"\n---\n", &Config::default()).unwrap();
+        let mut warnings = vec![];
+        md_report(&strict, &Config::default(), &mut warnings);
+        assert_eq!(strict.fm.as_ref().and_then(|fm| fm.requires_auth), None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("\"yes\""));
+    }
+
+    #[test]
+    fn prose_is_rewritten_as_it_is_on_disk() {
+        use crate::md::typography::Typography;
+
+        let dir = std::env::temp_dir().join(format!("ctx-rewrite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let doc = dir.join("doc.md");
+        std::fs::write(dir.join("part.md"), "Included \"part\"\n").unwrap();
+        std::fs::write(&doc, "\u{FEFF}---\ntitle: T\n---\n\"Hi\" there\n\n{{ include: part.md }}\n").unwrap();
+        let config = Config { resolve_includes: true, strip_bom: true, ..Config::default() };
+        let load = || {
+            let file = load_file(FileMeta::try_from(doc.to_str().unwrap()).unwrap(), &config).unwrap();
+            MarkdownDoc::from_file_with(file, &config).unwrap()
+        };
+
+        let md = load();
+        assert!(rewrite_prose(&md, &config, |p| p.apply_typography(Typography::Curl)).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&doc).unwrap(),
+            "\u{FEFF}---\ntitle: T\n---\n\u{201C}Hi\u{201D} there\n\n{{ include: part.md }}\n"
+        );
+        assert!(!rewrite_prose(&load(), &config, |p| p.apply_typography(Typography::Curl)).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn source_files_are_loaded_with_the_configured_options() {
+        let path = std::env::temp_dir().join(format!("ctx-source-{}.rs", std::process::id()));
+        std::fs::write(&path, b"\xEF\xBB\xBF/// Adds `a` to `b`\nfn add() { let _ = \"\xff\"; }\n").unwrap();
+        let target = Target { kind: Fingerprint::SourceFile, user_input: path.to_string_lossy().to_string() };
+        let config = Config { lossy: true, strip_bom: true, no_hash: true, ..Config::default() };
+        let report = source_file(&target, &config, &mut vec![]);
+        let strict = source_file(&target, &Config::default(), &mut vec![]);
+        std::fs::remove_file(&path).unwrap();
+
+        let report = report.unwrap();
+        assert!(strict.is_err());
+        assert!(report["prose"].get("hash").is_none());
+        assert_eq!(report["prose"]["content"], json!("Adds `a` to `b`\n"));
+    }
+
+    #[test]
+    fn invalid_utf8_is_replaced_when_lossy() {
+        let path = std::env::temp_dir().join(format!("ctx-lossy-{}.md", std::process::id()));
+        std::fs::write(&path, b"---\ntitle: Broken\n---\n# Body\n\nbad \xff byte\n").unwrap();
+        let path = path.to_str().unwrap();
+        let strict = load_md(path, &Config::default());
+        let lossy = load_md(path, &Config { lossy: true, ..Config::default() });
+        std::fs::remove_file(path).unwrap();
+
+        assert!(strict.is_err());
+        let doc = lossy.unwrap();
+        assert!(doc.lossy);
+        assert_eq!(doc.fm.as_ref().and_then(|fm| fm.title.as_deref()), Some("Broken"));
+        assert!(doc.prose.content.contains("bad \u{FFFD} byte"));
+        assert_eq!(md_report(&doc, &Config::default(), &mut vec![])["lossy"], json!(true));
+    }
+
+    #[test]
+    fn html_blocks_are_warned_about_when_failing_on_html() {
+        let doc = MarkdownDoc::try_from("# Page\n\n<div>\nRaw\n</div>\n\nSome <b>inline</b> html.\n").unwrap();
+        let mut warnings = vec![];
+        let report = md_report(&doc, &Config::default(), &mut warnings);
+
+        assert_eq!(report["html_blocks"], json!([{ "tag": "div", "line": 3 }]));
+        assert!(warnings.is_empty());
+
+        md_report(&doc, &Config { fail_on_html: true, ..Config::default() }, &mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, crate::WarningKind::RawHtml);
+        assert_eq!(warnings[0].message, "has a raw HTML block (<div>) on line 3");
+    }
+
+    #[test]
+    fn empty_prose_is_flagged() {
+        let empty = MarkdownDoc::try_from("---\ntitle: Stub\n---\n\n  \n").unwrap();
+        let short = MarkdownDoc::try_from("# Short\n\nOnly a few words.\n").unwrap();
+        let config = Config { fail_on_empty_prose: true, ..Config::default() };
+        let mut warnings = vec![];
+
+        assert_eq!(md_report(&empty, &config, &mut warnings)["empty_prose"], json!({ "words": 0, "min_words": 1 }));
+        assert!(md_report(&short, &config, &mut warnings).get("empty_prose").is_none());
+        assert_eq!(warnings.len(), 1);
+
+        let config = Config { min_words: 10, ..config };
+        assert_eq!(md_report(&short, &config, &mut warnings)["empty_prose"]["words"], json!(5));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn whitespace_variant_docs_report_same_normalized_hash() {
+        let a = MarkdownDoc::try_from("# Title\n\nSome text\n").unwrap();
+        let b = MarkdownDoc::try_from("# Title  \n\n\n\nSome text\n\n").unwrap();
+        let config = Config { hash_normalize_whitespace: true, ..Config::default() };
+        let a_report = md_report(&a, &config, &mut vec![]);
+        let b_report = md_report(&b, &config, &mut vec![]);
+
+        assert_eq!(a_report["prose"]["hash"], b_report["prose"]["hash"]);
+        assert_ne!(a_report["prose"]["content"], b_report["prose"]["content"]);
+    }
+
+    #[test]
+    fn normalized_hash_is_of_the_typeset_prose() {
+        let doc = MarkdownDoc::try_from("\"Quoted\"  text\n").unwrap();
+        let config = Config {
+            hash_normalize_whitespace: true,
+            typography: Some(crate::md::typography::Typography::Curl),
+            ..Config::default()
+        };
+        let report = md_report(&doc, &config, &mut vec![]);
+
+        assert_eq!(report["prose"]["content"], json!("\u{201C}Quoted\u{201D}  text\n"));
+        assert_eq!(report["prose"]["hash"], json!(normalized_hash("\u{201C}Quoted\u{201D}  text  \n\n")));
+        assert_ne!(report["prose"]["hash"], json!(doc.prose.normalized_hash()));
+    }
+
+    #[test]
+    fn reading_grade_is_always_reported() {
+        let doc = MarkdownDoc::try_from("# Title\n\nThe cat sat. The dog ran.\n").unwrap();
+        let report = md_report(&doc, &Config::default(), &mut vec![]);
+
+        assert_eq!(report["reading_grade"], json!(doc.reading_grade()));
+        assert_eq!(report["grade_level"], json!("elementary"));
+        assert!(report.get("readability").is_none());
+    }
+
+    #[test]
+    fn unreadable_file_in_dir_is_a_warning() {
+        let dir = std::env::temp_dir().join(format!("ctx-bad-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("index.md"), "---\nindex: true\n---\n# Home\n").unwrap();
+        std::fs::write(dir.join("bad.md"), [b'#', b' ', 0xff, 0xfe, b'\n']).unwrap();
+
+        let mut warnings = vec![];
+        let docs = md_docs_in_dir(&dir.to_string_lossy(), &Config::default(), &mut warnings).unwrap();
+        let mut index_warnings = vec![];
+        let pages = index_pages_in_dir(&dir.to_string_lossy(), &Config::default(), &mut index_warnings).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(docs.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].target.ends_with("bad.md"));
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].ends_with("index.md"));
+        assert_eq!(index_warnings, warnings);
+    }
+
+    #[test]
+    fn hashing_is_skipped_with_no_hash() {
+        let config = Config { no_hash: true, ..Config::default() };
+        let unhashed = load_md("test/data/lumberjack.md", &config).unwrap();
+        let hashed = load_md("test/data/lumberjack.md", &Config::default()).unwrap();
+        let report = md_report(&unhashed, &config, &mut vec![]);
+
+        assert_eq!(unhashed.prose.hash, crate::hasher::UNHASHED);
+        assert_ne!(hashed.prose.hash, crate::hasher::UNHASHED);
+        assert!(report["prose"].get("hash").is_none());
+        assert_eq!(report["prose"]["content"], json!(hashed.prose.content));
+        assert_eq!(unhashed.prose.word_count(), hashed.prose.word_count());
+        assert_eq!(md_report(&hashed, &Config::default(), &mut vec![])["prose"]["hash"], json!(hashed.prose.hash));
+    }
 
-    println!("- markdown {:?}", md);
+    #[test]
+    fn no_hash_holds_with_typography() {
+        let config = Config {
+            no_hash: true,
+            typography: Some(crate::md::typography::Typography::Curl),
+            ..Config::default()
+        };
+        let doc = load_md("test/data/lumberjack.md", &config).unwrap();
+        let report = md_report(&doc, &config, &mut vec![]);
+        let hashed = md_report(&doc, &Config { no_hash: false, ..config.clone() }, &mut vec![]);
 
+        assert!(report["prose"].get("hash").is_none());
+        assert_eq!(report["prose"]["content"], json!(doc.prose.apply_typography(crate::md::typography::Typography::Curl)));
+        assert!(hashed["prose"].get("hash").is_some());
+        assert_eq!(doc.prose.replace_all("a", "b").hash, crate::hasher::UNHASHED);
+    }
 
-    // println!("- {0} is {1:?}", target.user_input, md.file?.filename);
+    #[test]
+    fn word_count_drop_beyond_threshold_fails() {
+        let old = MarkdownDoc::try_from("one two three four five six seven eight nine ten\n").unwrap();
+        let new = MarkdownDoc::try_from("one two three four five six seven\n").unwrap();
 
-    Ok(json!(md))
+        assert_eq!(MarkdownDoc::word_count_delta(&old, &new), -3);
+        assert!(check_word_count(&new, Some(&old), None, Some(50.0)).failures.is_empty());
+        assert_eq!(check_word_count(&new, Some(&old), None, Some(25.0)).failures.len(), 1);
+        assert_eq!(check_word_count(&new, None, Some(10), None).failures.len(), 1);
+    }
 }
@@ -0,0 +1,97 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use super::markdown::MarkdownDoc;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Parses a duration such as `180d`, `6w`, `12h`, `30m` or `90s`; a number
+/// without a unit is a number of days.
+pub fn parse_age(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| format!("'{}' does not start with a number", text))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => DAY,
+        "w" => 7 * DAY,
+        _ => return Err(format!("'{}' is not a unit of time; use one of s, m, h, d or w", unit)),
+    };
+
+    Ok(Duration::from_secs(amount * seconds))
+}
+
+/// A document which has not been modified within the staleness period
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StaleDoc {
+    pub file: String,
+    /// when the file was last modified, in seconds since the Unix epoch
+    pub modified: u64,
+    /// the number of whole days since the file was last modified
+    pub age_days: u64,
+}
+
+/// The documents which have not been modified within a period -- oldest
+/// first -- along with the documents whose modified time is not known.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StaleReport {
+    pub stale: Vec<StaleDoc>,
+    pub unknown_modified: Vec<String>,
+}
+
+/// Reports the documents which -- as of `now` -- have not been modified for
+/// longer than `max_age`; documents not loaded from a file are ignored.
+pub fn stale_docs(docs: &[MarkdownDoc], max_age: Duration, now: SystemTime) -> StaleReport {
+    let mut report = StaleReport::default();
+
+    for file in docs.iter().filter_map(|d| d.file.as_ref()) {
+        let Some(modified) = file.modified() else {
+            report.unknown_modified.push(file.filename().to_string());
+            continue;
+        };
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age > max_age {
+            report.stale.push(StaleDoc {
+                file: file.filename().to_string(),
+                modified: modified.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+                age_days: age.as_secs() / DAY,
+            });
+        }
+    }
+    report.stale.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.file.cmp(&b.file)));
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::reporting::md_docs_in_dir;
+    use crate::Config;
+
+    #[test]
+    fn ages_are_parsed_with_units() {
+        assert_eq!(parse_age("180d"), Ok(Duration::from_secs(180 * DAY)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * DAY)));
+        assert_eq!(parse_age("90"), Ok(Duration::from_secs(90 * DAY)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert!(parse_age("3y").is_err());
+        assert!(parse_age("d").is_err());
+    }
+
+    #[test]
+    fn stale_docs_are_sorted_oldest_first() {
+        let docs = md_docs_in_dir("test", &Config::default(), &mut vec![]).unwrap();
+        let now = SystemTime::now() + Duration::from_secs(400 * DAY);
+        let report = stale_docs(&docs, Duration::from_secs(365 * DAY), now);
+
+        assert_eq!(report.stale.len() + report.unknown_modified.len(), docs.len());
+        assert!(report.stale.windows(2).all(|w| w[0].modified <= w[1].modified));
+        assert!(report.stale.iter().all(|d| d.age_days >= 400));
+        assert!(stale_docs(&docs, Duration::from_secs(100 * 365 * DAY), SystemTime::now()).stale.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use clap::ValueEnum;
+use serde::{Serialize, Deserialize};
+
+use super::prose::Prose;
+
+/// How typographic quotes and dashes should be normalized
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Typography {
+    /// curly quotes become straight quotes and en/em dashes become `--`/`---`
+    Straighten,
+    /// straight quotes become curly quotes and `--`/`---` become en/em dashes
+    Curl,
+}
+
+/// Splits a line into `(is_code, text)` segments where code segments are
+/// inline code spans (including their backtick delimiters).
+fn split_code_spans(line: &str) -> Vec<(bool, &str)> {
+    let mut segments: Vec<(bool, &str)> = vec![];
+    let mut start = 0;
+    let mut idx = 0;
+    let bytes = line.as_bytes();
+
+    while idx < bytes.len() {
+        if bytes[idx] != b'`' {
+            idx += 1;
+            continue;
+        }
+        let run = bytes[idx..].iter().take_while(|b| **b == b'`').count();
+        let delimiter = "`".repeat(run);
+        // a closing run must be of exactly the same length
+        let close = line[idx + run..]
+            .match_indices(&delimiter)
+            .map(|(i, _)| idx + run + i)
+            .find(|&i| bytes.get(i + run) != Some(&b'`') && bytes[i - 1] != b'`');
+
+        match close {
+            Some(end) => {
+                segments.push((false, &line[start..idx]));
+                segments.push((true, &line[idx..end + run]));
+                idx = end + run;
+                start = idx;
+            }
+            None => idx += run,
+        }
+    }
+    segments.push((false, &line[start..]));
+
+    segments
+}
+
+fn straighten(text: &str) -> String {
+    text.replace(['\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}'], "'")
+        .replace(['\u{201C}', '\u{201D}', '\u{201E}', '\u{201F}'], "\"")
+        .replace('\u{2014}', "---")
+        .replace('\u{2013}', "--")
+}
+
+fn curl(text: &str) -> String {
+    let text = text.replace("---", "\u{2014}").replace("--", "\u{2013}");
+    let mut curled = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+
+    for c in text.chars() {
+        let opening = previous.is_none_or(|p| p.is_whitespace() || "([{\u{2014}\u{2013}".contains(p));
+        curled.push(match (c, opening) {
+            ('"', true) => '\u{201C}',
+            ('"', false) => '\u{201D}',
+            ('\'', true) => '\u{2018}',
+            ('\'', false) => '\u{2019}',
+            _ => c,
+        });
+        previous = Some(c);
+    }
+
+    curled
+}
+
+impl Prose {
+    /// Normalizes quotes and dashes according to `mode`; fenced code blocks
+    /// and inline code spans are left untouched.
+    pub fn apply_typography(&self, mode: Typography) -> String {
+        self.map_outside_fences(|line| {
+            split_code_spans(line)
+                .into_iter()
+                .map(|(is_code, text)| match (is_code, mode) {
+                    (true, _) => text.to_string(),
+                    (false, Typography::Straighten) => straighten(text),
+                    (false, Typography::Curl) => curl(text),
+                })
+                .collect()
+        })
+        .content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curly_quotes_and_dashes_are_straightened() {
+        let prose = Prose::from("\u{201C}Don\u{2019}t\u{201D} \u{2014} pages 1\u{2013}3\n");
+
+        assert_eq!(prose.apply_typography(Typography::Straighten), "\"Don't\" --- pages 1--3\n");
+    }
+
+    #[test]
+    fn straight_quotes_are_curled() {
+        let prose = Prose::from("She said \"it's fine\" -- really.\n");
+
+        assert_eq!(
+            prose.apply_typography(Typography::Curl),
+            "She said \u{201C}it\u{2019}s fine\u{201D} \u{2013} really.\n"
+        );
+    }
+
+    #[test]
+    fn code_is_left_untouched() {
+        let prose = Prose::from("Use `\"x\" -- y` or ``a ` \"b\"`` for \"z\".\n\n```\nlet s = \"q\";\n```\n");
+
+        assert_eq!(
+            prose.apply_typography(Typography::Curl),
+            "Use `\"x\" -- y` or ``a ` \"b\"`` for \u{201C}z\u{201D}.\n\n```\nlet s = \"q\";\n```\n"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::{
+    errors::io::IoError,
+    file::FileWithMeta,
+};
+
+/// the text of a cell's `source`, which a notebook may store either as a
+/// single string or as a list of lines
+fn cell_source(cell: &Value) -> String {
+    match cell.get("source") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(|l| l.as_str()).collect(),
+        _ => String::new(),
+    }
+}
+
+/// **Notebook**
+///
+/// A summary of a Jupyter notebook (`.ipynb`) with the source of its
+/// markdown and code cells; `raw` cells are only included in the count.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Notebook {
+    /// the number of cells of any type
+    pub cell_count: usize,
+    /// the source of each markdown cell, in notebook order
+    pub markdown_cells: Vec<String>,
+    /// the source of each code cell, in notebook order
+    pub code_cells: Vec<String>,
+    /// the kernel's language (e.g., `python`) from `metadata.kernelspec`
+    pub language: Option<String>,
+}
+
+impl Notebook {
+    /// Parses the JSON of a notebook; `file` is only used to identify the
+    /// notebook when it is not valid.
+    pub fn parse(json: &str, file: &str) -> Result<Self, IoError> {
+        let notebook: Value = serde_json::from_str(json)
+            .map_err(|e| IoError::InvalidNotebook(file.to_string(), e.to_string()))?;
+        let cells = notebook
+            .get("cells")
+            .and_then(|c| c.as_array())
+            .ok_or_else(|| IoError::InvalidNotebook(file.to_string(), "there is no list of cells".to_string()))?;
+        let sources_of = |kind: &str| -> Vec<String> {
+            cells
+                .iter()
+                .filter(|c| c.get("cell_type").and_then(|t| t.as_str()) == Some(kind))
+                .map(cell_source)
+                .collect()
+        };
+
+        Ok(Notebook {
+            cell_count: cells.len(),
+            markdown_cells: sources_of("markdown"),
+            code_cells: sources_of("code"),
+            language: notebook
+                .pointer("/metadata/kernelspec/language")
+                .and_then(|l| l.as_str())
+                .map(|l| l.to_string()),
+        })
+    }
+
+    pub fn from_file(file: &FileWithMeta) -> Result<Self, IoError> {
+        Notebook::parse(&file.content, file.meta.filename())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_extracted_by_type() {
+        let json = r##"{
+            "cells": [
+                { "cell_type": "markdown", "metadata": {}, "source": ["# Analysis\n", "Some notes"] },
+                { "cell_type": "code", "metadata": {}, "outputs": [], "source": "import pandas as pd" },
+                { "cell_type": "raw", "metadata": {}, "source": [] }
+            ],
+            "metadata": { "kernelspec": { "name": "python3", "language": "python" } },
+            "nbformat": 4,
+            "nbformat_minor": 5
+        }"##;
+        let notebook = Notebook::parse(json, "analysis.ipynb").unwrap();
+
+        assert_eq!(notebook.cell_count, 3);
+        assert_eq!(notebook.markdown_cells, vec!["# Analysis\nSome notes"]);
+        assert_eq!(notebook.code_cells, vec!["import pandas as pd"]);
+        assert_eq!(notebook.language.as_deref(), Some("python"));
+    }
+
+    #[test]
+    fn invalid_notebooks_are_errors() {
+        assert!(Notebook::parse("not json", "a.ipynb").is_err());
+        assert!(Notebook::parse(r#"{ "metadata": {} }"#, "a.ipynb").is_err());
+    }
+}
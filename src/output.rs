@@ -0,0 +1,300 @@
+use std::{
+    cmp::Ordering,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value, json, ser::{PrettyFormatter, Serializer}};
+
+use crate::Config;
+
+/// the name of this tool as reported in output envelopes
+pub const TOOL_NAME: &str = "ctx";
+
+/// the suffix appended to a source file's name to name its sidecar
+pub const SIDECAR_SUFFIX: &str = ".ctx.json";
+
+/// The path of the sidecar JSON file for `source`: next to the source
+/// (e.g., `docs/foo.md` → `docs/foo.md.ctx.json`) or -- when `dir` is
+/// given -- at the same relative path within `dir`.
+pub fn sidecar_path(source: &str, dir: Option<&str>) -> PathBuf {
+    let sidecar = PathBuf::from(format!("{}{}", source, SIDECAR_SUFFIX));
+    match dir {
+        Some(dir) => Path::new(dir).join(
+            sidecar
+                .components()
+                .filter(|c| matches!(c, Component::Normal(_)))
+                .collect::<PathBuf>()
+        ),
+        None => sidecar,
+    }
+}
+
+/// Formats a point in time as an RFC 3339 timestamp in UTC
+/// (e.g., `2023-11-27T16:32:34Z`).
+pub fn rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3_600, (rem % 3_600) / 60, rem % 60
+    )
+}
+
+/// Sorts the keys of every JSON object -- at any depth -- within the value
+pub fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sort_keys(v))).collect::<Map<String, Value>>())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+/// Writes the value as JSON; with an `indent` each level is indented by
+/// that many spaces, otherwise the JSON is compact.
+pub fn write_json<W: Write>(writer: &mut W, value: &Value, indent: Option<usize>) -> io::Result<()> {
+    match indent {
+        Some(indent) => {
+            let spaces = " ".repeat(indent);
+            let mut serializer = Serializer::with_formatter(writer, PrettyFormatter::with_indent(spaces.as_bytes()));
+            value.serialize(&mut serializer)?;
+        }
+        None => serde_json::to_writer(writer, value)?,
+    }
+
+    Ok(())
+}
+
+/// Renders the value as JSON with the indentation and key ordering the
+/// configuration asks for.
+pub fn render_json(value: &Value, config: &Config) -> String {
+    let sorted;
+    let value = match config.sort_keys {
+        true => {
+            sorted = sort_keys(value.clone());
+            &sorted
+        }
+        false => value,
+    };
+    let mut out: Vec<u8> = vec![];
+    write_json(&mut out, value, config.indent).expect("writing JSON to memory can not fail");
+
+    String::from_utf8(out).expect("JSON is always valid UTF-8")
+}
+
+/// Wraps the results of a run in an envelope which describes the tool,
+/// its version, when the output was generated and the options in effect.
+pub fn envelope(results: Value, config: &Config) -> Value {
+    json!({
+        "tool": TOOL_NAME,
+        "version": env!("CARGO_PKG_VERSION"),
+        "generated_at": rfc3339(SystemTime::now()),
+        "options": config,
+        "results": results,
+    })
+}
+
+/// The property of a result by which multi-file output can be sorted
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    Path,
+    Title,
+    Words,
+    Modified,
+}
+
+/// the value of the sort field for a single result, if it has one
+fn sort_key(result: &Value, field: SortField) -> Option<Value> {
+    let found = match field {
+        SortField::Path => [&result["file"]["filename"], &result["file"], &result["url"]]
+            .into_iter()
+            .find(|v| v.is_string())
+            .cloned(),
+        SortField::Title => Some(result["fm"]["title"].clone()),
+        SortField::Words => Some(result["word_count"].clone()),
+        SortField::Modified => {
+            let modified = &result["file"]["modified"];
+            modified["secs_since_epoch"].as_u64().map(|secs| {
+                json!([secs, modified["nanos_since_epoch"].as_u64().unwrap_or(0)])
+            })
+        }
+    };
+
+    found.filter(|v| !v.is_null())
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or(0.0)
+            .partial_cmp(&b.as_f64().unwrap_or(0.0))
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| compare_values(a, b))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or(a.len().cmp(&b.len())),
+        _ => Ordering::Equal,
+    }
+}
+
+/// the results of a run as a flat list; directory targets produce a list
+/// of results which is merged into the list
+fn flatten(results: Value) -> Vec<Value> {
+    match results {
+        Value::Array(items) => items
+            .into_iter()
+            .flat_map(|i| match i {
+                Value::Array(nested) => nested,
+                other => vec![other],
+            })
+            .collect(),
+        other => vec![other],
+    }
+}
+
+/// Flattens the results (directory targets produce a list of results) and
+/// then sorts them -- stably -- by the given field; results which do not
+/// have a value for the field are always placed last.
+pub fn sort_results(results: Value, field: SortField, reverse: bool) -> Value {
+    let mut flat = flatten(results);
+
+    flat.sort_by(|a, b| match (sort_key(a, field), sort_key(b, field)) {
+        (Some(a), Some(b)) if reverse => compare_values(&b, &a),
+        (Some(a), Some(b)) => compare_values(&a, &b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+
+    json!(flat)
+}
+
+/// Validates a JSON Pointer (RFC 6901); it must be empty (the whole
+/// document) or start with a `/`.
+pub fn parse_json_pointer(pointer: &str) -> Result<String, String> {
+    match pointer.is_empty() || pointer.starts_with('/') {
+        true => Ok(pointer.to_string()),
+        false => Err(format!("'{}' is not a JSON Pointer; it must start with a '/' (e.g., /fm/title)", pointer)),
+    }
+}
+
+/// Applies the JSON Pointer to each result and provides a tab separated
+/// `path<TAB>value` line for each one; strings are written without quotes
+/// while other values are written as JSON. A result the pointer does not
+/// resolve in has an empty value or -- with `skip_missing` -- no line.
+pub fn query_results(results: Value, pointer: &str, skip_missing: bool) -> Vec<String> {
+    flatten(results)
+        .iter()
+        .filter_map(|result| {
+            let path = sort_key(result, SortField::Path)
+                .and_then(|p| p.as_str().map(|p| p.to_string()))
+                .unwrap_or_default();
+            let value = match result.pointer(pointer) {
+                Some(Value::String(text)) => text.clone(),
+                Some(value) => value.to_string(),
+                None if skip_missing => return None,
+                None => String::new(),
+            };
+            Some(format!("{}\t{}", path, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rfc3339_formats_utc_timestamps() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(UNIX_EPOCH + Duration::from_secs(1_701_102_754)), "2023-11-27T16:32:34Z");
+        assert_eq!(rfc3339(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn json_is_rendered_with_indent_and_sorted_keys() {
+        let value = json!({ "b": [{ "d": 1, "c": 2 }], "a": null });
+        let config = Config { indent: Some(4), sort_keys: true, ..Config::default() };
+
+        assert_eq!(
+            render_json(&value, &config),
+            "{\n    \"a\": null,\n    \"b\": [\n        {\n            \"c\": 2,\n            \"d\": 1\n        }\n    ]\n}"
+        );
+        assert_eq!(render_json(&value, &Config::default()), r#"{"a":null,"b":[{"c":2,"d":1}]}"#);
+    }
+
+    #[test]
+    fn sidecars_are_next_to_the_source_or_mirrored() {
+        assert_eq!(sidecar_path("docs/foo.md", None), PathBuf::from("docs/foo.md.ctx.json"));
+        assert_eq!(sidecar_path("./docs/foo.md", Some("out")), PathBuf::from("out/docs/foo.md.ctx.json"));
+        assert_eq!(sidecar_path("/abs/../foo.md", Some("out")), PathBuf::from("out/abs/foo.md.ctx.json"));
+    }
+
+    #[test]
+    fn envelope_wraps_results() {
+        let wrapped = envelope(json!([1, 2]), &Config::default());
+
+        assert_eq!(wrapped["tool"], "ctx");
+        assert_eq!(wrapped["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(wrapped["results"], json!([1, 2]));
+        assert_eq!(wrapped["options"]["draft_field"], "draft");
+    }
+
+    #[test]
+    fn results_are_flattened_and_sorted_with_missing_last() {
+        let results = json!([
+            { "file": { "filename": "b.md" }, "word_count": 10, "fm": { "title": "Beta" } },
+            [
+                { "file": { "filename": "a.md" }, "word_count": 30, "fm": null },
+                { "file": { "filename": "c.md" }, "word_count": 20, "fm": { "title": "alpha" } }
+            ]
+        ]);
+        let paths = |v: &Value| -> Vec<String> {
+            v.as_array().unwrap().iter().map(|i| i["file"]["filename"].as_str().unwrap().to_string()).collect()
+        };
+
+        assert_eq!(paths(&sort_results(results.clone(), SortField::Path, false)), vec!["a.md", "b.md", "c.md"]);
+        assert_eq!(paths(&sort_results(results.clone(), SortField::Words, true)), vec!["a.md", "c.md", "b.md"]);
+        assert_eq!(paths(&sort_results(results.clone(), SortField::Title, false)), vec!["c.md", "b.md", "a.md"]);
+        assert_eq!(paths(&sort_results(results, SortField::Title, true)), vec!["b.md", "c.md", "a.md"]);
+    }
+
+    #[test]
+    fn queries_extract_a_value_from_each_result() {
+        let results = json!([
+            { "file": { "filename": "b.md" }, "fm": { "title": "Beta", "tags": ["x"] } },
+            [{ "file": { "filename": "a.md" }, "fm": null }],
+            { "url": "https://example.com/c.md", "doc": {} }
+        ]);
+
+        assert_eq!(query_results(results.clone(), "/fm/title", false), vec!["b.md\tBeta", "a.md\t", "https://example.com/c.md\t"]);
+        assert_eq!(query_results(results.clone(), "/fm/tags", true), vec!["b.md\t[\"x\"]"]);
+        assert!(parse_json_pointer("fm/title").is_err());
+        assert_eq!(parse_json_pointer("/fm/title"), Ok("/fm/title".to_string()));
+    }
+}
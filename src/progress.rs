@@ -0,0 +1,144 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::Mutex,
+};
+
+/// the number of characters used to draw the bar itself
+const BAR_WIDTH: usize = 30;
+
+struct ProgressState {
+    done: usize,
+    out: Option<Box<dyn Write + Send>>,
+}
+
+/// **Progress**
+///
+/// A progress bar showing how many of the targets have been processed along
+/// with the target currently being worked on. Updates are serialized so it
+/// may be shared across threads and -- as it only ever writes to stderr --
+/// it can not corrupt the JSON written to stdout.
+///
+/// The bar is redrawn in place with ANSI escapes rather than by `indicatif`
+/// so it has a fixed width, no rate or ETA and a long target name may wrap
+/// in a narrow terminal.
+pub struct Progress {
+    total: usize,
+    state: Mutex<ProgressState>,
+}
+
+impl Progress {
+    /// A progress bar on stderr; nothing is displayed when `quiet` is set or
+    /// when stderr is not a terminal (e.g., it is redirected to a file).
+    pub fn stderr(total: usize, quiet: bool) -> Self {
+        let out = (!quiet && io::stderr().is_terminal()).then(|| Box::new(io::stderr()) as Box<dyn Write + Send>);
+        Progress { total, state: Mutex::new(ProgressState { done: 0, out }) }
+    }
+
+    /// a progress bar which is always drawn to the given writer
+    pub fn to_writer(total: usize, out: Box<dyn Write + Send>) -> Self {
+        Progress { total, state: Mutex::new(ProgressState { done: 0, out: Some(out) }) }
+    }
+
+    /// whether the progress bar is displayed at all
+    pub fn is_active(&self) -> bool {
+        self.state.lock().map(|s| s.out.is_some()).unwrap_or(false)
+    }
+
+    fn render(done: usize, total: usize, current: &str) -> String {
+        let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(BAR_WIDTH);
+        format!(
+            "\r\x1b[2K[{}{}] {}/{} {}",
+            "=".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            done,
+            total,
+            current
+        )
+    }
+
+    /// shows that `current` is now being processed
+    pub fn working_on(&self, current: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            let line = Progress::render(state.done, self.total, current);
+            if let Some(out) = state.out.as_mut() {
+                let _ = out.write_all(line.as_bytes()).and_then(|_| out.flush());
+            }
+        }
+    }
+
+    /// records that one more target has been processed
+    pub fn advance(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.done = (state.done + 1).min(self.total);
+        }
+    }
+
+    /// the number of targets processed so far
+    pub fn done(&self) -> usize {
+        self.state.lock().map(|s| s.done).unwrap_or_default()
+    }
+
+    /// clears the progress bar from the terminal
+    pub fn finish(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(out) = state.out.as_mut() {
+                let _ = out.write_all(b"\r\x1b[2K").and_then(|_| out.flush());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// a writer whose output can be inspected after it has been boxed
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn progress_shows_count_and_current_target() {
+        let out = Shared::default();
+        let progress = Progress::to_writer(4, Box::new(out.clone()));
+        progress.advance();
+        progress.working_on("docs/b.md");
+        let written = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+
+        assert!(written.ends_with(&format!("[{}{}] 1/4 docs/b.md", "=".repeat(7), " ".repeat(23))));
+    }
+
+    #[test]
+    fn progress_is_updated_safely_across_threads() {
+        let progress = Progress::to_writer(100, Box::new(Shared::default()));
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let progress = &progress;
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        progress.working_on(&format!("{}-{}", t, i));
+                        progress.advance();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(progress.done(), 100);
+    }
+
+    #[test]
+    fn progress_is_not_displayed_when_quiet() {
+        assert!(!Progress::stderr(3, true).is_active());
+    }
+}
@@ -0,0 +1,574 @@
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    thread::sleep,
+    time::Duration,
+};
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
+use tracing::{instrument, warn};
+
+use crate::{
+    Config, Fingerprint, Target, Warning,
+    fingerprint::{fingerprint_name, fingerprint_with_content},
+    errors::{Result, WithTarget, io::IoError, remote::RemoteError},
+    hasher::hash,
+    html::HtmlDoc,
+    md::{markdown::MarkdownDoc, reporting::md_report},
+};
+
+/// The parts of an `http://` URL needed to make a request; only plain HTTP
+/// is supported (there is no TLS) so `https://` URLs are rejected with
+/// `RemoteError::UnsupportedScheme`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Url {
+    /// the host name or IP address; an IPv6 address is held without the
+    /// brackets which surround it in the URL
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl TryFrom<&str> for Url {
+    type Error = RemoteError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let invalid = || RemoteError::InvalidUrl(value.to_string());
+        let rest = match value.split_once("://") {
+            Some(("http", rest)) => rest,
+            Some((_, _)) => return Err(RemoteError::UnsupportedScheme(value.to_string())),
+            None => return Err(invalid()),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "/"),
+        };
+        // an IPv6 address is bracketed (e.g., `[::1]:8080`) as it holds colons
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => match bracketed.split_once(']').ok_or_else(invalid)? {
+                (host, "") => (host, None),
+                (host, port) => (host, Some(port.strip_prefix(':').ok_or_else(invalid)?)),
+            },
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = match port {
+            Some(port) => port.parse::<u16>().map_err(|_| invalid())?,
+            None => 80,
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Url { host: host.to_string(), port, path: path.to_string() })
+    }
+}
+
+impl Url {
+    /// the value of the `Host` header for a request to this URL; the port
+    /// is included unless it is the default (`80`)
+    pub fn host_header(&self) -> String {
+        let host = match self.host.contains(':') {
+            true => format!("[{}]", self.host),
+            false => self.host.clone(),
+        };
+        match self.port {
+            80 => host,
+            port => format!("{}:{}", host, port),
+        }
+    }
+}
+
+/// A response to a remote request
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Response {
+    pub status: u16,
+    /// the response headers with lowercased names
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// the value of the header with the given (case-insensitive) name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// How failed requests should be retried
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// the number of retries after the initial attempt
+    pub retries: u32,
+    /// the delay before the first retry; each further retry doubles it
+    pub base_delay: Duration,
+    /// the timeout of each individual attempt
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            retries: 0,
+            base_delay: Duration::from_millis(250),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// decodes a `Transfer-Encoding: chunked` body
+fn dechunk(body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded: Vec<u8> = vec![];
+    let mut rest = body;
+    loop {
+        let line_end = rest.windows(2).position(|w| w == b"\r\n")?;
+        let size_line = std::str::from_utf8(&rest[..line_end]).ok()?;
+        let size = usize::from_str_radix(size_line.split(';').next()?.trim(), 16).ok()?;
+        rest = &rest[line_end + 2..];
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(rest.get(..size)?);
+        rest = rest.get(size + 2..)?;
+    }
+}
+
+fn parse_response(url: &str, raw: &[u8]) -> Result<Response, RemoteError> {
+    let invalid = || RemoteError::InvalidResponse(url.to_string());
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n").ok_or_else(invalid)?;
+    let head = std::str::from_utf8(&raw[..split]).map_err(|_| invalid())?;
+    let mut lines = head.lines();
+
+    let status = lines
+        .next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(invalid)?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let mut response = Response { status, headers, body: String::new() };
+    let body = &raw[split + 4..];
+    let body = match response.header("transfer-encoding") {
+        Some(enc) if enc.eq_ignore_ascii_case("chunked") => dechunk(body).ok_or_else(invalid)?,
+        _ => body.to_vec(),
+    };
+    response.body = String::from_utf8_lossy(&body).to_string();
+
+    Ok(response)
+}
+
+/// Makes a single `GET` request for the given `http://` URL; any non-2xx
+/// status is returned as a `RemoteError::HttpStatus`.
+pub fn fetch(url: &str, timeout: Duration) -> Result<Response, RemoteError> {
+    fetch_with_headers(url, timeout, &[])
+}
+
+/// Makes a single `GET` request for the given `http://` URL with additional
+/// request headers. As conditional requests (e.g., `If-None-Match`) may be
+/// made, a `304 Not Modified` status is returned as a response while any
+/// other non-2xx status is returned as a `RemoteError::HttpStatus`.
+#[instrument]
+pub fn fetch_with_headers(url: &str, timeout: Duration, headers: &[(String, String)]) -> Result<Response, RemoteError> {
+    let parsed = Url::try_from(url)?;
+    let connection = |e: std::io::Error| RemoteError::Connection(url.to_string(), e.to_string());
+
+    let addr = (parsed.host.as_str(), parsed.port)
+        .to_socket_addrs()
+        .map_err(connection)?
+        .next()
+        .ok_or_else(|| RemoteError::InvalidUrl(url.to_string()))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(connection)?;
+    stream.set_read_timeout(Some(timeout)).map_err(connection)?;
+    stream.set_write_timeout(Some(timeout)).map_err(connection)?;
+
+    let extra: String = headers.iter().map(|(k, v)| format!("{}: {}\r\n", k, v)).collect();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: ctx/{}\r\nAccept: */*\r\n{}Connection: close\r\n\r\n",
+        parsed.path, parsed.host_header(), env!("CARGO_PKG_VERSION"), extra
+    );
+    stream.write_all(request.as_bytes()).map_err(connection)?;
+    let mut raw: Vec<u8> = vec![];
+    stream.read_to_end(&mut raw).map_err(connection)?;
+
+    let response = parse_response(url, &raw)?;
+    if (200..300).contains(&response.status) || response.status == 304 {
+        Ok(response)
+    } else {
+        Err(RemoteError::HttpStatus(url.to_string(), response.status))
+    }
+}
+
+/// Fetches the URL, retrying transient failures (connection errors and 5xx
+/// statuses) with an exponential backoff; the final failure is returned
+/// once all retries have been exhausted.
+pub fn fetch_with_retry(url: &str, policy: &RetryPolicy) -> Result<Response, RemoteError> {
+    fetch_with_retry_and_headers(url, policy, &[])
+}
+
+fn fetch_with_retry_and_headers(
+    url: &str,
+    policy: &RetryPolicy,
+    headers: &[(String, String)]
+) -> Result<Response, RemoteError> {
+    let mut attempt = 0;
+    loop {
+        match fetch_with_headers(url, policy.timeout, headers) {
+            Err(e) if e.is_transient() && attempt < policy.retries => {
+                let delay = policy.base_delay * 2u32.pow(attempt);
+                warn!("attempt {} for {} failed ({}); retrying in {:?}", attempt + 1, url, e, delay);
+                sleep(delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A cached response along with the validators -- the `ETag` and
+/// `Last-Modified` headers -- used to revalidate it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// a hash of the cached body; entries whose body no longer matches it
+    /// are ignored
+    pub hash: u64,
+    pub response: Response,
+}
+
+impl CachedResponse {
+    /// the conditional request headers which revalidate this response
+    fn conditional_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<(String, String)> = vec![];
+        if let Some(etag) = &self.etag {
+            headers.push(("If-None-Match".to_string(), etag.clone()));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.push(("If-Modified-Since".to_string(), last_modified.clone()));
+        }
+
+        headers
+    }
+}
+
+/// **HttpCache**
+///
+/// A directory of responses -- one JSON file per URL -- which can be
+/// revalidated with conditional requests rather than fetched again.
+///
+/// Unlike the `FileCache` -- which only needs the content hash of each local
+/// file to tell whether it changed -- a `304 Not Modified` response must be
+/// answered from the cached body, so whole responses are kept here (checked
+/// against the same content hash) in the directory given by `--cache`.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+impl HttpCache {
+    pub fn new(dir: &str) -> Self {
+        HttpCache { dir: PathBuf::from(dir) }
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", hash(url)))
+    }
+
+    /// the cached response for the URL, if there is a valid one
+    pub fn load(&self, url: &str) -> Option<CachedResponse> {
+        let cached: CachedResponse = serde_json::from_str(&read_to_string(self.path_for(url)).ok()?).ok()?;
+        (hash(&cached.response.body) == cached.hash).then_some(cached)
+    }
+
+    /// Caches the response for the URL; responses without an `ETag` or
+    /// `Last-Modified` header can't be revalidated and are not cached.
+    pub fn store(&self, url: &str, response: &Response) -> Result<(), IoError> {
+        let cached = CachedResponse {
+            etag: response.header("etag").map(|v| v.to_string()),
+            last_modified: response.header("last-modified").map(|v| v.to_string()),
+            hash: hash(&response.body),
+            response: response.clone(),
+        };
+        if cached.etag.is_none() && cached.last_modified.is_none() {
+            return Ok(());
+        }
+
+        let path = self.path_for(url);
+        let failed = |e| IoError::WriteFailedWith(path.to_string_lossy().to_string(), e);
+        create_dir_all(&self.dir).map_err(failed)?;
+        write(&path, json!(cached).to_string()).map_err(failed)
+    }
+}
+
+/// Fetches the URL -- with retries -- revalidating any response cached for
+/// it; the cached response is used when the server responds with `304 Not
+/// Modified` while any other successful response replaces it.
+pub fn fetch_cached(url: &str, policy: &RetryPolicy, cache: &HttpCache) -> Result<Response, RemoteError> {
+    let cached = cache.load(url);
+    let headers = cached.as_ref().map(|c| c.conditional_headers()).unwrap_or_default();
+    let response = fetch_with_retry_and_headers(url, policy, &headers)?;
+
+    match (response.status, cached) {
+        (304, Some(cached)) => Ok(cached.response),
+        (304, None) => Err(RemoteError::HttpStatus(url.to_string(), 304)),
+        _ => {
+            if let Err(e) = cache.store(url, &response) {
+                warn!("the response for {} could not be cached: {}", url, e);
+            }
+            Ok(response)
+        }
+    }
+}
+
+/// Determines the kind of a remote resource: the `Content-Type` header is
+/// consulted first and -- when it is not conclusive -- the URL's extension
+/// and finally the fetched content itself are used; nothing on the local
+/// filesystem is consulted.
+pub fn remote_fingerprint(url: &str, response: &Response) -> Fingerprint {
+    let content_type = response
+        .header("content-type")
+        .map(|ct| ct.split(';').next().unwrap_or_default().trim().to_lowercase());
+
+    match content_type.as_deref() {
+        Some("text/markdown") | Some("text/x-markdown") => return Fingerprint::MarkdownFile,
+        Some("text/html") | Some("application/xhtml+xml") => return Fingerprint::HtmlFile,
+        _ => {}
+    }
+
+    let path = Url::try_from(url).map(|u| u.path).unwrap_or_default();
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let kind = match fingerprint_name(path) {
+        Fingerprint::Unknown if content_type.as_deref() == Some("text/plain") => {
+            fingerprint_with_content(path, response.body.as_bytes())
+        }
+        kind => kind,
+    };
+    match kind {
+        Fingerprint::MarkdownFile | Fingerprint::HtmlFile => kind,
+        _ => Fingerprint::Unknown,
+    }
+}
+
+/// Fetches a remote target and reports on it; markdown and HTML content is
+/// reported on in the same way as a local file.
+pub fn remote_report(target: &Target, config: &Config, warnings: &mut Vec<Warning>) -> Result<Value> {
+    eprintln!("- '{}' is being processed as a remote URL", &target.user_input);
+    let response = match &config.cache {
+        Some(dir) => fetch_cached(&target.user_input, &config.retry_policy(), &HttpCache::new(dir)),
+        None => fetch_with_retry(&target.user_input, &config.retry_policy()),
+    }
+    .map_err(IoError::from)
+    .with_target(&target.user_input)?;
+    let kind = remote_fingerprint(&target.user_input, &response);
+
+    let doc = match kind {
+        Fingerprint::MarkdownFile => {
+            let md = MarkdownDoc::parse_with(&response.body, config).with_target(&target.user_input)?;
+            md_report(&md, config, warnings)
+        }
+        Fingerprint::HtmlFile => json!(HtmlDoc::parse(&response.body)),
+        _ => Value::Null,
+    };
+
+    Ok(json!({
+        "url": target.user_input,
+        "status": response.status,
+        "content_type": response.header("content-type"),
+        "kind": kind,
+        "doc": doc,
+    }))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    /// Starts a mock HTTP server which serves the given responses -- as
+    /// `(status, content_type, body)` -- to successive connections and
+    /// returns the base URL of the server.
+    pub(crate) fn mock_server(responses: Vec<(u16, &'static str, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            for (status, content_type, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, content_type, body.len(), body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    fn quick_policy(retries: u32) -> RetryPolicy {
+        RetryPolicy { retries, base_delay: Duration::from_millis(1), timeout: Duration::from_secs(5) }
+    }
+
+    #[test]
+    fn urls_are_parsed() {
+        let url = Url::try_from("http://example.com:8080/docs/a.md").unwrap();
+        assert_eq!(url, Url { host: "example.com".to_string(), port: 8080, path: "/docs/a.md".to_string() });
+        assert_eq!(Url::try_from("http://example.com").unwrap().path, "/");
+        assert!(matches!(Url::try_from("https://example.com"), Err(RemoteError::UnsupportedScheme(_))));
+        assert!(matches!(Url::try_from("example.com"), Err(RemoteError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn ipv6_hosts_and_ports_are_parsed() {
+        let url = Url::try_from("http://[::1]:8080/a.md").unwrap();
+        assert_eq!(url, Url { host: "::1".to_string(), port: 8080, path: "/a.md".to_string() });
+        assert_eq!(Url::try_from("http://[::1]").unwrap().port, 80);
+        assert!(matches!(Url::try_from("http://[::1"), Err(RemoteError::InvalidUrl(_))));
+        assert!(matches!(Url::try_from("http://[::1]8080/"), Err(RemoteError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn host_header_includes_a_non_default_port() {
+        let host = |url: &str| Url::try_from(url).unwrap().host_header();
+        assert_eq!(host("http://example.com/a.md"), "example.com");
+        assert_eq!(host("http://example.com:8080/a.md"), "example.com:8080");
+        assert_eq!(host("http://[::1]:8080/a.md"), "[::1]:8080");
+        assert_eq!(host("http://[::1]/a.md"), "[::1]");
+    }
+
+    #[test]
+    fn transient_failures_are_retried_until_success() {
+        let base = mock_server(vec![
+            (503, "text/plain", "down"),
+            (502, "text/plain", "down"),
+            (200, "text/markdown", "# Hello"),
+        ]);
+        let response = fetch_with_retry(&format!("{}/doc", base), &quick_policy(2)).unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "# Hello");
+        assert_eq!(response.header("Content-Type"), Some("text/markdown"));
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let base = mock_server(vec![(404, "text/plain", "missing"), (200, "text/plain", "ok")]);
+        let result = fetch_with_retry(&format!("{}/doc", base), &quick_policy(3));
+
+        assert!(matches!(result, Err(RemoteError::HttpStatus(_, 404))));
+    }
+
+    #[test]
+    fn final_failure_reported_when_retries_exhausted() {
+        let base = mock_server(vec![(500, "text/plain", "x"), (500, "text/plain", "x")]);
+        let result = fetch_with_retry(&format!("{}/doc", base), &quick_policy(1));
+
+        assert!(matches!(result, Err(RemoteError::HttpStatus(_, 500))));
+    }
+
+    /// Starts a mock HTTP server which serves the given raw responses to
+    /// successive connections; each request received is sent to the
+    /// returned channel.
+    fn recording_server(responses: Vec<String>) -> (String, std::sync::mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, requests) = std::sync::mpsc::channel();
+
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let read = stream.read(&mut buf).unwrap_or_default();
+                let _ = sender.send(String::from_utf8_lossy(&buf[..read]).to_string());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (format!("http://127.0.0.1:{}", port), requests)
+    }
+
+    #[test]
+    fn not_modified_responses_use_the_cached_body() {
+        let body = "# Cached";
+        let (base, requests) = recording_server(vec![
+            format!(
+                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nLast-Modified: Mon, 01 Jan 2024 00:00:00 GMT\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(), body
+            ),
+            "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+        ]);
+        let dir = std::env::temp_dir().join(format!("ctx-http-cache-{}", std::process::id()));
+        let cache = HttpCache::new(&dir.to_string_lossy());
+        let url = format!("{}/doc.md", base);
+
+        let first = fetch_cached(&url, &quick_policy(0), &cache).unwrap();
+        let second = fetch_cached(&url, &quick_policy(0), &cache).unwrap();
+        let _ = requests.recv().unwrap();
+        let revalidation = requests.recv().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first.body, body);
+        assert_eq!(second.status, 200);
+        assert_eq!(second.body, body);
+        assert!(revalidation.contains("If-None-Match: \"v1\"\r\n"));
+        assert!(revalidation.contains("If-Modified-Since: Mon, 01 Jan 2024 00:00:00 GMT\r\n"));
+    }
+
+    #[test]
+    fn chunked_bodies_are_decoded() {
+        assert_eq!(dechunk(b"5\r\nHello\r\n6\r\n World\r\n0\r\n\r\n").unwrap(), b"Hello World");
+    }
+
+    #[test]
+    fn content_type_drives_remote_fingerprint() {
+        let base = mock_server(vec![(200, "text/markdown; charset=utf-8", "---\ntitle: Remote\n---\n# Remote\n")]);
+        let target = Target { user_input: format!("{}/docs/page", base), kind: Fingerprint::RemoteUrl };
+        let report = remote_report(&target, &Config::default(), &mut vec![]).unwrap();
+
+        assert_eq!(report["kind"], "MarkdownFile");
+        assert_eq!(report["doc"]["fm"]["title"], "Remote");
+    }
+
+    #[test]
+    fn failed_remote_targets_report_network_errors() {
+        let base = mock_server(vec![(404, "text/plain", "missing")]);
+        let target = Target { user_input: format!("{}/docs/page.md", base), kind: Fingerprint::RemoteUrl };
+        let err = remote_report(&target, &Config::default(), &mut vec![]).unwrap_err();
+
+        assert!(matches!(
+            err,
+            crate::errors::ContextError::Target { ref source, .. }
+                if matches!(**source, crate::errors::ContextError::Io(IoError::NetworkError { status: Some(404), .. }))
+        ));
+    }
+
+    #[test]
+    fn plain_text_is_sniffed_and_extension_used_as_fallback() {
+        let response = |content_type: &str, body: &str| Response {
+            status: 200,
+            headers: vec![("content-type".to_string(), content_type.to_string())],
+            body: body.to_string(),
+        };
+
+        assert_eq!(remote_fingerprint("http://x/readme", &response("text/plain", "# Title\n")), Fingerprint::MarkdownFile);
+        assert_eq!(remote_fingerprint("http://x/notes", &response("text/plain", "just text")), Fingerprint::Unknown);
+        assert_eq!(remote_fingerprint("http://x/a.html", &response("application/octet-stream", "")), Fingerprint::HtmlFile);
+        assert_eq!(remote_fingerprint("http://x/a.md?x=1", &response("text/html", "")), Fingerprint::HtmlFile);
+        // the URL path is never looked up on the local filesystem
+        assert_eq!(remote_fingerprint("http://x/test", &response("application/octet-stream", "")), Fingerprint::Unknown);
+        assert_eq!(remote_fingerprint("http://x/test/data/lumberjack", &response("text/plain", "just text")), Fingerprint::Unknown);
+    }
+}
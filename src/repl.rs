@@ -0,0 +1,106 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::{Value, json};
+
+use crate::md::markdown::MarkdownDoc;
+
+/// the commands understood by the REPL along with what they show
+const COMMANDS: [(&str, &str); 7] = [
+    ("headings", "the headings with their level and line"),
+    ("links", "the links and images in the prose"),
+    ("fm [field]", "the frontmatter or -- with a field -- one of its values"),
+    ("wordcount", "the number of words in the prose"),
+    ("prose", "the prose without its frontmatter"),
+    ("help", "this list of commands"),
+    ("quit", "leave the REPL (as does end of input)"),
+];
+
+fn help() -> String {
+    COMMANDS
+        .iter()
+        .map(|(command, about)| format!("  {:<12} {}", command, about))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The REPL's response to a single line of input; `None` when the line
+/// asks to leave the REPL.
+pub fn respond(doc: &MarkdownDoc, line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let response = match (words.next(), words.next()) {
+        (None, _) => String::new(),
+        (Some("quit" | "exit"), _) => return None,
+        (Some("help"), _) => help(),
+        (Some("headings"), _) => doc.prose
+            .headings()
+            .iter()
+            .map(|h| format!("{} {} (line {})", "#".repeat(h.level as usize), h.text, h.line))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        (Some("links"), _) => doc.prose
+            .links()
+            .iter()
+            .map(|l| format!("{}: [{}]({})", l.line, l.text, l.url))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        (Some("fm"), None) => doc.fm.as_ref().map(|fm| json!(fm).to_string()).unwrap_or_else(|| "null".to_string()),
+        (Some("fm"), Some(field)) => {
+            let value = doc.fm.as_ref().map(|fm| json!(fm)).unwrap_or(Value::Null);
+            match value.get(field) {
+                Some(Value::String(text)) => text.clone(),
+                Some(value) => value.to_string(),
+                None => format!("the frontmatter does not set '{}'", field),
+            }
+        }
+        (Some("wordcount"), _) => doc.word_count.to_string(),
+        (Some("prose"), _) => doc.prose.content.trim_end().to_string(),
+        (Some(command), _) => format!("unknown command '{}'; type `help` for the list of commands", command),
+    };
+
+    Some(response)
+}
+
+/// Reads commands -- one per line -- from `input` and writes the response
+/// to each to `output` until `quit` or the end of the input. Lines are read
+/// as they are (there is no `rustyline`) so there is no line editing beyond
+/// what the terminal provides and no command history.
+pub fn run<R: BufRead, W: Write>(doc: &MarkdownDoc, input: R, mut output: W) -> io::Result<()> {
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        match respond(doc, &line?) {
+            Some(response) if response.is_empty() => {}
+            Some(response) => writeln!(output, "{}", response)?,
+            None => break,
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_inspect_the_parsed_document() {
+        let doc = MarkdownDoc::try_from("---\ntitle: Guide\n---\n# Intro\n\nSee [the docs](/docs).\n\n## Setup\n").unwrap();
+
+        assert_eq!(respond(&doc, "headings").unwrap(), "# Intro (line 1)\n## Setup (line 5)");
+        assert_eq!(respond(&doc, "links").unwrap(), "3: [the docs](/docs)");
+        assert_eq!(respond(&doc, "fm title").unwrap(), "Guide");
+        assert_eq!(respond(&doc, "  wordcount ").unwrap(), doc.word_count.to_string());
+        assert!(respond(&doc, "bogus").unwrap().starts_with("unknown command"));
+        assert_eq!(respond(&doc, "quit"), None);
+    }
+
+    #[test]
+    fn input_is_read_until_quit() {
+        let doc = MarkdownDoc::try_from("# Intro\n").unwrap();
+        let mut output: Vec<u8> = vec![];
+        run(&doc, "wordcount\n\nquit\nprose\n".as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "> 1\n> > \n");
+    }
+}
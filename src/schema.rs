@@ -0,0 +1,350 @@
+use serde_json::{Value, json};
+
+use crate::Fingerprint;
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// a JSON type which may also be `null`
+fn nullable(kind: &str) -> Value {
+    json!({ "type": [kind, "null"] })
+}
+
+fn file_meta_schema() -> Value {
+    let time = json!({
+        "type": ["object", "null"],
+        "properties": {
+            "secs_since_epoch": { "type": "integer" },
+            "nanos_since_epoch": { "type": "integer" }
+        }
+    });
+
+    json!({
+        "type": ["object", "null"],
+        "properties": {
+            "filename": { "type": "string" },
+            "file_type": {
+                "oneOf": [
+                    { "enum": ["File", "Text", "Binary", "Empty", "Directory"] },
+                    {
+                        "type": "object",
+                        "properties": { "Symlink": { "type": "object" } },
+                        "required": ["Symlink"]
+                    }
+                ]
+            },
+            "size": { "type": "integer", "minimum": 0 },
+            "modified": time,
+            "created": time,
+            "detected_encoding": { "type": "string" }
+        },
+        "required": ["filename", "file_type", "size"]
+    })
+}
+
+fn frontmatter_schema() -> Value {
+    let string_list = json!({ "type": "array", "items": { "type": "string" } });
+
+    json!({
+        "type": ["object", "null"],
+        "properties": {
+            "title": { "type": "string" },
+            "aliases": string_list,
+            "tags": string_list,
+            "description": { "type": "string" },
+            "subject": { "type": "string" },
+            "category": { "type": "string" },
+            "name": { "type": "string" },
+            "excerpt": { "type": "string" },
+            "image": { "type": "string" },
+            "icon": { "type": "string" },
+            "layout": { "type": "string" },
+            "language": { "type": "string" },
+            "requiresAuth": { "type": "boolean" },
+            "expires": { "type": "string" }
+        },
+        "additionalProperties": true
+    })
+}
+
+fn markdown_doc_schema() -> Value {
+    let string_list = json!({ "type": "array", "items": { "type": "string" } });
+    let count = json!({ "type": "integer", "minimum": 0 });
+    let line = json!({ "type": "integer", "minimum": 1 });
+
+    json!({
+        "type": "object",
+        "properties": {
+            "has_frontmatter": { "type": "boolean" },
+            "is_index": { "type": "boolean" },
+            "language": nullable("string"),
+            "all_tags": string_list,
+            "callouts": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "kind": { "type": "string" },
+                        "title": nullable("string"),
+                        "line": line
+                    },
+                    "required": ["kind", "title", "line"]
+                }
+            },
+            "word_count": count,
+            "fm": frontmatter_schema(),
+            "prose": {
+                "type": "object",
+                "properties": {
+                    "content": { "type": "string" },
+                    "hash": count
+                },
+                "required": ["content"]
+            },
+            "structure": {
+                "type": ["object", "null"],
+                "properties": {
+                    "h1": string_list,
+                    "has_multiple_h1": { "type": "boolean" },
+                    "h2": string_list,
+                    "h3": string_list
+                }
+            },
+            "file": file_meta_schema(),
+            "canonical_url": nullable("string"),
+            "stable_id": { "type": "string", "pattern": "^[0-9a-f]{16}-[0-9a-f]{8}$" },
+            "sentence_count": count,
+            "avg_words_per_sentence": { "type": "number", "minimum": 0 },
+            "preview": nullable("string"),
+            "effective_description": nullable("string"),
+            "lossy": { "const": true },
+            "definitions": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "term": { "type": "string" },
+                        "body": { "type": "string" }
+                    },
+                    "required": ["term", "body"]
+                }
+            },
+            "section_words": {
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "prefixItems": [{ "type": "string" }, count],
+                    "items": false
+                }
+            },
+            "char_count": {
+                "type": "object",
+                "properties": {
+                    "with_whitespace": count,
+                    "no_whitespace": count
+                },
+                "required": ["with_whitespace", "no_whitespace"]
+            },
+            "readability": {
+                "type": "object",
+                "properties": { "flesch_kincaid": { "type": "number" } },
+                "required": ["flesch_kincaid"]
+            },
+            "reading_grade": { "type": "number" },
+            "grade_level": { "enum": ["elementary", "middle-school", "high-school", "college", "graduate"] },
+            "empty_prose": {
+                "type": "object",
+                "properties": {
+                    "words": count,
+                    "min_words": count
+                },
+                "required": ["words", "min_words"]
+            },
+            "html_blocks": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "tag": { "type": "string" },
+                        "line": line
+                    },
+                    "required": ["tag", "line"]
+                }
+            },
+            "heading_jumps": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "integer", "minimum": 1, "maximum": 6 },
+                        "to": { "type": "integer", "minimum": 1, "maximum": 6 },
+                        "line": line
+                    },
+                    "required": ["from", "to", "line"]
+                }
+            },
+            "links": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "text": { "type": "string" },
+                        "url": { "type": "string" },
+                        "is_image": { "type": "boolean" },
+                        "is_wiki": { "type": "boolean" },
+                        "line": line
+                    },
+                    "required": ["text", "url", "is_image", "is_wiki", "line"]
+                }
+            },
+            "emoji_count": count,
+            "emoji_threshold_exceeded": { "type": "boolean" },
+            "emoji": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "kind": { "enum": ["shortcode", "unicode"] },
+                        "value": { "type": "string" },
+                        "line": line
+                    },
+                    "required": ["kind", "value", "line"]
+                }
+            }
+        },
+        "required": ["has_frontmatter", "is_index", "all_tags", "callouts", "word_count", "prose"]
+    })
+}
+
+fn html_doc_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "language": nullable("string"),
+            "fm": frontmatter_schema(),
+            "file": file_meta_schema()
+        },
+        "required": ["language", "fm", "file"]
+    })
+}
+
+fn skipped_doc_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "file": nullable("string"),
+            "skipped": { "const": true },
+            "reason": { "type": "string" }
+        },
+        "required": ["file", "skipped", "reason"]
+    })
+}
+
+/// Provides the JSON Schema (draft 2020-12) which describes the output
+/// produced for a target with the given `Fingerprint`.
+pub fn json_schema_for(fp: &Fingerprint) -> Value {
+    match fp {
+        Fingerprint::MarkdownFile => json!({
+            "$schema": SCHEMA_DIALECT,
+            "title": "MarkdownFile",
+            "oneOf": [markdown_doc_schema(), skipped_doc_schema()]
+        }),
+        Fingerprint::HtmlFile => {
+            let mut schema = html_doc_schema();
+            schema["$schema"] = json!(SCHEMA_DIALECT);
+            schema["title"] = json!("HtmlFile");
+            schema
+        }
+        Fingerprint::SourceFile => {
+            let mut doc = markdown_doc_schema();
+            doc["properties"]["source_language"] = json!({ "enum": ["rust", "python", "javascript"] });
+            json!({
+                "$schema": SCHEMA_DIALECT,
+                "title": "SourceFile",
+                "description": "the markdown held in the doc comments of a source file",
+                "oneOf": [doc, skipped_doc_schema()]
+            })
+        }
+        Fingerprint::JupyterNotebook => json!({
+            "$schema": SCHEMA_DIALECT,
+            "title": "JupyterNotebook",
+            "type": "object",
+            "properties": {
+                "cell_count": { "type": "integer", "minimum": 0 },
+                "markdown_cells": { "type": "array", "items": { "type": "string" } },
+                "code_cells": { "type": "array", "items": { "type": "string" } },
+                "language": nullable("string")
+            },
+            "required": ["cell_count", "markdown_cells", "code_cells", "language"]
+        }),
+        Fingerprint::RemoteUrl => json!({
+            "$schema": SCHEMA_DIALECT,
+            "title": "RemoteUrl",
+            "type": "object",
+            "properties": {
+                "url": { "type": "string" },
+                "status": { "type": "integer" },
+                "content_type": nullable("string"),
+                "kind": { "enum": ["MarkdownFile", "HtmlFile", "Unknown"] },
+                "doc": { "oneOf": [markdown_doc_schema(), skipped_doc_schema(), html_doc_schema(), { "type": "null" }] }
+            },
+            "required": ["url", "status", "content_type", "kind", "doc"]
+        }),
+        Fingerprint::Directory => json!({
+            "$schema": SCHEMA_DIALECT,
+            "title": "Directory",
+            "type": "array",
+            "items": { "oneOf": [markdown_doc_schema(), skipped_doc_schema()] }
+        }),
+        Fingerprint::Unknown => json!({
+            "$schema": SCHEMA_DIALECT,
+            "title": "Unknown",
+            "description": "unknown targets are ignored and produce no output",
+            "not": {}
+        }),
+    }
+}
+
+/// the JSON Schema of every `Fingerprint`, keyed by the fingerprint's name
+pub fn json_schemas() -> Value {
+    let fingerprints = [
+        Fingerprint::MarkdownFile,
+        Fingerprint::HtmlFile,
+        Fingerprint::SourceFile,
+        Fingerprint::JupyterNotebook,
+        Fingerprint::RemoteUrl,
+        Fingerprint::Directory,
+        Fingerprint::Unknown,
+    ];
+
+    Value::Object(
+        fingerprints
+            .iter()
+            .map(|fp| (json!(fp).as_str().unwrap_or_default().to_string(), json_schema_for(fp)))
+            .collect()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::markdown::MarkdownDoc;
+
+    #[test]
+    fn markdown_schema_lists_every_doc_field() {
+        let schema = json_schema_for(&Fingerprint::MarkdownFile);
+        let properties = &schema["oneOf"][0]["properties"];
+        let doc = json!(MarkdownDoc::try_from("---\ntitle: hi\n---\n# Hi\n").unwrap());
+
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+        for key in doc.as_object().unwrap().keys() {
+            assert!(properties.get(key).is_some(), "schema is missing '{}'", key);
+        }
+    }
+
+    #[test]
+    fn schemas_are_keyed_by_fingerprint() {
+        let schemas = json_schemas();
+        assert!(schemas.get("MarkdownFile").is_some());
+        assert!(schemas.get("Directory").is_some());
+    }
+}
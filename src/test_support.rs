@@ -0,0 +1,40 @@
+use std::sync::Once;
+
+use tracing::{info, Level};
+
+static TRACING: Once = Once::new();
+
+/// Installs a tracing subscriber for tests; it is safe to call from any
+/// number of tests -- including concurrently -- as only the first call
+/// installs a subscriber (and so decides the level).
+pub fn init_tracing_once(lvl: Level) {
+    TRACING.call_once(|| {
+        let collector = tracing_subscriber::fmt()
+            // filter spans/events with the given level or higher.
+            .with_max_level(lvl)
+            .pretty()
+            // build but do not install the subscriber.
+            .finish();
+
+        // another subscriber may have been installed outside of this helper
+        if tracing::subscriber::set_global_default(collector).is_ok() {
+            info!("Tracing for tests enabled");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracing_can_be_initialized_repeatedly_and_concurrently() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| init_tracing_once(Level::WARN)))
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+
+        init_tracing_once(Level::TRACE);
+        assert!(TRACING.is_completed());
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Display;
+
+use serde::{Serialize, Deserialize};
+
+/// the message of the warning raised for a target which was not recognized
+const UNRECOGNIZED: &str = "was not recognized and will be ignored!";
+/// the start of the message of the warning raised for a raw HTML block
+const RAW_HTML: &str = "has a raw HTML block";
+
+/// The kind of a `Warning`, which decides whether flags such as
+/// `--fail-on-unknown` treat it as a failure
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    /// a target which was not recognized
+    Unrecognized,
+    /// a raw HTML block in the prose of a document
+    RawHtml,
+    #[default]
+    Other,
+}
+
+/// A non-fatal problem found while processing a target (e.g., an unknown
+/// target or a lint issue). Warnings are collected as processing proceeds
+/// so that a decision on how to treat them can be made in one place.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Warning {
+    /// the target (or file within a target) the warning relates to
+    pub target: String,
+    pub message: String,
+    #[serde(default)]
+    pub kind: WarningKind,
+}
+
+impl Warning {
+    pub fn new(target: &str, message: &str) -> Self {
+        Warning::with_kind(target, message, WarningKind::Other)
+    }
+
+    fn with_kind(target: &str, message: &str, kind: WarningKind) -> Self {
+        Warning {
+            target: target.to_string(),
+            message: message.to_string(),
+            kind,
+        }
+    }
+
+    /// the warning for a target whose fingerprint was not recognized
+    pub fn unrecognized(target: &str) -> Self {
+        Warning::with_kind(target, UNRECOGNIZED, WarningKind::Unrecognized)
+    }
+
+    /// the warning for a raw HTML block opened with `tag` on `line`
+    pub fn raw_html(target: &str, tag: &str, line: usize) -> Self {
+        Warning::with_kind(
+            target,
+            &format!("{} (<{}>) on line {}", RAW_HTML, tag, line),
+            WarningKind::RawHtml
+        )
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "- '{}' {}", self.target, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_is_set_where_the_warning_is_created() {
+        let unrecognized = Warning::unrecognized("notes.xyz");
+        let reworded = Warning { message: "is a mystery".to_string(), ..unrecognized.clone() };
+
+        assert_eq!(unrecognized.kind, WarningKind::Unrecognized);
+        assert_eq!(reworded.kind, WarningKind::Unrecognized);
+        assert_eq!(Warning::new("notes.xyz", UNRECOGNIZED).kind, WarningKind::Other);
+        assert_eq!(Warning::raw_html("page.md", "div", 3).kind, WarningKind::RawHtml);
+        assert_eq!(Warning::new("page.md", RAW_HTML).kind, WarningKind::Other);
+    }
+}
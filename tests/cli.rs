@@ -0,0 +1,204 @@
+use std::process::Command;
+
+fn ctx() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_ctx"))
+}
+
+#[test]
+fn unknown_target_fails_in_strict_mode() {
+    let output = ctx().args(["--strict", "not-a-known-target"]).output().unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn unknown_target_succeeds_without_strict_mode() {
+    let output = ctx().args(["not-a-known-target"]).output().unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn unknown_file_fails_with_fail_on_unknown() {
+    let file = std::env::temp_dir().join(format!("ctx-unknown-{}.xyz", std::process::id()));
+    std::fs::write(&file, "not something ctx recognizes").unwrap();
+    let output = ctx().args(["--fail-on-unknown", file.to_str().unwrap()]).output().unwrap();
+    let known = ctx().args(["--fail-on-unknown", "test/data/lumberjack.md"]).output().unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    assert!(!output.status.success());
+    assert!(known.status.success());
+}
+
+#[test]
+fn meta_only_output_excludes_content_and_hash() {
+    let output = ctx().args(["--meta-only", "test/data/lumberjack.md"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let meta = &json[0];
+
+    assert_eq!(meta["filename"], "test/data/lumberjack.md");
+    assert!(meta.get("size").is_some());
+    assert!(meta.get("content").is_none());
+    assert!(meta.get("hash").is_none());
+    assert!(!stdout.contains("prose"));
+}
+
+#[cfg(unix)]
+#[test]
+fn meta_only_does_not_read_file_content() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file = std::env::temp_dir().join(format!("ctx-meta-perm-{}.md", std::process::id()));
+    std::fs::write(&file, "# Secret\n").unwrap();
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o000)).unwrap();
+    let output = ctx().args(["--meta-only", file.to_str().unwrap()]).output().unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["filename"], file.to_str().unwrap());
+    assert_eq!(json[0]["file_type"], "File");
+    assert_eq!(json[0]["size"], 9);
+}
+
+#[test]
+fn print_config_shows_cli_override_and_source() {
+    let output = ctx().args(["--print-config", "--draft-field", "wip"]).output().unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(json["config"]["draft_field"], "wip");
+    assert_eq!(json["sources"]["draft_field"], "cli");
+    assert_eq!(json["config"]["strict"], false);
+}
+
+#[test]
+fn as_flag_forces_extensionless_file_to_markdown() {
+    let file = std::env::temp_dir().join(format!("ctx-as-{}", std::process::id()));
+    std::fs::write(&file, "Just a few plain words\n").unwrap();
+    let path = file.to_string_lossy().to_string();
+
+    let forced = ctx().args(["--as", "markdown", &path]).output().unwrap();
+    let sniffed = ctx().args([&path]).output().unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&forced.stdout).unwrap();
+    assert_eq!(json[0]["word_count"], 5);
+    assert_eq!(String::from_utf8(sniffed.stdout).unwrap().trim(), "[]");
+}
+
+#[test]
+fn get_prints_a_frontmatter_field() {
+    let output = ctx().args(["get", "title", "test/data/sections.md"]).output().unwrap();
+    let missing = ctx().args(["get", "--required", "title", "test/data/lumberjack.md"]).output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "Field Guide\n");
+    assert!(!missing.status.success());
+    assert!(missing.stdout.is_empty());
+}
+
+#[test]
+fn set_writes_a_frontmatter_field() {
+    let file = std::env::temp_dir().join(format!("ctx-set-{}.md", std::process::id()));
+    std::fs::write(&file, "---\ntitle: Set Me\n---\n# Body\n").unwrap();
+    let path = file.to_string_lossy().to_string();
+
+    let dry_run = ctx().args(["set", "--dry-run", "layout", "post", &path]).output().unwrap();
+    let unchanged = std::fs::read_to_string(&file).unwrap();
+    let output = ctx().args(["set", "layout", "post", &path]).output().unwrap();
+    let content = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    assert!(String::from_utf8(dry_run.stdout).unwrap().contains("layout: post"));
+    assert!(!unchanged.contains("layout"));
+    assert!(output.status.success());
+    assert_eq!(content, "---\ntitle: Set Me\nlayout: post\n---\n# Body\n");
+}
+
+#[test]
+fn unset_removes_a_frontmatter_field() {
+    let file = std::env::temp_dir().join(format!("ctx-unset-{}.md", std::process::id()));
+    std::fs::write(&file, "---\ntitle: Unset Me\nlegacy_id: 7\n---\n# Body\n").unwrap();
+    let path = file.to_string_lossy().to_string();
+
+    let output = ctx().args(["unset", "legacy_id", &path]).output().unwrap();
+    let missing = ctx().args(["unset", "legacy_id", &path]).output().unwrap();
+    let content = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    assert!(output.status.success());
+    assert!(missing.status.success());
+    assert_eq!(content, "---\ntitle: Unset Me\n---\n# Body\n");
+}
+
+#[test]
+fn progress_is_never_written_to_stdout() {
+    let output = ctx().args(["test/data/lumberjack.md", "test/data/sections.md"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(json.as_array().map(|a| a.len()), Some(2));
+    assert!(!stdout.contains('\r'));
+    assert!(!stdout.contains("2/2"));
+}
+
+#[test]
+fn sidecar_is_written_next_to_the_source() {
+    let dir = std::env::temp_dir().join(format!("ctx-sidecar-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("doc.md");
+    std::fs::write(&source, "---\ntitle: Sidecar\n---\n# Hello\n").unwrap();
+    let output = ctx().args(["--sidecar", source.to_str().unwrap()]).output().unwrap();
+    let sidecar = std::fs::read_to_string(dir.join("doc.md.ctx.json"));
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let json: serde_json::Value = serde_json::from_str(&sidecar.unwrap()).unwrap();
+    assert_eq!(json["fm"]["title"], "Sidecar");
+}
+
+#[test]
+fn lint_reports_issues_and_fails() {
+    let dir = std::env::temp_dir().join(format!("ctx-lint-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("bad.md"), "# Title\n\n### Skipped\n\nSee [here](missing.md).\n").unwrap();
+    let output = ctx().args(["lint", dir.to_str().unwrap()]).output().unwrap();
+    let selected = ctx().args(["lint", "--lint-select", "empty-sections", dir.to_str().unwrap()]).output().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["counts"]["heading-hierarchy"], 1);
+    assert_eq!(json["counts"]["poor-link-text"], 1);
+    assert_eq!(json["counts"]["broken-links"], 1);
+    assert!(selected.status.success());
+}
+
+#[test]
+fn compare_fails_when_a_file_cannot_be_loaded() {
+    let output = ctx().args(["--compare", "test/data/lumberjack.md", "does-not-exist.md"]).output().unwrap();
+    let same = ctx().args(["--compare", "test/data/lumberjack.md", "test/data/lumberjack.md"]).output().unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(same.status.success());
+}
+
+#[test]
+fn https_targets_are_rejected_as_unrecognized() {
+    let output = ctx().args(["--fail-on-unknown", "https://example.com/docs/page.md"]).output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("'https://example.com/docs/page.md' was not recognized"));
+}
+
+#[test]
+fn max_depth_applies_before_the_target_limit() {
+    let limited = ["--fingerprint-only", "--max-targets", "1", "test", "test/data/lumberjack.md"];
+    let deep = ctx().args(limited).output().unwrap();
+    let shallow = ctx().args(limited).args(["--max-depth", "0"]).output().unwrap();
+
+    assert!(!deep.status.success());
+    assert!(shallow.status.success());
+}